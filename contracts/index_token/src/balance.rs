@@ -1,4 +1,4 @@
-use crate::storage::{save_last_transfer, DataKey, LastTransfer};
+use crate::storage::DataKey;
 use normal::constants::{BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
 use soroban_sdk::{Address, Env};
 
@@ -17,19 +17,11 @@ pub fn read_balance(env: &Env, addr: Address) -> i128 {
 }
 
 fn write_balance(env: &Env, addr: Address, amount: i128) {
-    let key = DataKey::Balance(addr.clone());
+    let key = DataKey::Balance(addr);
     env.storage().persistent().set(&key, &amount);
     env.storage()
         .persistent()
         .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
-
-    // Updating the <addr> last transfer info for fee tracking
-    // let last_transfer = get_last_transfer(env, &addr);
-    let updated_last_transfer = LastTransfer {
-        ts: env.ledger().timestamp(),
-        balance: amount,
-    };
-    save_last_transfer(env, &addr, &updated_last_transfer);
 }
 
 pub fn receive_balance(env: &Env, addr: Address, amount: i128) {
@@ -1,17 +1,25 @@
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
 use crate::balance::{read_balance, receive_balance, spend_balance};
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
-use crate::msg::IndexResponse;
-use crate::storage::{get_index, get_last_transfer, Swap, TransferWithFees, USD, XLM};
+use crate::msg::{AccruedFeesResponse, IndexResponse};
+use crate::storage::{
+    get_index, get_rebalance_result, save_rebalance_result, ComponentWeight, RebalanceResult, Swap,
+    USD, XLM,
+};
 
 use normal::error::{ErrorCode, NormalResult};
 use normal::math::casting::Cast;
+use normal::math::helpers::{annualize_fee_amount, sqrt_price_to_price, standardize_token_amount};
 use normal::math::safe_math::SafeMath;
-use normal::types::{IndexAsset, IndexParams};
+use normal::types::{
+    index::{IndexAccessMode, IndexAsset, IndexParams, IndexPriceSource, RebalanceStrategy},
+    market::MarketPriceData,
+};
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
 use soroban_sdk::token::{self, Interface as _};
 use soroban_sdk::{
-    contract, contractimpl, contractmeta, log, panic_with_error, vec, Address, Env, Map, String,
-    Symbol, Vec,
+    contract, contractimpl, contractmeta, log, panic_with_error, vec, Address, Env, IntoVal, Map,
+    String, Symbol, Vec,
 };
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
@@ -22,10 +30,20 @@ use crate::{
     index_token::IndexTokenTrait,
     storage::{save_index, utils, Index, IndexOperation},
 };
-use normal::oracle::get_oracle_price;
+use normal::math::oracle::{oracle_validity, validate_oracle_for_action, NormalAction};
+use normal::oracle::{
+    get_oracle_price, get_oracle_price_with_feeds, HistoricalOracleData, OracleFeed,
+    OracleGuardRails,
+};
 
 use normal::{
-    constants::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, SECONDS_IN_A_YEAR},
+    constants::{
+        BPS_DENOMINATOR, DEFAULT_INDEX_DRIFT_TOLERANCE, DEFAULT_INDEX_MAX_SWAP_SLIPPAGE,
+        DEFAULT_INDEX_MAX_TURNOVER, DEFAULT_INDEX_MAX_WEIGHT_CHANGE,
+        DEFAULT_INDEX_PERFORMANCE_FEE_PERIOD, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD,
+        ONE_HOUR, PERCENTAGE_PRECISION_I128, PRICE_PRECISION, PRICE_PRECISION_I128,
+        SPOT_BALANCE_PRECISION_EXP,
+    },
     validate_bps,
 };
 
@@ -58,12 +76,23 @@ impl IndexTokenTrait for IndexToken {
             panic_with_error!(&env, ErrorCode::AlreadyInitialized);
         }
 
+        validate_access_mode(&env, params.is_public, params.access_mode);
+
         let now = env.ledger().timestamp();
         let oracle = params.oracle;
         let oracle_source = params.oracle_source;
+        let oracle_feeds = params.oracle_feeds;
 
         // Verify oracle is readable
-        let oracle_price_data = get_oracle_price(&env, &oracle_source, &oracle, (XLM, USD), now)?;
+        let oracle_price_data = get_oracle_price_with_feeds(
+            &env,
+            &oracle_source,
+            &oracle,
+            &oracle_feeds,
+            (XLM, USD),
+            now,
+            &OracleGuardRails::default().validity,
+        );
 
         validate_bps!(params.manager_fee_bps);
 
@@ -79,24 +108,46 @@ impl IndexTokenTrait for IndexToken {
             },
         );
 
-        let base_nav =
-            initial_deposit.safe_mul(oracle_price_data.price.cast::<i128>(&env)?, &env)?;
+        // USD value of the deposit, in the same units as `calculate_current_nav`
+        let base_nav = initial_deposit
+            .safe_mul(oracle_price_data.price.cast::<i128>(&env), &env)
+            .safe_div(PRICE_PRECISION_I128, &env);
 
         let index = Index {
             quote_token: params.quote_token.clone(),
             oracle: oracle.clone(),
             oracle_source,
+            oracle_feeds,
+            historical_oracle_data: HistoricalOracleData::default_with_current_oracle(
+                oracle_price_data,
+            ),
+            price_source: params.price_source,
+            max_price_staleness: params.max_price_staleness,
             is_public: params.is_public,
+            access_mode: params.access_mode,
             paused_operations: Vec::new(&env),
             manager_fee_bps: params.manager_fee_bps,
+            fee_accrual_ts: now,
+            performance_fee_bps: 0,
+            performance_fee_period: DEFAULT_INDEX_PERFORMANCE_FEE_PERIOD,
+            high_water_mark: params.initial_price,
+            performance_fee_ts: now,
+            keepers: Vec::new(&env),
             whitelist: params.whitelist,
             blacklist: params.blacklist,
+            authorized_participants: Vec::new(&env),
             base_nav,
             initial_price: params.initial_price,
             component_balances: Map::new(&env),
             component_balance_update_ts: now,
             component_assets: params.component_assets,
             rebalance_threshold: params.rebalance_threshold,
+            rebalance_strategy: RebalanceStrategy::FixedWeights,
+            drift_tolerance: DEFAULT_INDEX_DRIFT_TOLERANCE,
+            keeper_reward: 0,
+            max_weight_change: DEFAULT_INDEX_MAX_WEIGHT_CHANGE,
+            max_turnover: DEFAULT_INDEX_MAX_TURNOVER,
+            max_swap_slippage: DEFAULT_INDEX_MAX_SWAP_SLIPPAGE,
             rebalance_ts: now,
             last_updated_ts: now,
             total_fees: 0,
@@ -106,7 +157,9 @@ impl IndexTokenTrait for IndexToken {
 
         save_index(&env, index);
 
-        let initial_mint_amount = base_nav.safe_div(params.initial_price, &env)?;
+        let initial_mint_amount = base_nav
+            .safe_mul(PRICE_PRECISION_I128, &env)
+            .safe_div(params.initial_price, &env);
         let _ = Self::mint(env, admin, initial_mint_amount);
 
         // IndexTokenEvents::initialize(&env, admin, name, symbol);
@@ -122,6 +175,7 @@ impl IndexTokenTrait for IndexToken {
         utils::is_admin(&env, sender);
 
         let mut index = get_index(&env);
+        accrue_fees(&env, &mut index, env.ledger().timestamp());
 
         index.manager_fee_bps = manager_fee_bps;
 
@@ -165,48 +219,137 @@ impl IndexTokenTrait for IndexToken {
         );
     }
 
+    fn update_access_mode(env: Env, sender: Address, access_mode: IndexAccessMode) {
+        sender.require_auth();
+
+        let index = get_index(&env);
+
+        if index.is_public {
+            utils::is_governor(&env, sender);
+        } else {
+            utils::is_admin(&env, sender);
+        }
+
+        validate_access_mode(&env, index.is_public, access_mode);
+
+        save_index(
+            &env,
+            Index {
+                access_mode,
+                ..index
+            },
+        );
+    }
+
     fn update_whitelist(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>) {
         sender.require_auth();
-        utils::is_admin(&env, sender);
+        utils::is_admin(&env, sender.clone());
 
         let index: Index = get_index(&env);
         let mut whitelist = index.whitelist;
+        let mut added: Vec<Address> = Vec::new(&env);
+        let mut removed: Vec<Address> = Vec::new(&env);
 
         to_add.into_iter().for_each(|addr| {
             if !whitelist.contains(addr.clone()) {
-                whitelist.push_back(addr);
+                whitelist.push_back(addr.clone());
+                added.push_back(addr);
             }
         });
 
         to_remove.into_iter().for_each(|addr| {
             if let Some(id) = whitelist.iter().position(|x| x == addr) {
                 whitelist.remove(id as u32);
+                removed.push_back(addr);
             }
         });
 
         save_index(&env, Index { whitelist, ..index });
+
+        IndexTokenEvents::update_whitelist(&env, sender, added, removed);
     }
 
     fn update_blacklist(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>) {
         sender.require_auth();
-        utils::is_admin(&env, sender);
+        utils::is_admin(&env, sender.clone());
 
         let index: Index = get_index(&env);
         let mut blacklist = index.blacklist;
+        let mut added: Vec<Address> = Vec::new(&env);
+        let mut removed: Vec<Address> = Vec::new(&env);
 
         to_add.into_iter().for_each(|addr| {
             if !blacklist.contains(addr.clone()) {
-                blacklist.push_back(addr);
+                blacklist.push_back(addr.clone());
+                added.push_back(addr);
             }
         });
 
         to_remove.into_iter().for_each(|addr| {
             if let Some(id) = blacklist.iter().position(|x| x == addr) {
                 blacklist.remove(id as u32);
+                removed.push_back(addr);
             }
         });
 
         save_index(&env, Index { blacklist, ..index });
+
+        IndexTokenEvents::update_blacklist(&env, sender, added, removed);
+    }
+
+    fn update_keepers(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>) {
+        sender.require_auth();
+        utils::is_admin(&env, sender);
+
+        let index: Index = get_index(&env);
+        let mut keepers = index.keepers;
+
+        to_add.into_iter().for_each(|addr| {
+            if !keepers.contains(addr.clone()) {
+                keepers.push_back(addr);
+            }
+        });
+
+        to_remove.into_iter().for_each(|addr| {
+            if let Some(id) = keepers.iter().position(|x| x == addr) {
+                keepers.remove(id as u32);
+            }
+        });
+
+        save_index(&env, Index { keepers, ..index });
+    }
+
+    fn update_authorized_participants(
+        env: Env,
+        sender: Address,
+        to_add: Vec<Address>,
+        to_remove: Vec<Address>,
+    ) {
+        sender.require_auth();
+        utils::is_admin(&env, sender);
+
+        let index: Index = get_index(&env);
+        let mut authorized_participants = index.authorized_participants;
+
+        to_add.into_iter().for_each(|addr| {
+            if !authorized_participants.contains(addr.clone()) {
+                authorized_participants.push_back(addr);
+            }
+        });
+
+        to_remove.into_iter().for_each(|addr| {
+            if let Some(id) = authorized_participants.iter().position(|x| x == addr) {
+                authorized_participants.remove(id as u32);
+            }
+        });
+
+        save_index(
+            &env,
+            Index {
+                authorized_participants,
+                ..index
+            },
+        );
     }
 
     fn update_rebalance_threshold(env: Env, sender: Address, rebalance_threshold: u64) {
@@ -228,271 +371,1228 @@ impl IndexTokenTrait for IndexToken {
         // });
     }
 
-    // ################################################################
-    //                             KEEPER
-    // ################################################################
-
-    fn rebalance(env: Env, sender: Address, updated_assets: Vec<IndexAsset>) {
+    fn update_rebalance_strategy(
+        env: Env,
+        sender: Address,
+        rebalance_strategy: RebalanceStrategy,
+        drift_tolerance: i128,
+        keeper_reward: i128,
+    ) {
         sender.require_auth();
 
         let index = get_index(&env);
 
         if index.is_public {
-            utils::is_governor(&env, sender.clone());
-            // TODO: weight change guardrails to avoid massive spikes in price
+            utils::is_governor(&env, sender);
         } else {
-            utils::is_admin(&env, sender.clone());
+            utils::is_admin(&env, sender);
         }
 
-        let now = env.ledger().timestamp();
-        if !index.can_rebalance(now) {
-            panic_with_error!(&env, ErrorCode::TooSoonToRebalance);
+        if !(0..=PERCENTAGE_PRECISION_I128).contains(&drift_tolerance) || keeper_reward < 0 {
+            log!(
+                &env,
+                "Index Token: invalid drift tolerance {} or keeper reward {}",
+                drift_tolerance,
+                keeper_reward
+            );
+            panic_with_error!(&env, ErrorCode::InvalidRebalanceParams);
         }
 
-        // TODO: validate updated asset markets
-
-        let position_increases: Vec<Swap> = Vec::new(&env);
-        let position_reductions: Vec<Swap> = Vec::new(&env);
-
-        // updated_assets.iter().for_each(|updated_asset| {
-        //     let current_asset = index.component_assets
-        //         .iter()
-        //         .find(|current_asset| current_asset.market == updated_asset.market)
-        //         .cloned();
-
-        //     match current_asset {
-        //         Some(current_asset) => {
-        //             let percent_delta = updated_asset.weight - current_asset.weight;
-        //             let amount_delta = 0;
-
-        //             if delta > 0 {
-        //                 position_increases.push(Swap {
-        //                     ask_asset: updated_asset.market, // Buy the asset
-        //                     offer_asset: index.quote_asset,
-        //                     ask_asset_min_amount: amount_delta,
-        //                 })
-        //             } else {
-        //                 position_reductions.push(Swap {
-        //                     ask_asset: index.quote_asset,
-        //                     offer_asset: updated_asset.market, // Sell the asset
-        //                     ask_asset_min_amount: amount_delta,
-        //                 })
-        //             }
-        //         }
-        //         None => {
-        //             let amount_delta = 0;
-
-        //             position_increases.push(Swap {
-        //                 ask_asset: updated_asset.market, // Buy the asset
-        //                 offer_asset: index.quote_asset,
-        //                 ask_asset_min_amount: amount_delta,
-        //             })
-        //         }
-        //     }
-        // });
-
-        swap_and_update_component_balances(&env, position_increases, &index);
-        swap_and_update_component_balances(&env, position_reductions, &index);
-
         save_index(
             &env,
             Index {
-                component_balances: Map::new(&env), // TODO:
-                component_assets: Vec::new(&env),   // TODO:
-                rebalance_ts: now,
-                last_updated_ts: now,
+                rebalance_strategy,
+                drift_tolerance,
+                keeper_reward,
                 ..index
             },
         );
-
-        IndexTokenEvents::rebalance(&env, sender, updated_assets);
     }
 
-    // ################################################################
-    //                             USER
-    // ################################################################
-
-    fn mint(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
-        utils::check_nonnegative_amount(index_token_amount);
+    fn update_performance_fee(
+        env: Env,
+        sender: Address,
+        performance_fee_bps: i64,
+        performance_fee_period: u64,
+    ) {
         sender.require_auth();
 
-        let now = env.ledger().timestamp();
-
-        // Get index and price
-        let index = get_index(&env);
-        if !index.can_invest(sender.clone()) {
-            panic_with_error!(&env, ErrorCode::AdminNotSet);
+        validate_bps!(performance_fee_bps);
+        if performance_fee_period == 0 {
+            log!(&env, "Index Token: performance fee period must be positive");
+            panic_with_error!(&env, ErrorCode::InvalidPerformanceFeePeriod);
         }
 
-        let index_price = get_index_price(&env, &index)?;
+        utils::is_admin(&env, sender);
 
-        // Compute amount of quote asset needed
-        let (quote_token_amount, _price) = convert_index_token_amount_to_quote_amount(
-            &env,
-            &index,
-            index_token_amount,
-            index_price,
-            now,
-        )?;
+        // Gains made under the current rate are charged at the current rate
+        let mut index = get_index(&env);
+        charge_performance_fee(&env, &mut index, env.ledger().timestamp());
 
-        // Deposit initial investment
-        utils::transfer_token(
+        save_index(
             &env,
-            &index.quote_token,
-            &sender,
-            &env.current_contract_address(),
-            quote_token_amount,
+            Index {
+                performance_fee_bps,
+                performance_fee_period,
+                ..index
+            },
         );
+    }
 
-        // Compute asset amounts / swaps
-        let _operations: Vec<Swap> = Vec::new(&env);
-
-        // TODO:
-        // for (k, v) in index.component_assets.iter() {
-        //     //
-        //     let amount = 0;
+    fn update_rebalance_guardrails(
+        env: Env,
+        sender: Address,
+        max_weight_change: i128,
+        max_turnover: i128,
+    ) {
+        sender.require_auth();
 
-        //     let swap = Swap {
-        //         ask_asset: &asset.market_address,
-        //         offer_asset: "XLM",
-        //         ask_asset_min_amount: &amount,
-        //     };
+        let index = get_index(&env);
 
-        //     operations.push_back(swap);
-        // };
+        if index.is_public {
+            utils::is_governor(&env, sender);
+        } else {
+            utils::is_admin(&env, sender);
+        }
 
-        // swap_and_update_component_balances(&env, operations, index);
+        if !(0..=PERCENTAGE_PRECISION_I128).contains(&max_weight_change)
+            || !(0..=PERCENTAGE_PRECISION_I128).contains(&max_turnover)
+        {
+            log!(
+                &env,
+                "Index Token: invalid max weight change {} or max turnover {}",
+                max_weight_change,
+                max_turnover
+            );
+            panic_with_error!(&env, ErrorCode::InvalidRebalanceParams);
+        }
 
-        // Mint index tokens
-        receive_balance(&env, sender.clone(), index_token_amount);
-        TokenUtils::new(&env).events().mint(
-            utils::get_admin(&env),
-            sender.clone(),
-            index_token_amount,
+        save_index(
+            &env,
+            Index {
+                max_weight_change,
+                max_turnover,
+                ..index
+            },
         );
-
-        IndexTokenEvents::mint(&env, sender, index_token_amount);
-
-        Ok(())
     }
 
-    fn redeem(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
-        utils::check_nonnegative_amount(index_token_amount);
+    fn update_max_swap_slippage(env: Env, sender: Address, max_swap_slippage: i128) {
         sender.require_auth();
 
-        let now = env.ledger().timestamp();
-
-        // Get index and price
         let index = get_index(&env);
-        let index_price = get_index_price(&env, &index)?;
-
-        // Compute amount of quote asset needed
-        let (quote_token_amount, _price) = convert_index_token_amount_to_quote_amount(
-            &env,
-            &index,
-            index_token_amount,
-            index_price,
-            now,
-        )?;
 
-        // Ensure sufficient quote funds
-        let balance = utils::get_token_balance(&env, &index.quote_token, &sender);
-        if balance < quote_token_amount {
-            panic_with_error!(&env, ErrorCode::InsufficientFunds);
+        if index.is_public {
+            utils::is_governor(&env, sender);
+        } else {
+            utils::is_admin(&env, sender);
         }
 
-        // Compute asset amounts / swaps
-        let _operations: Vec<Swap> = Vec::new(&env);
+        if !(0..=PERCENTAGE_PRECISION_I128).contains(&max_swap_slippage) {
+            log!(
+                &env,
+                "Index Token: invalid max swap slippage {}",
+                max_swap_slippage
+            );
+            panic_with_error!(&env, ErrorCode::InvalidRebalanceParams);
+        }
 
-        // index.component_assets.iter().for_each(|asset| {
-        //     let amount = 0;
+        save_index(
+            &env,
+            Index {
+                max_swap_slippage,
+                ..index
+            },
+        );
+    }
 
-        //     let swap = Swap {
-        //         ask_asset: &asset.market_address,
-        //         offer_asset: "XLM",
-        //         ask_asset_min_amount: &amount,
-        //     };
+    fn update_oracle_feeds(env: Env, sender: Address, oracle_feeds: Vec<OracleFeed>) {
+        sender.require_auth();
 
-        //     operations.push_back(swap);
-        // });
+        let index = get_index(&env);
 
-        // swap_and_update_component_balances(&env, operations, index);
+        if index.is_public {
+            utils::is_governor(&env, sender);
+        } else {
+            utils::is_admin(&env, sender);
+        }
 
-        utils::transfer_token(
+        save_index(
             &env,
-            &index.quote_token,
-            &env.current_contract_address(),
-            &sender,
-            quote_token_amount,
+            Index {
+                oracle_feeds,
+                ..index
+            },
         );
+    }
+
+    fn update_price_source(
+        env: Env,
+        sender: Address,
+        price_source: IndexPriceSource,
+        max_price_staleness: u64,
+    ) {
+        sender.require_auth();
 
-        // IndexTokenEvents::redeem(&env, sender, index_token_amount);
+        let index = get_index(&env);
 
-        // Burn index tokens
-        Self::burn(env, sender.clone(), index_token_amount);
+        if index.is_public {
+            utils::is_governor(&env, sender);
+        } else {
+            utils::is_admin(&env, sender);
+        }
 
-        Ok(())
+        save_index(
+            &env,
+            Index {
+                price_source,
+                max_price_staleness,
+                ..index
+            },
+        );
     }
 
     // ################################################################
-    //                             QUERIES
+    //                             KEEPER
     // ################################################################
 
-    fn query_index(env: Env) -> IndexResponse {
-        env.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        IndexResponse {
-            index: get_index(&env),
-        }
-    }
+    fn rebalance(env: Env, sender: Address, updated_assets: Vec<IndexAsset>) {
+        sender.require_auth();
+
+        let index = get_index(&env);
 
-    // fn query_price(env: Env) -> i128 {
-    //     env.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        if index.is_public {
+            utils::is_governor(&env, sender.clone());
+        } else {
+            utils::is_admin(&env, sender.clone());
+        }
 
-    //     let index = get_index(&env);
+        let now = env.ledger().timestamp();
+        if !index.can_rebalance(now) {
+            panic_with_error!(&env, ErrorCode::TooSoonToRebalance);
+        }
 
-    //     get_index_price(&env, index);
-    // }
+        validate_index_weights(&env, &updated_assets);
 
-    // fn query_nav(env: Env) -> i128 {
-    //     env.storage().instance().extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        execute_rebalance(&env, index, &sender, updated_assets, now, None);
+    }
 
-    //     let index = get_index(&env);
+    fn execute_scheduled_rebalance(env: Env, sender: Address) {
+        sender.require_auth();
 
-    //     calculate_current_nav(&env, index);
-    // }
-}
+        let mut index = get_index(&env);
+        validate_admin_or_keeper(&env, &index, &sender);
 
-fn annualize_fee_amount(env: &Env, amount: i128, fee: i64, time_held: u64) -> NormalResult<i128> {
-    let fee = amount
-        .safe_mul(fee.cast::<i128>(env)?, env)?
-        .safe_mul(time_held.cast::<i128>(env)?, env)?
-        .safe_div(SECONDS_IN_A_YEAR.cast::<i128>(env)?, env)?;
+        let now = env.ledger().timestamp();
+        if !index.can_rebalance(now) {
+            panic_with_error!(&env, ErrorCode::TooSoonToRebalance);
+        }
 
-    Ok(fee)
-}
+        let updated_assets = get_strategy_weights(&env, &index, now);
+        validate_index_weights(&env, &updated_assets);
 
-#[contractimpl]
-impl token::Interface for IndexToken {
-    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
-        env.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        read_allowance(&env, from, spender).amount
-    }
+        // Streaming fees accrue on the supply before the keeper reward is minted
+        accrue_fees(&env, &mut index, now);
 
-    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
-        utils::check_nonnegative_amount(amount);
-        from.require_auth();
+        if index.keeper_reward > 0 {
+            receive_balance(&env, sender.clone(), index.keeper_reward);
+            TokenUtils::new(&env).events().mint(
+                utils::get_admin(&env),
+                sender.clone(),
+                index.keeper_reward,
+            );
 
-        env.storage()
-            .instance()
-            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+            index.total_fees = index.total_fees.safe_add(index.keeper_reward, &env);
+            index.total_mints = index.total_mints.safe_add(index.keeper_reward, &env);
+        }
 
-        write_allowance(
+        let drift_tolerance = index.drift_tolerance;
+        execute_rebalance(
             &env,
-            from.clone(),
+            index,
+            &sender,
+            updated_assets,
+            now,
+            Some(drift_tolerance),
+        );
+    }
+
+    fn collect_fees(env: Env, sender: Address) {
+        sender.require_auth();
+
+        // Permissionless: fees only ever go to the manager and protocol treasury
+        let mut index = get_index(&env);
+        accrue_fees(&env, &mut index, env.ledger().timestamp());
+
+        save_index(&env, index);
+    }
+
+    fn crystallize_performance_fee(env: Env, sender: Address) {
+        sender.require_auth();
+
+        let mut index = get_index(&env);
+        validate_admin_or_keeper(&env, &index, &sender);
+
+        let now = env.ledger().timestamp();
+        if !index.can_crystallize(now) {
+            panic_with_error!(&env, ErrorCode::TooSoonToCrystallize);
+        }
+
+        charge_performance_fee(&env, &mut index, now);
+
+        save_index(&env, index);
+    }
+
+    // ################################################################
+    //                             USER
+    // ################################################################
+
+    fn mint(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        mint_index_tokens(&env, &sender, index_token_amount, None)?;
+
+        Ok(())
+    }
+
+    fn redeem(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        redeem_index_tokens(&env, &sender, index_token_amount)?;
+
+        Ok(())
+    }
+
+    fn mint_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        max_quote_in: i128,
+        deadline: u64,
+    ) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        validate_deadline(&env, deadline);
+
+        mint_index_tokens(&env, &sender, index_token_amount, Some(max_quote_in))?;
+
+        Ok(())
+    }
+
+    fn redeem_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        min_quote_out: i128,
+        deadline: u64,
+    ) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        validate_deadline(&env, deadline);
+
+        let quote_token_amount = redeem_index_tokens(&env, &sender, index_token_amount)?;
+
+        if quote_token_amount < min_quote_out {
+            log!(
+                &env,
+                "Index Token: Redeem: quote out {} < min quote out {}",
+                quote_token_amount,
+                min_quote_out
+            );
+            panic_with_error!(&env, ErrorCode::SlippageToleranceExceeded);
+        }
+
+        Ok(())
+    }
+
+    // ################################################################
+    //                     AUTHORIZED PARTICIPANT
+    // ################################################################
+
+    fn mint_in_kind(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        let mut index = get_index(&env);
+        validate_account_access(&env, &index, &sender);
+
+        accrue_fees(&env, &mut index, env.ledger().timestamp());
+
+        let total_supply = validate_in_kind_participant(&env, &index, &sender);
+
+        // Deposit each component in proportion to the current basket
+        let mut basket: Map<Address, i128> = Map::new(&env);
+        for (token, balance) in index.component_balances.clone().iter() {
+            let amount = balance
+                .safe_mul(index_token_amount, &env)
+                .safe_div_ceil(total_supply, &env);
+            if amount == 0 {
+                continue;
+            }
+
+            utils::transfer_token(
+                &env,
+                &token,
+                &sender,
+                &env.current_contract_address(),
+                amount,
+            );
+
+            index
+                .component_balances
+                .set(token.clone(), balance.safe_add(amount, &env));
+            basket.set(token, amount);
+        }
+
+        let now = env.ledger().timestamp();
+        index.component_balance_update_ts = now;
+        index.total_mints = index.total_mints.safe_add(index_token_amount, &env);
+        save_index(&env, index);
+
+        receive_balance(&env, sender.clone(), index_token_amount);
+        TokenUtils::new(&env).events().mint(
+            utils::get_admin(&env),
+            sender.clone(),
+            index_token_amount,
+        );
+
+        IndexTokenEvents::mint_in_kind(&env, sender, index_token_amount, basket);
+
+        Ok(())
+    }
+
+    fn redeem_in_kind(env: Env, sender: Address, index_token_amount: i128) -> NormalResult {
+        utils::check_nonnegative_amount(index_token_amount);
+        sender.require_auth();
+
+        let mut index = get_index(&env);
+        validate_account_access(&env, &index, &sender);
+
+        accrue_fees(&env, &mut index, env.ledger().timestamp());
+
+        let total_supply = validate_in_kind_participant(&env, &index, &sender);
+
+        if index_token_amount > total_supply {
+            panic_with_error!(&env, ErrorCode::InsufficientFunds);
+        }
+
+        // Pay out the redeemed share of each component
+        let mut basket: Map<Address, i128> = Map::new(&env);
+        for (token, balance) in index.component_balances.clone().iter() {
+            let amount = balance
+                .safe_mul(index_token_amount, &env)
+                .safe_div(total_supply, &env);
+            if amount == 0 {
+                continue;
+            }
+
+            index
+                .component_balances
+                .set(token.clone(), balance.safe_sub(amount, &env));
+
+            utils::transfer_token(
+                &env,
+                &token,
+                &env.current_contract_address(),
+                &sender,
+                amount,
+            );
+            basket.set(token, amount);
+        }
+
+        let now = env.ledger().timestamp();
+        index.component_balance_update_ts = now;
+        burn_index_tokens(&env, &mut index, &sender, index_token_amount);
+        save_index(&env, index);
+
+        IndexTokenEvents::redeem_in_kind(&env, sender, index_token_amount, basket);
+
+        Ok(())
+    }
+
+    // ################################################################
+    //                             QUERIES
+    // ################################################################
+
+    fn query_index(env: Env) -> IndexResponse {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        IndexResponse {
+            index: get_index(&env),
+        }
+    }
+
+    fn query_price(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let index = get_index(&env);
+
+        get_index_price(&env, &index, env.ledger().timestamp())
+    }
+
+    fn query_nav(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let index = get_index(&env);
+
+        calculate_current_nav(&env, &index, env.ledger().timestamp())
+    }
+
+    fn query_quote_amount(env: Env, index_token_amount: i128) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut index = get_index(&env);
+        let now = env.ledger().timestamp();
+        let index_price = get_index_price(&env, &index, now);
+
+        match convert_index_token_amount_to_quote_amount(
+            &env,
+            &mut index,
+            index_token_amount,
+            index_price,
+            now,
+            NormalAction::IndexMint,
+        ) {
+            Ok((quote_token_amount, _price)) => quote_token_amount,
+            Err(error) => panic_with_error!(&env, error),
+        }
+    }
+
+    fn query_accrued_fees(env: Env) -> AccruedFeesResponse {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let index = get_index(&env);
+        let (manager_fee, protocol_fee) =
+            calculate_accrued_fees(&env, &index, env.ledger().timestamp());
+
+        AccruedFeesResponse {
+            manager_fee,
+            protocol_fee,
+        }
+    }
+
+    fn query_rebalance_result(env: Env) -> Option<RebalanceResult> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        get_rebalance_result(&env)
+    }
+
+    fn check_account_access(env: Env, account: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let index = get_index(&env);
+        validate_account_access(&env, &index, &account);
+    }
+}
+
+/// Checks `account` against the blacklist and the index access mode. The
+/// admin (manager), factory and protocol treasury are always allowed so fee
+/// mints and transfers can't be blocked.
+fn validate_account_access(env: &Env, index: &Index, account: &Address) {
+    if *account == utils::get_admin(env) || *account == utils::get_factory(env) {
+        return;
+    }
+
+    let is_blacklisted = index.is_blacklisted(account);
+    let is_not_whitelisted =
+        index.access_mode == IndexAccessMode::WhitelistOnly && !index.is_whitelisted(account);
+    if !is_blacklisted && !is_not_whitelisted {
+        return;
+    }
+
+    if *account == get_protocol_treasury(env) {
+        return;
+    }
+
+    if is_blacklisted {
+        log!(env, "Index Token: {} is blacklisted", account);
+        panic_with_error!(env, ErrorCode::AccountBlacklisted);
+    }
+
+    log!(env, "Index Token: {} is not whitelisted", account);
+    panic_with_error!(env, ErrorCode::AccountNotWhitelisted);
+}
+
+/// Public indexes are open to everyone, so they can't be whitelist only
+fn validate_access_mode(env: &Env, is_public: bool, access_mode: IndexAccessMode) {
+    if is_public && access_mode == IndexAccessMode::WhitelistOnly {
+        log!(env, "Index Token: public indexes can't be whitelist only");
+        panic_with_error!(env, ErrorCode::InvalidAccessMode);
+    }
+}
+
+fn validate_admin_or_keeper(env: &Env, index: &Index, sender: &Address) {
+    if *sender != utils::get_admin(env) && !index.is_keeper(sender) {
+        log!(env, "Index Token: {} is not a keeper", sender);
+        panic_with_error!(env, ErrorCode::UnauthorizedKeeper);
+    }
+}
+
+/// Trades the components from their current weights by NAV towards `updated_assets`.
+/// Each swap must fill within `index.max_swap_slippage` of its oracle value.
+///
+/// With a `drift_tolerance`, fails unless some component has drifted further than
+/// the tolerance from its target.
+fn execute_rebalance(
+    env: &Env,
+    mut index: Index,
+    keeper: &Address,
+    updated_assets: Vec<IndexAsset>,
+    now: u64,
+    drift_tolerance: Option<i128>,
+) {
+    accrue_fees(env, &mut index, now);
+
+    let nav = calculate_current_nav(env, &index, now);
+
+    let mut target_weights: Map<Address, i128> = Map::new(env);
+    for asset in updated_assets.iter() {
+        target_weights.set(asset.market, asset.weight);
+    }
+
+    // Current components followed by the ones being added
+    let mut markets: Vec<Address> = Vec::new(env);
+    for asset in index.component_assets.iter().chain(updated_assets.iter()) {
+        if !markets.contains(&asset.market) {
+            markets.push_back(asset.market);
+        }
+    }
+
+    let mut weights: Vec<ComponentWeight> = Vec::new(env);
+    let mut sells: Vec<Swap> = Vec::new(env);
+    // Buys hold their weight deficit until the sale proceeds are known
+    let mut buys: Vec<Swap> = Vec::new(env);
+    let mut buy_prices: Map<Address, i128> = Map::new(env);
+    let mut total_weight_deficit: i128 = 0;
+    let mut total_weight_change: i128 = 0;
+    let mut max_drift: i128 = 0;
+    let mut quote_price: Option<i128> = None;
+
+    for market in markets.iter() {
+        let price_data = get_market_price_data(env, &market);
+        let balance = index
+            .component_balances
+            .get(price_data.synth_token.clone())
+            .unwrap_or(0);
+
+        let current_weight = if nav > 0 && balance > 0 {
+            let price =
+                get_component_price(env, &index, &market, &price_data, &mut quote_price, now);
+            balance
+                .safe_mul(price, env)
+                .safe_div(PRICE_PRECISION_I128, env)
+                .safe_mul(PERCENTAGE_PRECISION_I128, env)
+                .safe_div(nav, env)
+        } else {
+            0
+        };
+        let target_weight = target_weights.get(market.clone()).unwrap_or(0);
+        let weight_change = target_weight.safe_sub(current_weight, env);
+
+        // An empty index has nothing to trade, the new weights apply from the next mint
+        if nav > 0 && weight_change.abs() > index.max_weight_change {
+            log!(
+                env,
+                "Index Token: Rebalance: weight change {} > max {}",
+                weight_change,
+                index.max_weight_change
+            );
+            panic_with_error!(env, ErrorCode::RebalanceWeightChangeTooLarge);
+        }
+
+        if weight_change < 0 && balance > 0 {
+            let sell_amount = if target_weight == 0 {
+                balance
+            } else {
+                balance
+                    .safe_mul(weight_change.abs(), env)
+                    .safe_div(current_weight, env)
+            };
+
+            let component_price =
+                get_component_oracle_price(env, &index, &market, &price_data, now);
+            let quote_price =
+                *quote_price.get_or_insert_with(|| get_quote_oracle_price(env, &index, now));
+
+            sells.push_back(Swap {
+                market: market.clone(),
+                ask_asset: index.quote_token.clone(),
+                offer_asset: price_data.synth_token.clone(),
+                offer_amount: sell_amount,
+                ask_asset_min_amount: Some(get_swap_min_out(
+                    env,
+                    &index,
+                    sell_amount,
+                    component_price,
+                    quote_price,
+                )),
+            });
+        } else if weight_change > 0 && nav > 0 {
+            buy_prices.set(
+                market.clone(),
+                get_component_oracle_price(env, &index, &market, &price_data, now),
+            );
+            buys.push_back(Swap {
+                market: market.clone(),
+                ask_asset: price_data.synth_token.clone(),
+                offer_asset: index.quote_token.clone(),
+                offer_amount: weight_change,
+                ask_asset_min_amount: None,
+            });
+            total_weight_deficit = total_weight_deficit.safe_add(weight_change, env);
+        }
+
+        total_weight_change = total_weight_change.safe_add(weight_change.abs(), env);
+        max_drift = max_drift.max(weight_change.abs());
+        weights.push_back(ComponentWeight {
+            market,
+            current_weight,
+            target_weight,
+        });
+    }
+
+    if let Some(drift_tolerance) = drift_tolerance {
+        if max_drift <= drift_tolerance {
+            log!(
+                env,
+                "Index Token: Rebalance: drift {} within tolerance {}",
+                max_drift,
+                drift_tolerance
+            );
+            panic_with_error!(env, ErrorCode::RebalanceDriftWithinTolerance);
+        }
+    }
+
+    let turnover = total_weight_change.safe_div(2, env);
+    if nav > 0 && turnover > index.max_turnover {
+        log!(
+            env,
+            "Index Token: Rebalance: turnover {} > max {}",
+            turnover,
+            index.max_turnover
+        );
+        panic_with_error!(env, ErrorCode::RebalanceTurnoverTooLarge);
+    }
+
+    let quote_balance_before =
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address());
+
+    swap_and_update_component_balances(env, sells, &mut index);
+
+    let quote_sold =
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address())
+            .safe_sub(quote_balance_before, env);
+
+    let mut position_increases: Vec<Swap> = Vec::new(env);
+    if total_weight_deficit > 0 {
+        let quote_price =
+            *quote_price.get_or_insert_with(|| get_quote_oracle_price(env, &index, now));
+
+        for buy in buys.iter() {
+            let offer_amount = quote_sold
+                .safe_mul(buy.offer_amount, env)
+                .safe_div(total_weight_deficit, env);
+            let component_price = buy_prices.get(buy.market.clone()).unwrap_or(0);
+
+            position_increases.push_back(Swap {
+                offer_amount,
+                ask_asset_min_amount: Some(get_swap_min_out(
+                    env,
+                    &index,
+                    offer_amount,
+                    quote_price,
+                    component_price,
+                )),
+                ..buy
+            });
+        }
+    }
+
+    swap_and_update_component_balances(env, position_increases, &mut index);
+
+    let quote_bought = quote_balance_before.safe_add(quote_sold, env).safe_sub(
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address()),
+        env,
+    );
+
+    // Drop components that were sold off
+    for (token, balance) in index.component_balances.clone().iter() {
+        if balance == 0 {
+            index.component_balances.remove(token);
+        }
+    }
+
+    let mut component_assets: Vec<IndexAsset> = Vec::new(env);
+    let mut component_markets: Vec<Address> = Vec::new(env);
+    for asset in updated_assets.iter() {
+        component_markets.push_back(asset.market.clone());
+        component_assets.push_back(IndexAsset {
+            last_updated_ts: now.cast(env),
+            ..asset
+        });
+    }
+
+    // Keep the factory's market -> indexes lookup in sync
+    env.invoke_contract::<()>(
+        &utils::get_factory(env),
+        &Symbol::new(env, "sync_index_markets"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            component_markets.into_val(env),
+        ],
+    );
+
+    save_index(
+        env,
+        Index {
+            component_assets,
+            rebalance_ts: now,
+            last_updated_ts: now,
+            ..index
+        },
+    );
+
+    save_rebalance_result(
+        env,
+        &RebalanceResult {
+            ts: now,
+            keeper: keeper.clone(),
+            nav,
+            turnover,
+            quote_sold,
+            quote_bought,
+            weights,
+        },
+    );
+
+    IndexTokenEvents::rebalance(env, keeper.clone(), updated_assets);
+}
+
+/// Target weights of the current components under `index.rebalance_strategy`.
+/// Rounding dust goes to the first component so the weights sum to 100%.
+fn get_strategy_weights(env: &Env, index: &Index, now: u64) -> Vec<IndexAsset> {
+    let component_count = index.component_assets.len();
+    if component_count == 0 {
+        panic_with_error!(env, ErrorCode::EmptyIndex);
+    }
+
+    let weights: Vec<i128> = match index.rebalance_strategy {
+        RebalanceStrategy::FixedWeights => return index.component_assets.clone(),
+        RebalanceStrategy::EqualWeight => {
+            let weight = PERCENTAGE_PRECISION_I128.safe_div(component_count.into(), env);
+            let mut weights = Vec::new(env);
+            for _ in 0..component_count {
+                weights.push_back(weight);
+            }
+            weights
+        }
+        RebalanceStrategy::MarketCap => {
+            let mut market_caps: Vec<i128> = Vec::new(env);
+            let mut total_market_cap: i128 = 0;
+            let mut quote_price: Option<i128> = None;
+
+            for asset in index.component_assets.iter() {
+                let price_data = get_market_price_data(env, &asset.market);
+                let price = get_component_price(
+                    env,
+                    index,
+                    &asset.market,
+                    &price_data,
+                    &mut quote_price,
+                    now,
+                );
+                // Markets can have different token decimals
+                let market_cap = standardize_token_amount(
+                    env,
+                    price_data.synth_supply,
+                    price_data.synth_decimals,
+                    SPOT_BALANCE_PRECISION_EXP,
+                )
+                .cast::<i128>(env)
+                .safe_mul(price, env);
+
+                total_market_cap = total_market_cap.safe_add(market_cap, env);
+                market_caps.push_back(market_cap);
+            }
+
+            if total_market_cap == 0 {
+                panic_with_error!(env, ErrorCode::InvalidIndexWeights);
+            }
+
+            let mut weights = Vec::new(env);
+            for market_cap in market_caps.iter() {
+                weights.push_back(
+                    market_cap
+                        .safe_mul(PERCENTAGE_PRECISION_I128, env)
+                        .safe_div(total_market_cap, env),
+                );
+            }
+            weights
+        }
+    };
+
+    let total_weight = weights
+        .iter()
+        .fold(0i128, |acc, weight| acc.safe_add(weight, env));
+    let dust = PERCENTAGE_PRECISION_I128.safe_sub(total_weight, env);
+
+    let mut assets: Vec<IndexAsset> = Vec::new(env);
+    for (i, asset) in index.component_assets.iter().enumerate() {
+        let weight = weights.get_unchecked(i as u32);
+        assets.push_back(IndexAsset {
+            weight: if i == 0 {
+                weight.safe_add(dust, env)
+            } else {
+                weight
+            },
+            ..asset
+        });
+    }
+
+    assets
+}
+
+/// Component weights must be non-negative, unique per market and sum to 100%
+fn validate_index_weights(env: &Env, assets: &Vec<IndexAsset>) {
+    let mut markets: Vec<Address> = Vec::new(env);
+    let mut total_weight: i128 = 0;
+
+    for asset in assets.iter() {
+        if asset.weight < 0 || markets.contains(&asset.market) {
+            log!(env, "Index Token: invalid weight for {}", asset.market);
+            panic_with_error!(env, ErrorCode::InvalidIndexWeights);
+        }
+
+        total_weight = total_weight.safe_add(asset.weight, env);
+        markets.push_back(asset.market);
+    }
+
+    if total_weight != PERCENTAGE_PRECISION_I128 {
+        log!(env, "Index Token: weights sum to {}", total_weight);
+        panic_with_error!(env, ErrorCode::InvalidIndexWeights);
+    }
+}
+
+fn validate_deadline(env: &Env, deadline: u64) {
+    let now = env.ledger().timestamp();
+    if now > deadline {
+        log!(env, "Index Token: deadline {} passed at {}", deadline, now);
+        panic_with_error!(env, ErrorCode::DeadlineExceeded);
+    }
+}
+
+/// Buys the components for `index_token_amount` and mints it to `sender`.
+///
+/// Fails before taking any quote if the amount needed exceeds `max_quote_in`. Each
+/// component buy must fill within `index.max_swap_slippage` of its oracle value.
+///
+/// Returns the quote amount spent on the component swaps. Any quote left unspent
+/// is returned to `sender`.
+fn mint_index_tokens(
+    env: &Env,
+    sender: &Address,
+    index_token_amount: i128,
+    max_quote_in: Option<i128>,
+) -> NormalResult<i128> {
+    let now = env.ledger().timestamp();
+
+    // Get index and price
+    let mut index = get_index(env);
+    validate_account_access(env, &index, sender);
+
+    accrue_fees(env, &mut index, now);
+
+    let index_price = get_index_price(env, &index, now);
+
+    // Compute amount of quote asset needed
+    let (quote_token_amount, _price) = convert_index_token_amount_to_quote_amount(
+        env,
+        &mut index,
+        index_token_amount,
+        index_price,
+        now,
+        NormalAction::IndexMint,
+    )?;
+
+    if let Some(max_quote_in) = max_quote_in {
+        if quote_token_amount > max_quote_in {
+            log!(
+                env,
+                "Index Token: Mint: quote in {} > max quote in {}",
+                quote_token_amount,
+                max_quote_in
+            );
+            panic_with_error!(env, ErrorCode::SlippageToleranceExceeded);
+        }
+    }
+
+    // Deposit initial investment
+    utils::receive_token(env, &index.quote_token, sender, quote_token_amount);
+
+    // Buy each component with its share of the deposit
+    let quote_balance_before =
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address());
+
+    let operations = get_component_buys(env, &index, quote_token_amount, now);
+    swap_and_update_component_balances(env, operations, &mut index);
+
+    let quote_spent = quote_balance_before.safe_sub(
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address()),
+        env,
+    );
+
+    // Rounding dust from splitting the deposit by weight
+    let quote_unspent = quote_token_amount.safe_sub(quote_spent, env);
+    if quote_unspent > 0 {
+        utils::transfer_token(
+            env,
+            &index.quote_token,
+            &env.current_contract_address(),
+            sender,
+            quote_unspent,
+        );
+    }
+
+    index.total_mints = index.total_mints.safe_add(index_token_amount, env);
+    save_index(env, index);
+
+    // Mint index tokens
+    receive_balance(env, sender.clone(), index_token_amount);
+    TokenUtils::new(env)
+        .events()
+        .mint(utils::get_admin(env), sender.clone(), index_token_amount);
+
+    IndexTokenEvents::mint(env, sender.clone(), index_token_amount);
+
+    Ok(quote_spent)
+}
+
+/// Sells the components backing `index_token_amount`, pays the proceeds to `sender`
+/// and burns the index tokens.
+///
+/// Returns the quote amount received from the component swaps.
+fn redeem_index_tokens(
+    env: &Env,
+    sender: &Address,
+    index_token_amount: i128,
+) -> NormalResult<i128> {
+    let now = env.ledger().timestamp();
+
+    // Get index and price
+    let mut index = get_index(env);
+    validate_account_access(env, &index, sender);
+
+    accrue_fees(env, &mut index, now);
+
+    let index_price = get_index_price(env, &index, now);
+
+    // Validate the quote oracle for redemptions
+    let _ = convert_index_token_amount_to_quote_amount(
+        env,
+        &mut index,
+        index_token_amount,
+        index_price,
+        now,
+        NormalAction::IndexRedeem,
+    )?;
+
+    let total_supply = index.total_supply();
+    if index_token_amount > total_supply {
+        panic_with_error!(env, ErrorCode::InsufficientFunds);
+    }
+
+    // Sell the redeemed share of each component, paying out what the pools return
+    let quote_balance_before =
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address());
+
+    let operations = get_component_sells(env, &index, index_token_amount, total_supply, now);
+    swap_and_update_component_balances(env, operations, &mut index);
+
+    let quote_token_amount =
+        utils::get_token_balance(env, &index.quote_token, &env.current_contract_address())
+            .safe_sub(quote_balance_before, env);
+
+    burn_index_tokens(env, &mut index, sender, index_token_amount);
+    save_index(env, index.clone());
+
+    utils::transfer_token(
+        env,
+        &index.quote_token,
+        &env.current_contract_address(),
+        sender,
+        quote_token_amount,
+    );
+
+    IndexTokenEvents::redeem(env, sender.clone(), index_token_amount);
+
+    Ok(quote_token_amount)
+}
+
+/// Burns `amount` index tokens held by `from`, counting them as redeemed so the
+/// total supply stays in sync with the balances
+fn burn_index_tokens(env: &Env, index: &mut Index, from: &Address, amount: i128) {
+    spend_balance(env, from.clone(), amount);
+    index.total_redemptions = index.total_redemptions.safe_add(amount, env);
+
+    TokenUtils::new(env).events().burn(from.clone(), amount);
+}
+
+/// Index tokens owed to the manager and protocol since `index.fee_accrual_ts`,
+/// streamed pro-rata on the total supply
+///
+/// Returns `(manager_fee, protocol_fee)`
+fn calculate_accrued_fees(env: &Env, index: &Index, now: u64) -> (i128, i128) {
+    let total_supply = index.total_supply();
+    let elapsed = now.saturating_sub(index.fee_accrual_ts);
+    if total_supply <= 0 || elapsed == 0 {
+        return (0, 0);
+    }
+
+    let protocol_fee_bps: i64 = env.invoke_contract(
+        &utils::get_factory(env),
+        &Symbol::new(env, "query_protocol_fee"),
+        vec![env],
+    );
+
+    (
+        annualize_fee_amount(env, total_supply, index.manager_fee_bps, elapsed),
+        annualize_fee_amount(env, total_supply, protocol_fee_bps, elapsed),
+    )
+}
+
+fn get_protocol_treasury(env: &Env) -> Address {
+    env.invoke_contract(
+        &utils::get_factory(env),
+        &Symbol::new(env, "query_treasury"),
+        vec![env],
+    )
+}
+
+/// Mints the streaming fees accrued since the last accrual as dilutive index
+/// tokens to the manager (admin) and the factory's protocol treasury
+fn accrue_fees(env: &Env, index: &mut Index, now: u64) {
+    let (manager_fee, protocol_fee) = calculate_accrued_fees(env, index, now);
+    index.fee_accrual_ts = now;
+
+    if manager_fee == 0 && protocol_fee == 0 {
+        return;
+    }
+
+    let admin = utils::get_admin(env);
+    if manager_fee > 0 {
+        receive_balance(env, admin.clone(), manager_fee);
+        TokenUtils::new(env)
+            .events()
+            .mint(admin.clone(), admin.clone(), manager_fee);
+    }
+
+    if protocol_fee > 0 {
+        let treasury = get_protocol_treasury(env);
+        receive_balance(env, treasury.clone(), protocol_fee);
+        TokenUtils::new(env)
+            .events()
+            .mint(admin, treasury, protocol_fee);
+    }
+
+    let total_fees = manager_fee.safe_add(protocol_fee, env);
+    index.total_fees = index.total_fees.safe_add(total_fees, env);
+    index.total_mints = index.total_mints.safe_add(total_fees, env);
+
+    IndexTokenEvents::accrue_fees(env, manager_fee, protocol_fee);
+}
+
+/// Charges `performance_fee_bps` of the gain above the high-water mark by
+/// minting index tokens to the manager, after accruing streaming fees
+///
+/// The mint dilutes holders by exactly the fee, so `fee * supply / (nav - fee)`
+/// tokens are minted and the high-water mark is set to the post-mint price
+fn charge_performance_fee(env: &Env, index: &mut Index, now: u64) {
+    accrue_fees(env, index, now);
+
+    let index_price = get_index_price(env, index, now);
+    let total_supply = index.total_supply();
+
+    let (fee, minted) = if index_price > index.high_water_mark && total_supply > 0 {
+        let nav = index_price
+            .safe_mul(total_supply, env)
+            .safe_div(PRICE_PRECISION_I128, env);
+        // Value of the gain above the high-water mark owed to the manager
+        let fee = index_price
+            .safe_sub(index.high_water_mark, env)
+            .safe_mul(total_supply, env)
+            .safe_mul(index.performance_fee_bps.cast::<i128>(env), env)
+            .safe_div(BPS_DENOMINATOR, env)
+            .safe_div(PRICE_PRECISION_I128, env);
+
+        if fee > 0 && nav > fee {
+            let minted = fee
+                .safe_mul(total_supply, env)
+                .safe_div(nav.safe_sub(fee, env), env);
+            index.high_water_mark = index_price
+                .safe_mul(total_supply, env)
+                .safe_div(total_supply.safe_add(minted, env), env);
+            (fee, minted)
+        } else {
+            (0, 0)
+        }
+    } else {
+        (0, 0)
+    };
+
+    let manager = utils::get_admin(env);
+    if minted > 0 {
+        receive_balance(env, manager.clone(), minted);
+        TokenUtils::new(env)
+            .events()
+            .mint(manager.clone(), manager.clone(), minted);
+
+        index.total_fees = index.total_fees.safe_add(minted, env);
+        index.total_mints = index.total_mints.safe_add(minted, env);
+    }
+
+    index.performance_fee_ts = now;
+
+    IndexTokenEvents::crystallize(env, manager, index.high_water_mark, fee, minted);
+}
+
+#[contractimpl]
+impl token::Interface for IndexToken {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_allowance(&env, from, spender).amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        utils::check_nonnegative_amount(amount);
+        from.require_auth();
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_allowance(
+            &env,
+            from.clone(),
             spender.clone(),
             amount,
             expiration_ledger,
@@ -518,7 +1618,13 @@ impl token::Interface for IndexToken {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let _ = transfer_with_index_fee(&env, from, to, amount);
+        let index = get_index(&env);
+        validate_account_access(&env, &index, &from);
+        validate_account_access(&env, &index, &to);
+
+        spend_balance(&env, from.clone(), amount);
+        receive_balance(&env, to.clone(), amount);
+        TokenUtils::new(&env).events().transfer(from, to, amount);
     }
 
     fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
@@ -531,7 +1637,13 @@ impl token::Interface for IndexToken {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
         spend_allowance(&env, from.clone(), spender, amount);
-        let _ = transfer_with_index_fee(&env, from, to, amount);
+        let index = get_index(&env);
+        validate_account_access(&env, &index, &from);
+        validate_account_access(&env, &index, &to);
+
+        spend_balance(&env, from.clone(), amount);
+        receive_balance(&env, to.clone(), amount);
+        TokenUtils::new(&env).events().transfer(from, to, amount);
     }
 
     fn burn(env: Env, from: Address, amount: i128) {
@@ -543,8 +1655,9 @@ impl token::Interface for IndexToken {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        spend_balance(&env, from.clone(), amount);
-        TokenUtils::new(&env).events().burn(from, amount);
+        let mut index = get_index(&env);
+        burn_index_tokens(&env, &mut index, &from, amount);
+        save_index(&env, index);
     }
 
     fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
@@ -557,8 +1670,10 @@ impl token::Interface for IndexToken {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
         spend_allowance(&env, from.clone(), spender, amount);
-        spend_balance(&env, from.clone(), amount);
-        TokenUtils::new(&env).events().burn(from, amount)
+
+        let mut index = get_index(&env);
+        burn_index_tokens(&env, &mut index, &from, amount);
+        save_index(&env, index);
     }
 
     fn decimals(env: Env) -> u32 {
@@ -574,174 +1689,416 @@ impl token::Interface for IndexToken {
     }
 }
 
-fn check_fee_exemption(env: &Env, user: &Address) -> NormalResult<bool> {
-    Ok(user.eq(&utils::get_admin(env)) || user.eq(&env.current_contract_address()))
-}
+fn convert_index_token_amount_to_quote_amount(
+    env: &Env,
+    index: &mut Index,
+    index_token_amount: i128,
+    index_price: i128,
+    now: u64,
+    action: NormalAction,
+) -> NormalResult<(i128, i128)> {
+    // Get quote asset price
+    let validity_guard_rails = OracleGuardRails::default().validity;
+    let oracle_price_data = get_oracle_price_with_feeds(
+        env,
+        &index.oracle_source,
+        &index.oracle,
+        &index.oracle_feeds,
+        (XLM, USD),
+        now,
+        &validity_guard_rails,
+    );
 
-fn transfer_with_index_fee(env: &Env, from: Address, to: Address, amount: i128) -> NormalResult {
-    let transfer_info = calculate_fees(env, &from, amount)?;
+    let oracle_validity = oracle_validity(
+        env,
+        index.oracle.clone(),
+        index.historical_oracle_data.last_oracle_price_twap,
+        &oracle_price_data,
+        &validity_guard_rails,
+        1,
+        false,
+    );
+    validate_oracle_for_action(env, oracle_validity, action);
 
-    let factory_addr = utils::get_factory(env);
-    let manager_addr = utils::get_admin(env); // Assumes admin is manager
+    index
+        .historical_oracle_data
+        .update(env, &oracle_price_data, now, ONE_HOUR.cast(env));
 
-    spend_balance(env, from.clone(), amount);
-    receive_balance(env, to.clone(), transfer_info.net_amount);
-    TokenUtils::new(env)
-        .events()
-        .transfer(from, to, transfer_info.net_amount);
+    // Compute amount of quote asset needed
+    let quote_token_amount = index_price
+        .safe_mul(index_token_amount, env)
+        .safe_div(oracle_price_data.price.cast::<i128>(env), env);
 
-    if transfer_info.protocol_fee_amount > 0 {
-        receive_balance(env, factory_addr.clone(), transfer_info.protocol_fee_amount);
+    Ok((
+        quote_token_amount,
+        oracle_price_data.price.cast::<i128>(env),
+    ))
+}
+
+/// NAV per index token. Minting and redeeming at this price leave it unchanged.
+fn get_index_price(env: &Env, index: &Index, now: u64) -> i128 {
+    let total_supply = index.total_supply();
+    if total_supply <= 0 {
+        return index.initial_price;
     }
 
-    if transfer_info.manager_fee_amount > 0 {
-        receive_balance(env, manager_addr.clone(), transfer_info.manager_fee_amount);
+    let current_nav = calculate_current_nav(env, index, now);
+
+    // The index is priced at inception until it holds any components
+    if current_nav == 0 {
+        return index.initial_price;
     }
 
-    Ok(())
+    current_nav
+        .safe_mul(PRICE_PRECISION_I128, env)
+        .safe_div(total_supply, env)
 }
 
-fn calculate_fees(env: &Env, from: &Address, amount: i128) -> NormalResult<TransferWithFees> {
-    if check_fee_exemption(env, from)? {
-        // No fees applied, transfer the full amount
-        return Ok(TransferWithFees {
-            protocol_fee_amount: 0,
-            manager_fee_amount: 0,
-            total_fees: 0,
-            net_amount: amount,
-        });
-    }
+/// Sums the USD value of every component balance, priced by `index.price_source`
+fn calculate_current_nav(env: &Env, index: &Index, now: u64) -> i128 {
+    let mut nav: i128 = 0;
 
-    let last_transfer = get_last_transfer(env, from);
+    // Pool prices are quoted in XLM
+    let mut quote_price: Option<i128> = None;
 
-    if last_transfer.balance == 0 {
-        // No fee if there was no prior balance
-        return Ok(TransferWithFees {
-            protocol_fee_amount: 0,
-            manager_fee_amount: 0,
-            total_fees: 0,
-            net_amount: amount,
-        });
-    }
+    for asset in index.component_assets.iter() {
+        let price_data = get_market_price_data(env, &asset.market);
 
-    let index = get_index(env);
+        let balance = index
+            .component_balances
+            .get(price_data.synth_token.clone())
+            .unwrap_or(0);
+        if balance == 0 {
+            continue;
+        }
 
-    // Get the protocol fee
-    let protocol_fee: i64 = env.invoke_contract(
-        &utils::get_factory(env),
-        &Symbol::new(env, "query_protocol_fee"),
-        vec![&env],
-    );
+        let price = get_component_price(
+            env,
+            index,
+            &asset.market,
+            &price_data,
+            &mut quote_price,
+            now,
+        );
 
-    // Calculate weighted holding time
-    let time_held = env.ledger().timestamp() - last_transfer.ts;
+        nav = nav.safe_add(
+            balance
+                .safe_mul(price, env)
+                .safe_div(PRICE_PRECISION_I128, env),
+            env,
+        );
+    }
 
-    // Prorated fee calculation
-    let protocol_fee_amount = annualize_fee_amount(env, amount, protocol_fee, time_held)?;
-    let manager_fee_amount = annualize_fee_amount(env, amount, index.manager_fee_bps, time_held)?;
+    nav
+}
 
-    let total_fees = protocol_fee_amount + manager_fee_amount;
-    let net_amount = amount - total_fees;
+/// USD price of a component, priced by `index.price_source`. The XLM price used
+/// to convert pool prices is cached in `quote_price`.
+fn get_component_price(
+    env: &Env,
+    index: &Index,
+    market: &Address,
+    price_data: &MarketPriceData,
+    quote_price: &mut Option<i128>,
+    now: u64,
+) -> i128 {
+    match index.price_source {
+        IndexPriceSource::Oracle => get_component_oracle_price(env, index, market, price_data, now),
+        IndexPriceSource::Pool => {
+            let quote_price =
+                *quote_price.get_or_insert_with(|| get_quote_oracle_price(env, index, now));
+            get_component_pool_price(env, index, price_data, quote_price, now)
+        }
+    }
+}
 
-    if net_amount <= 0 {
-        panic_with_error!(env, ErrorCode::TransferAmountTooSmallAfterFees);
+fn get_component_oracle_price(
+    env: &Env,
+    index: &Index,
+    market: &Address,
+    price_data: &MarketPriceData,
+    now: u64,
+) -> i128 {
+    let validity_guard_rails = OracleGuardRails::default().validity;
+    let oracle_price_data = get_oracle_price(
+        env,
+        &price_data.oracle_source,
+        &price_data.oracle,
+        (price_data.symbol.clone(), USD),
+        now,
+    );
+
+    let oracle_validity = oracle_validity(
+        env,
+        market.clone(),
+        price_data.last_oracle_price_twap,
+        &oracle_price_data,
+        &validity_guard_rails,
+        1,
+        false,
+    );
+    validate_oracle_for_action(env, oracle_validity, NormalAction::IndexPricing);
+
+    if oracle_price_data.delay > index.max_price_staleness {
+        log!(
+            env,
+            "Oracle price for {} is {}s old",
+            price_data.symbol,
+            oracle_price_data.delay
+        );
+        panic_with_error!(env, ErrorCode::StaleIndexComponentPrice);
     }
 
-    Ok(TransferWithFees {
-        protocol_fee_amount,
-        manager_fee_amount,
-        total_fees,
-        net_amount,
-    })
+    oracle_price_data.price.cast(env)
 }
 
-fn convert_index_token_amount_to_quote_amount(
+fn get_component_pool_price(
     env: &Env,
     index: &Index,
-    index_token_amount: i128,
-    index_price: i128,
+    price_data: &MarketPriceData,
+    quote_price: i128,
     now: u64,
-) -> NormalResult<(i128, i128)> {
-    // Get quote asset price
-    let oracle_price_data =
-        get_oracle_price(env, &index.oracle_source, &index.oracle, (XLM, USD), now)?;
-
-    // let oracle_validity = oracle_validity(
-    //     env,
-    //     String::from(env, "Hello, Soroban!"),
-    //     pool.historical_oracle_data.last_oracle_price_twap,
-    //     &oracle_price_data,
-    //     oracle_guard_rails().validity, // import from Oracle module
-    //     2,
-    //     false
-    // )?;
-
-    // validate!(
-    //     is_oracle_valid_for_action(oracle_validity, action)?,
-    //     ErrorCode::InvalidOracle,
-    //     "Invalid Oracle ({} vs ema={}) for index={} and action={}",
-    //     oracle_price_data,
-    //     risk_ema_price,
-    //     market.name,
-    //     action
-    // )?;
+) -> i128 {
+    if now.saturating_sub(price_data.last_price_ts) > index.max_price_staleness {
+        log!(
+            env,
+            "Pool price for {} last updated at {}",
+            price_data.symbol,
+            price_data.last_price_ts
+        );
+        panic_with_error!(env, ErrorCode::StaleIndexComponentPrice);
+    }
 
-    // Compute amount of quote asset needed
-    let quote_token_amount = index_price
-        .safe_mul(index_token_amount, env)?
-        .safe_div(oracle_price_data.price.cast::<i128>(env)?, env)?;
+    sqrt_price_to_price(env, price_data.sqrt_price)
+        .cast::<i128>(env)
+        .safe_mul(quote_price, env)
+        .safe_div(PRICE_PRECISION.cast(env), env)
+}
 
-    Ok((
-        quote_token_amount,
-        oracle_price_data.price.cast::<i128>(env)?,
-    ))
+/// XLM price in USD from the index oracle
+fn get_quote_oracle_price(env: &Env, index: &Index, now: u64) -> i128 {
+    let validity_guard_rails = OracleGuardRails::default().validity;
+    let oracle_price_data = get_oracle_price_with_feeds(
+        env,
+        &index.oracle_source,
+        &index.oracle,
+        &index.oracle_feeds,
+        (XLM, USD),
+        now,
+        &validity_guard_rails,
+    );
+
+    let oracle_validity = oracle_validity(
+        env,
+        index.oracle.clone(),
+        index.historical_oracle_data.last_oracle_price_twap,
+        &oracle_price_data,
+        &validity_guard_rails,
+        1,
+        false,
+    );
+    validate_oracle_for_action(env, oracle_validity, NormalAction::IndexPricing);
+
+    oracle_price_data.price.cast(env)
 }
 
-fn get_index_price(env: &Env, index: &Index) -> NormalResult<i128> {
-    let current_nav = calculate_current_nav(env, index.component_balances.clone())?;
+fn get_market_price_data(env: &Env, market: &Address) -> MarketPriceData {
+    env.invoke_contract(market, &Symbol::new(env, "query_price_data"), vec![env])
+}
 
-    let price = current_nav
-        .safe_div(index.base_nav, env)?
-        .safe_mul(index.initial_price, env)?;
+/// Returns the total supply of an index that can be minted/redeemed in-kind by `sender`
+fn validate_in_kind_participant(env: &Env, index: &Index, sender: &Address) -> i128 {
+    if !index.is_authorized_participant(sender) {
+        log!(
+            env,
+            "Index Token: {} is not an authorized participant",
+            sender
+        );
+        panic_with_error!(env, ErrorCode::UnauthorizedParticipant);
+    }
 
-    Ok(price)
+    let total_supply = index.total_supply();
+    if total_supply <= 0 || index.component_balances.is_empty() {
+        panic_with_error!(env, ErrorCode::EmptyIndex);
+    }
+
+    total_supply
 }
 
-fn calculate_current_nav(_env: &Env, component_balances: Map<Address, i128>) -> NormalResult<i128> {
-    let mut nav = 0;
+/// Minimum output of a swap of `offer_amount` at oracle prices (USD, PRICE_PRECISION),
+/// less `index.max_swap_slippage`. Synth and quote tokens share the same decimals.
+fn get_swap_min_out(
+    env: &Env,
+    index: &Index,
+    offer_amount: i128,
+    offer_price: i128,
+    ask_price: i128,
+) -> i128 {
+    offer_amount
+        .safe_mul(offer_price, env)
+        .safe_div(ask_price, env)
+        .safe_mul(
+            PERCENTAGE_PRECISION_I128.safe_sub(index.max_swap_slippage, env),
+            env,
+        )
+        .safe_div(PERCENTAGE_PRECISION_I128, env)
+}
 
-    component_balances
+/// Splits `quote_amount` across the components by weight. Each buy must fill within
+/// `index.max_swap_slippage` of the component's oracle value.
+fn get_component_buys(env: &Env, index: &Index, quote_amount: i128, now: u64) -> Vec<Swap> {
+    let total_weight = index
+        .component_assets
         .iter()
-        .for_each(|(_token_address, token_balance)| {
-            // TODO: Fetch the asset price from the synth token AMM
-            let price = 0;
+        .fold(0i128, |acc, asset| acc.safe_add(asset.weight, env));
+
+    let mut operations: Vec<Swap> = Vec::new(env);
+    if total_weight == 0 {
+        return operations;
+    }
 
-            // Add total value to NAV
-            nav += token_balance * price;
+    let quote_price = get_quote_oracle_price(env, index, now);
+
+    for asset in index.component_assets.iter() {
+        let price_data = get_market_price_data(env, &asset.market);
+        let component_price =
+            get_component_oracle_price(env, index, &asset.market, &price_data, now);
+
+        let offer_amount = quote_amount
+            .safe_mul(asset.weight, env)
+            .safe_div(total_weight, env);
+
+        operations.push_back(Swap {
+            market: asset.market,
+            ask_asset: price_data.synth_token,
+            offer_asset: index.quote_token.clone(),
+            offer_amount,
+            ask_asset_min_amount: Some(get_swap_min_out(
+                env,
+                index,
+                offer_amount,
+                quote_price,
+                component_price,
+            )),
         });
+    }
 
-    Ok(nav)
+    operations
 }
 
-fn swap_and_update_component_balances(_env: &Env, _operations: Vec<Swap>, _index: &Index) {
-    // let index_factory_client = index_factory_contract::Client::new(env, utils::get_factory(env));
+/// Sells the `index_token_amount / total_supply` share of each component balance.
+/// Each sell must fill within `index.max_swap_slippage` of the component's oracle value.
+fn get_component_sells(
+    env: &Env,
+    index: &Index,
+    index_token_amount: i128,
+    total_supply: i128,
+    now: u64,
+) -> Vec<Swap> {
+    let mut operations: Vec<Swap> = Vec::new(env);
+
+    let quote_price = get_quote_oracle_price(env, index, now);
+
+    for asset in index.component_assets.iter() {
+        let price_data = get_market_price_data(env, &asset.market);
+        let balance = index
+            .component_balances
+            .get(price_data.synth_token.clone())
+            .unwrap_or(0);
+        let component_price =
+            get_component_oracle_price(env, index, &asset.market, &price_data, now);
+
+        let offer_amount = balance
+            .safe_mul(index_token_amount, env)
+            .safe_div(total_supply, env);
+
+        operations.push_back(Swap {
+            market: asset.market,
+            ask_asset: index.quote_token.clone(),
+            offer_asset: price_data.synth_token,
+            offer_amount,
+            ask_asset_min_amount: Some(get_swap_min_out(
+                env,
+                index,
+                offer_amount,
+                component_price,
+                quote_price,
+            )),
+        });
+    }
 
-    // operations.iter().for_each(|op| {
-    //     let amm_addr: Address = index_factory_client.query_for_amm_by_market(&op.clone().asset);
+    operations
+}
 
-    //     let amm_client = amm_contract::Client::new(env, &amm_addr);
+/// Executes each swap against its market pool and books the filled amounts
+/// into `index.component_balances`
+fn swap_and_update_component_balances(env: &Env, operations: Vec<Swap>, index: &mut Index) {
+    let current_contract = env.current_contract_address();
 
-    //     swap_response = amm_client.swap(
-    //         &recipient,
-    //         &op.offer_asset,
-    //         &next_offer_amount,
-    //         &op.ask_asset_min_amount,
-    //         &max_spread_bps,
-    //         &max_allowed_fee_bps
-    //     );
+    for op in operations.iter() {
+        if op.offer_amount <= 0 {
+            continue;
+        }
+
+        let ask_balance_before = utils::get_token_balance(env, &op.ask_asset, &current_contract);
+
+        // Pools are synth (token a) / quote (token b)
+        let a_to_b = op.ask_asset == index.quote_token;
+
+        // The market pulls the offer asset from the index
+        env.authorize_as_current_contract(vec![
+            env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: op.offer_asset.clone(),
+                    fn_name: Symbol::new(env, "transfer"),
+                    args: (current_contract.clone(), op.market.clone(), op.offer_amount)
+                        .into_val(env),
+                },
+                sub_invocations: Vec::new(env),
+            }),
+        ]);
+
+        env.invoke_contract::<()>(
+            &op.market,
+            &Symbol::new(env, "swap"),
+            vec![
+                env,
+                current_contract.into_val(env),
+                op.offer_amount.cast::<u64>(env).into_val(env),
+                op.ask_asset_min_amount
+                    .unwrap_or(0)
+                    .cast::<u64>(env)
+                    .into_val(env),
+                0u128.into_val(env), // no sqrt price limit
+                true.into_val(env),  // amount is input
+                a_to_b.into_val(env),
+                Vec::<i32>::new(env).into_val(env),
+            ],
+        );
 
-    //     let signed_amount = util(swap_response);
+        let ask_amount = utils::get_token_balance(env, &op.ask_asset, &current_contract)
+            .safe_sub(ask_balance_before, env);
+
+        if a_to_b {
+            let balance = index
+                .component_balances
+                .get(op.offer_asset.clone())
+                .unwrap_or(0);
+            index.component_balances.set(
+                op.offer_asset.clone(),
+                balance.safe_sub(op.offer_amount, env),
+            );
+        } else {
+            let balance = index
+                .component_balances
+                .get(op.ask_asset.clone())
+                .unwrap_or(0);
+            index
+                .component_balances
+                .set(op.ask_asset.clone(), balance.safe_add(ask_amount, env));
+        }
+    }
 
-    //     index.component_balances[op.asset] += signed_amount;
-    // });
+    index.component_balance_update_ts = env.ledger().timestamp();
 }
@@ -1,5 +1,5 @@
-use normal::types::IndexAsset;
-use soroban_sdk::{Address, Env, String, Symbol, Vec};
+use normal::types::index::IndexAsset;
+use soroban_sdk::{Address, Env, Map, String, Symbol, Vec};
 
 pub struct IndexTokenEvents {}
 
@@ -17,6 +17,24 @@ impl IndexTokenEvents {
         env.events().publish(topics, (name, symbol));
     }
 
+    /// Emitted when accounts are added to or removed from the whitelist
+    ///
+    /// - topics - `["update_whitelist", admin: Address]`
+    /// - data - `[added: Vec<Address>, removed: Vec<Address>]`
+    pub fn update_whitelist(env: &Env, admin: Address, added: Vec<Address>, removed: Vec<Address>) {
+        let topics = (Symbol::new(env, "update_whitelist"), admin);
+        env.events().publish(topics, (added, removed));
+    }
+
+    /// Emitted when accounts are added to or removed from the blacklist
+    ///
+    /// - topics - `["update_blacklist", admin: Address]`
+    /// - data - `[added: Vec<Address>, removed: Vec<Address>]`
+    pub fn update_blacklist(env: &Env, admin: Address, added: Vec<Address>, removed: Vec<Address>) {
+        let topics = (Symbol::new(env, "update_blacklist"), admin);
+        env.events().publish(topics, (added, removed));
+    }
+
     /// Emitted when index tokens are minted
     ///
     /// - topics - `["mint", minter: Address]`
@@ -30,12 +48,58 @@ impl IndexTokenEvents {
     ///
     /// - topics - `["redeem", redeemer: Address]`
     /// - data - `[amount: i128]`
-    #[allow(dead_code)]
     pub fn redeem(env: &Env, redeemer: Address, amount: i128) {
         let topics = (Symbol::new(env, "redeem"), redeemer);
         env.events().publish(topics, amount);
     }
 
+    /// Emitted when index tokens are minted in exchange for the component basket
+    ///
+    /// - topics - `["mint_in_kind", participant: Address]`
+    /// - data - `[amount: i128, basket: Map<Address, i128>]`
+    pub fn mint_in_kind(env: &Env, participant: Address, amount: i128, basket: Map<Address, i128>) {
+        let topics = (Symbol::new(env, "mint_in_kind"), participant);
+        env.events().publish(topics, (amount, basket));
+    }
+
+    /// Emitted when index tokens are redeemed for the component basket
+    ///
+    /// - topics - `["redeem_in_kind", participant: Address]`
+    /// - data - `[amount: i128, basket: Map<Address, i128>]`
+    pub fn redeem_in_kind(
+        env: &Env,
+        participant: Address,
+        amount: i128,
+        basket: Map<Address, i128>,
+    ) {
+        let topics = (Symbol::new(env, "redeem_in_kind"), participant);
+        env.events().publish(topics, (amount, basket));
+    }
+
+    /// Emitted when streaming fees are minted to the manager and protocol treasury
+    ///
+    /// - topics - `["accrue_fees"]`
+    /// - data - `[manager_fee: i128, protocol_fee: i128]`
+    pub fn accrue_fees(env: &Env, manager_fee: i128, protocol_fee: i128) {
+        let topics = (Symbol::new(env, "accrue_fees"),);
+        env.events().publish(topics, (manager_fee, protocol_fee));
+    }
+
+    /// Emitted when a performance fee is crystallized
+    ///
+    /// - topics - `["crystallize", manager: Address]`
+    /// - data - `[high_water_mark: i128, fee: i128, minted: i128]`
+    pub fn crystallize(
+        env: &Env,
+        manager: Address,
+        high_water_mark: i128,
+        fee: i128,
+        minted: i128,
+    ) {
+        let topics = (Symbol::new(env, "crystallize"), manager);
+        env.events().publish(topics, (high_water_mark, fee, minted));
+    }
+
     /// Emitted when an index is rebalanced
     ///
     /// - topics - `["rebalance", keeper: Address]`
@@ -1,10 +1,14 @@
 use normal::{
     error::{ErrorCode, NormalResult},
-    types::{IndexAsset, IndexParams},
+    oracle::OracleFeed,
+    types::index::{IndexAccessMode, IndexAsset, IndexParams, IndexPriceSource, RebalanceStrategy},
 };
 use soroban_sdk::{contractclient, Address, Env, Vec};
 
-use crate::{msg::IndexResponse, storage::IndexOperation};
+use crate::{
+    msg::{AccruedFeesResponse, IndexResponse},
+    storage::{IndexOperation, RebalanceResult},
+};
 
 #[contractclient(name = "IndexTokenClient")]
 pub trait IndexTokenTrait {
@@ -16,6 +20,7 @@ pub trait IndexTokenTrait {
         params: IndexParams,
     ) -> Result<(), ErrorCode>;
 
+    /// Fees owed at the previous rate are accrued before the new rate applies
     fn update_manager_fee(env: Env, sender: Address, manager_fee_bps: i64);
 
     fn update_paused_operations(
@@ -25,18 +30,97 @@ pub trait IndexTokenTrait {
         to_remove: Vec<IndexOperation>,
     );
 
+    /// Sets who can mint, redeem and receive the index token. Public indexes
+    /// can't be whitelist only.
+    fn update_access_mode(env: Env, sender: Address, access_mode: IndexAccessMode);
+
     fn update_whitelist(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>);
 
     fn update_blacklist(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>);
 
+    fn update_keepers(env: Env, sender: Address, to_add: Vec<Address>, to_remove: Vec<Address>);
+
+    fn update_authorized_participants(
+        env: Env,
+        sender: Address,
+        to_add: Vec<Address>,
+        to_remove: Vec<Address>,
+    );
+
     fn update_rebalance_threshold(env: Env, sender: Address, rebalance_threshold: u64);
 
+    /// Sets how keepers derive target weights, how far components must drift
+    /// before they can rebalance and the index tokens they are rewarded with
+    fn update_rebalance_strategy(
+        env: Env,
+        sender: Address,
+        rebalance_strategy: RebalanceStrategy,
+        drift_tolerance: i128,
+        keeper_reward: i128,
+    );
+
+    /// Sets the share of gains above the high-water mark paid to the manager and
+    /// how often it can be crystallized. Gains so far are crystallized at the old
+    /// rate first. `performance_fee_period` must be positive.
+    fn update_performance_fee(
+        env: Env,
+        sender: Address,
+        performance_fee_bps: i64,
+        performance_fee_period: u64,
+    );
+
+    /// Sets the max weight change per component and max turnover allowed in a
+    /// single rebalance (PERCENTAGE_PRECISION)
+    fn update_rebalance_guardrails(
+        env: Env,
+        sender: Address,
+        max_weight_change: i128,
+        max_turnover: i128,
+    );
+
+    /// Sets how far below its oracle value a component swap may fill
+    /// (PERCENTAGE_PRECISION)
+    fn update_max_swap_slippage(env: Env, sender: Address, max_swap_slippage: i128);
+
+    fn update_oracle_feeds(env: Env, sender: Address, oracle_feeds: Vec<OracleFeed>);
+
+    /// Sets where component prices are read from and how old they can be
+    fn update_price_source(
+        env: Env,
+        sender: Address,
+        price_source: IndexPriceSource,
+        max_price_staleness: u64,
+    );
+
     // ################################################################
     //                              KEEPER
     // ################################################################
 
+    /// Trades the components towards the weights in `updated_assets`, which must
+    /// sum to 100% (PERCENTAGE_PRECISION).
+    ///
+    /// Current weights are measured from the NAV. Overweight components are sold
+    /// first and the proceeds are split across underweight components by their
+    /// weight deficit. Components left out of `updated_assets` are sold in full.
     fn rebalance(env: Env, sender: Address, updated_assets: Vec<IndexAsset>);
 
+    /// Rebalances towards the weights given by `rebalance_strategy`. Can be called
+    /// by the admin or a keeper once `rebalance_threshold` has passed and a component
+    /// has drifted more than `drift_tolerance` from its target. The caller is rewarded
+    /// with `keeper_reward` index tokens.
+    fn execute_scheduled_rebalance(env: Env, sender: Address);
+
+    /// Mints the streaming manager and protocol fees accrued since the last
+    /// state-changing call to the manager and protocol treasury. Permissionless
+    /// poke: anyone can call it since fees only ever go to those two accounts.
+    fn collect_fees(env: Env, sender: Address);
+
+    /// Charges `performance_fee_bps` of the index price gain above the high-water
+    /// mark by minting index tokens to the manager, then raises the high-water mark
+    /// to the post-mint price. Can be called by the admin or a keeper once every
+    /// `performance_fee_period`.
+    fn crystallize_performance_fee(env: Env, sender: Address);
+
     // ################################################################
     //                             USER
     // ################################################################
@@ -45,15 +129,66 @@ pub trait IndexTokenTrait {
 
     fn redeem(env: Env, sender: Address, index_token_amount: i128) -> NormalResult;
 
+    /// Mints like `mint`, reverting if the quote spent on the component swaps
+    /// exceeds `max_quote_in` or the ledger timestamp is past `deadline`
+    fn mint_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        max_quote_in: i128,
+        deadline: u64,
+    ) -> NormalResult;
+
+    /// Redeems like `redeem`, reverting if the quote received from the component
+    /// swaps is below `min_quote_out` or the ledger timestamp is past `deadline`
+    fn redeem_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        min_quote_out: i128,
+        deadline: u64,
+    ) -> NormalResult;
+
+    // ################################################################
+    //                     AUTHORIZED PARTICIPANT
+    // ################################################################
+
+    /// Mints index tokens in exchange for the exact basket of component tokens,
+    /// in proportion to `component_balances` (ETF-style creation unit).
+    /// Amounts owed are rounded up in favour of the index.
+    fn mint_in_kind(env: Env, sender: Address, index_token_amount: i128) -> NormalResult;
+
+    /// Burns index tokens in exchange for their share of each component balance,
+    /// with no swaps or slippage. Amounts paid out are rounded down.
+    fn redeem_in_kind(env: Env, sender: Address, index_token_amount: i128) -> NormalResult;
+
     // ################################################################
     //                             QUERIES
     // ################################################################
 
     fn query_index(env: Env) -> IndexResponse;
 
-    // fn query_price(env: Env) -> i128;
+    /// Returns the index token price in USD
+    /// precision: PRICE_PRECISION
+    fn query_price(env: Env) -> i128;
+
+    /// Returns the Net Asset Value of the index components in USD, in the same
+    /// units as `base_nav`. The index price is `nav * PRICE_PRECISION / total_supply`.
+    fn query_nav(env: Env) -> i128;
+
+    /// Returns the quote amount `index_token_amount` index tokens are worth at the
+    /// current index price, before swap slippage
+    fn query_quote_amount(env: Env, index_token_amount: i128) -> i128;
+
+    /// Returns the index tokens that `collect_fees` would mint now
+    fn query_accrued_fees(env: Env) -> AccruedFeesResponse;
+
+    /// Returns the outcome of the last rebalance, if any
+    fn query_rebalance_result(env: Env) -> Option<RebalanceResult>;
 
-    // fn query_nav(env: Env) -> i128;
+    /// Panics if `account` is blocked by the blacklist or access mode. Lets
+    /// contracts that mint and redeem on behalf of users check the beneficiary.
+    fn check_account_access(env: Env, account: Address);
 
     // fn query_index_info_for_factory(env: Env) -> IndexInfo;
 }
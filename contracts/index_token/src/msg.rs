@@ -7,3 +7,11 @@ use crate::storage::Index;
 pub struct IndexResponse {
     pub index: Index,
 }
+
+/// Index tokens owed to the manager and protocol treasury since the last accrual
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccruedFeesResponse {
+    pub manager_fee: i128,
+    pub protocol_fee: i128,
+}
@@ -1,7 +1,7 @@
 use normal::{
     constants::{PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD},
-    oracle::OracleSource,
-    types::IndexAsset,
+    oracle::{HistoricalOracleData, OracleFeed, OracleSource},
+    types::index::{IndexAccessMode, IndexAsset, IndexPriceSource, RebalanceStrategy},
 };
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, Vec};
 
@@ -31,6 +31,7 @@ pub enum DataKey {
     State(Address),
     Admin,
     Initialized,
+    RebalanceResult,
 }
 
 // ################################################################
@@ -55,17 +56,42 @@ pub struct Index {
     pub oracle: Address,
     /// Oracle type
     pub oracle_source: OracleSource,
+    /// Additional feeds aggregated with `oracle`, empty to read `oracle` on its own
+    pub oracle_feeds: Vec<OracleFeed>,
+    /// Historically witnessed quote asset oracle data
+    pub historical_oracle_data: HistoricalOracleData,
+    /// Where component prices are read from to compute the NAV
+    pub price_source: IndexPriceSource,
+    /// Max seconds since a component price was last updated for it to be used in the NAV
+    pub max_price_staleness: u64,
     /// Private indexes are mutable and can only be minted by the admin and whitelist
     /// Pubilic indexes are immutabel and can be minted by anyone
     pub is_public: bool,
+    /// Who can mint, redeem and receive the index token, always `Public` for
+    /// public indexes
+    pub access_mode: IndexAccessMode,
     /// List of operations temporarily paused
     pub paused_operations: Vec<IndexOperation>,
     /// A custom annual fee set by the admin
     pub manager_fee_bps: i64,
-    /// List of accounts explicitly allowed to mint the index
+    /// The ts when the streaming manager and protocol fees were last accrued
+    pub fee_accrual_ts: u64,
+    /// Share of the gain above `high_water_mark` paid to the manager when crystallized
+    pub performance_fee_bps: i64,
+    /// Minimum amount of time between performance fee crystallizations
+    pub performance_fee_period: u64,
+    /// Highest index price a performance fee has been crystallized at (PRICE_PRECISION)
+    pub high_water_mark: i128,
+    /// The ts when the performance fee was last crystallized
+    pub performance_fee_ts: u64,
+    /// List of accounts allowed to run keeper operations alongside the admin
+    pub keepers: Vec<Address>,
+    /// List of accounts allowed to access the index in `WhitelistOnly` mode
     pub whitelist: Vec<Address>,
-    /// List of accounts blocked from minting the index
+    /// List of accounts blocked from the index in every access mode
     pub blacklist: Vec<Address>,
+    /// List of accounts allowed to mint and redeem in-kind with the component basket
+    pub authorized_participants: Vec<Address>,
     /// The Net Asset Value (NAV) at the inception of the index - what the creator deposits (e.g. $1,000)
     pub base_nav: i128,
     /// The price assigned to the index at inception (e.g. $100)
@@ -76,6 +102,18 @@ pub struct Index {
     pub component_assets: Vec<IndexAsset>,
     /// Minimum amount of time that must pass before the index can be rebalanced again
     pub rebalance_threshold: u64,
+    /// How target weights are derived for keeper rebalances
+    pub rebalance_strategy: RebalanceStrategy,
+    /// Min drift of a component from its target weight for keepers to rebalance (PERCENTAGE_PRECISION)
+    pub drift_tolerance: i128,
+    /// Index tokens minted to the keeper for each scheduled rebalance
+    pub keeper_reward: i128,
+    /// Max change of a component's weight in a single rebalance (PERCENTAGE_PRECISION)
+    pub max_weight_change: i128,
+    /// Max share of the NAV traded in a single rebalance (PERCENTAGE_PRECISION)
+    pub max_turnover: i128,
+    /// Max shortfall of a component swap's output from its oracle value (PERCENTAGE_PRECISION)
+    pub max_swap_slippage: i128,
     /// The ts when the index was last rebalanced
     pub rebalance_ts: u64,
     /// The ts when the index was last updated (any property)
@@ -87,8 +125,24 @@ pub struct Index {
 }
 
 impl Index {
-    pub fn can_invest(&self, account: Address) -> bool {
-        self.whitelist.contains(&account)
+    pub fn is_whitelisted(&self, account: &Address) -> bool {
+        self.whitelist.contains(account)
+    }
+
+    pub fn is_blacklisted(&self, account: &Address) -> bool {
+        self.blacklist.contains(account)
+    }
+
+    pub fn is_keeper(&self, account: &Address) -> bool {
+        self.keepers.contains(account)
+    }
+
+    pub fn can_crystallize(&self, now: u64) -> bool {
+        now.saturating_sub(self.performance_fee_ts) >= self.performance_fee_period
+    }
+
+    pub fn is_authorized_participant(&self, account: &Address) -> bool {
+        self.authorized_participants.contains(account)
     }
 
     pub fn can_rebalance(&self, now: u64) -> bool {
@@ -98,6 +152,10 @@ impl Index {
     pub fn time_since_last_rebalance(&self, now: u64) -> u64 {
         now - self.rebalance_ts
     }
+
+    pub fn total_supply(&self) -> i128 {
+        self.total_mints - self.total_redemptions
+    }
 }
 
 pub fn save_index(env: &Env, index: Index) {
@@ -126,63 +184,79 @@ pub fn get_index(env: &Env) -> Index {
 }
 
 // ################################################################
-//                         Last Transfer
+//                           Rebalance
 // ################################################################
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LastTransfer {
-    pub balance: i128,
-    pub ts: u64,
+pub struct ComponentWeight {
+    pub market: Address,
+    /// Weight of the component by NAV before the rebalance (PERCENTAGE_PRECISION)
+    pub current_weight: i128,
+    pub target_weight: i128,
 }
 
-pub fn get_last_transfer(env: &Env, key: &Address) -> LastTransfer {
-    let last_transfer = match env.storage().persistent().get::<_, LastTransfer>(key) {
-        Some(transfer) => transfer,
-        None => LastTransfer {
-            balance: 0i128,
-            ts: 0u64, // current_time
-        },
-    };
-    env.storage().persistent().has(&key).then(|| {
-        env.storage().persistent().extend_ttl(
-            &key,
-            PERSISTENT_LIFETIME_THRESHOLD,
-            PERSISTENT_BUMP_AMOUNT,
-        );
-    });
-
-    last_transfer
+/// Outcome of the last rebalance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RebalanceResult {
+    pub ts: u64,
+    pub keeper: Address,
+    /// NAV before the rebalance
+    pub nav: i128,
+    /// Share of the NAV traded, half the sum of absolute weight changes (PERCENTAGE_PRECISION)
+    pub turnover: i128,
+    /// Quote received from selling overweight components
+    pub quote_sold: i128,
+    /// Quote spent buying underweight components
+    pub quote_bought: i128,
+    pub weights: Vec<ComponentWeight>,
 }
 
-pub fn save_last_transfer(env: &Env, key: &Address, last_transfer: &LastTransfer) {
-    env.storage().persistent().set(key, last_transfer);
+pub fn save_rebalance_result(env: &Env, result: &RebalanceResult) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RebalanceResult, result);
     env.storage().persistent().extend_ttl(
-        &key,
+        &DataKey::RebalanceResult,
         PERSISTENT_LIFETIME_THRESHOLD,
         PERSISTENT_BUMP_AMOUNT,
     );
 }
 
+pub fn get_rebalance_result(env: &Env) -> Option<RebalanceResult> {
+    let result = env
+        .storage()
+        .persistent()
+        .get::<_, RebalanceResult>(&DataKey::RebalanceResult);
+
+    env.storage()
+        .persistent()
+        .has(&DataKey::RebalanceResult)
+        .then(|| {
+            env.storage().persistent().extend_ttl(
+                &DataKey::RebalanceResult,
+                PERSISTENT_LIFETIME_THRESHOLD,
+                PERSISTENT_BUMP_AMOUNT,
+            );
+        });
+
+    result
+}
+
 // ################################################################
 
+/// A component trade routed through the component's synth market pool
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Swap {
+    pub market: Address,
     pub ask_asset: Address,
     pub offer_asset: Address,
+    pub offer_amount: i128,
     pub ask_asset_min_amount: Option<i128>,
 }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TransferWithFees {
-    pub protocol_fee_amount: i128,
-    pub manager_fee_amount: i128,
-    pub total_fees: i128,
-    pub net_amount: i128,
-}
-
 // ################################################################
 
 pub mod utils {
@@ -202,6 +276,20 @@ pub mod utils {
         token_client.transfer(from, to, &amount);
     }
 
+    /// Pulls `amount` of `token` from `from` into the index. An allowance granted to the
+    /// index is spent when it covers the amount, so contracts (e.g. the scheduler) can
+    /// fund a mint without knowing the exact quote it costs up front.
+    pub fn receive_token(env: &Env, token: &Address, from: &Address, amount: i128) {
+        let token_client = token_contract::Client::new(env, token);
+        let current_contract = env.current_contract_address();
+
+        if token_client.allowance(from, &current_contract) >= amount {
+            token_client.transfer_from(&current_contract, from, &current_contract, &amount);
+        } else {
+            token_client.transfer(from, &current_contract, &amount);
+        }
+    }
+
     pub fn check_nonnegative_amount(amount: i128) {
         if amount < 0 {
             panic!("negative amount is not allowed: {}", amount)
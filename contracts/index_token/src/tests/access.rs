@@ -0,0 +1,172 @@
+use normal::types::index::IndexAccessMode;
+use soroban_sdk::{testutils::Address as _, vec, Address};
+
+use super::setup::{IndexTest, INITIAL_SUPPLY, UNIT};
+
+#[test]
+fn public_index_is_open_to_everyone() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+
+    test.index.mint(&user, &UNIT).unwrap();
+    test.index.check_account_access(&user);
+
+    let other = Address::generate(&test.env);
+    test.index.transfer(&user, &other, &UNIT);
+    assert_eq!(test.index.balance(&other), UNIT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #42)")]
+fn public_whitelist_only_index_should_fail() {
+    IndexTest::with_params(|params| params.access_mode = IndexAccessMode::WhitelistOnly);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #42)")]
+fn update_public_index_to_whitelist_only_should_fail() {
+    let test = IndexTest::new();
+
+    test.index
+        .update_access_mode(&test.admin, &IndexAccessMode::WhitelistOnly);
+}
+
+#[test]
+fn whitelist_only_index_accepts_whitelisted_accounts() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+    let user = test.create_user(1_000 * UNIT);
+    test.index.update_whitelist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+
+    test.index.mint(&user, &UNIT).unwrap();
+    test.index.redeem(&user, &(UNIT / 2)).unwrap();
+    assert_eq!(test.index.balance(&user), UNIT / 2);
+
+    // The admin is always allowed
+    test.index.transfer(&user, &test.admin, &(UNIT / 2));
+    assert_eq!(test.index.balance(&test.admin), INITIAL_SUPPLY + UNIT / 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #33)")]
+fn whitelist_only_index_mint_by_non_whitelisted_account_should_fail() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+    let user = test.create_user(1_000 * UNIT);
+
+    test.index.mint(&user, &UNIT).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #33)")]
+fn whitelist_only_index_transfer_to_non_whitelisted_account_should_fail() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+
+    test.index
+        .transfer(&test.admin, &Address::generate(&test.env), &UNIT);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #33)")]
+fn whitelist_only_index_redeem_after_removal_from_whitelist_should_fail() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+    let user = test.create_user(1_000 * UNIT);
+    test.index.update_whitelist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+    test.index.mint(&user, &UNIT).unwrap();
+
+    test.index.update_whitelist(
+        &test.admin,
+        &vec![&test.env],
+        &vec![&test.env, user.clone()],
+    );
+
+    test.index.redeem(&user, &UNIT).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #34)")]
+fn public_index_mint_by_blacklisted_account_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+    test.index.update_blacklist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+
+    test.index.mint(&user, &UNIT).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #34)")]
+fn whitelist_only_index_blacklist_overrides_whitelist() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+    let user = Address::generate(&test.env);
+    test.index.update_whitelist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+    test.index.update_blacklist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+
+    test.index.check_account_access(&user);
+}
+
+#[test]
+fn removing_account_from_blacklist_restores_access() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+    test.index.update_blacklist(
+        &test.admin,
+        &vec![&test.env, user.clone()],
+        &vec![&test.env],
+    );
+    test.index.update_blacklist(
+        &test.admin,
+        &vec![&test.env],
+        &vec![&test.env, user.clone()],
+    );
+
+    test.index.mint(&user, &UNIT).unwrap();
+    assert_eq!(test.index.balance(&user), UNIT);
+}
+
+#[test]
+fn whitelist_only_index_mints_fees_to_treasury() {
+    let test = IndexTest::with_params(|params| {
+        params.is_public = false;
+        params.access_mode = IndexAccessMode::WhitelistOnly;
+    });
+    test.factory.update_protocol_fee(&100);
+
+    test.jump(86_400);
+    test.index.collect_fees(&test.admin);
+    test.index.check_account_access(&test.treasury);
+
+    assert!(test.index.balance(&test.treasury) > 0);
+}
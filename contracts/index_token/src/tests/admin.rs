@@ -0,0 +1,70 @@
+use normal::{constants::PERCENTAGE_PRECISION_I128, types::index::RebalanceStrategy};
+
+use super::setup::IndexTest;
+
+#[test]
+fn update_rebalance_params() {
+    let test = IndexTest::new();
+
+    test.index.update_rebalance_strategy(
+        &test.admin,
+        &RebalanceStrategy::EqualWeight,
+        &20_000,
+        &1_000,
+    );
+    test.index
+        .update_rebalance_guardrails(&test.admin, &200_000, &300_000);
+    test.index.update_max_swap_slippage(&test.admin, &5_000);
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.rebalance_strategy, RebalanceStrategy::EqualWeight);
+    assert_eq!(index.drift_tolerance, 20_000);
+    assert_eq!(index.keeper_reward, 1_000);
+    assert_eq!(index.max_weight_change, 200_000);
+    assert_eq!(index.max_turnover, 300_000);
+    assert_eq!(index.max_swap_slippage, 5_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #44)")]
+fn update_rebalance_strategy_with_drift_tolerance_above_100_percent_should_fail() {
+    let test = IndexTest::new();
+
+    test.index.update_rebalance_strategy(
+        &test.admin,
+        &RebalanceStrategy::EqualWeight,
+        &(PERCENTAGE_PRECISION_I128 + 1),
+        &0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #44)")]
+fn update_rebalance_strategy_with_negative_keeper_reward_should_fail() {
+    let test = IndexTest::new();
+
+    test.index.update_rebalance_strategy(
+        &test.admin,
+        &RebalanceStrategy::EqualWeight,
+        &20_000,
+        &-1,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #44)")]
+fn update_rebalance_guardrails_with_negative_turnover_should_fail() {
+    let test = IndexTest::new();
+
+    test.index
+        .update_rebalance_guardrails(&test.admin, &100_000, &-1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #44)")]
+fn update_max_swap_slippage_above_100_percent_should_fail() {
+    let test = IndexTest::new();
+
+    test.index
+        .update_max_swap_slippage(&test.admin, &(PERCENTAGE_PRECISION_I128 + 1));
+}
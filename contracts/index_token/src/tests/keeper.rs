@@ -0,0 +1,277 @@
+use normal::{
+    constants::ONE_YEAR,
+    types::index::{IndexAsset, RebalanceStrategy},
+};
+use soroban_sdk::{testutils::Address as _, vec, Address, Vec};
+
+use super::setup::{
+    IndexTest, BTC_PRICE, ETH_PRICE, INITIAL_PRICE, INITIAL_SUPPLY, UNIT, XLM_PRICE,
+};
+
+const ONE_DAY: u64 = 86_400;
+
+fn weights(test: &IndexTest, btc_weight: i128, eth_weight: i128) -> Vec<IndexAsset> {
+    vec![
+        &test.env,
+        IndexAsset {
+            market: test.btc.address.clone(),
+            weight: btc_weight,
+            last_updated_ts: 0,
+        },
+        IndexAsset {
+            market: test.eth.address.clone(),
+            weight: eth_weight,
+            last_updated_ts: 0,
+        },
+    ]
+}
+
+#[test]
+fn rebalance_within_guardrails_trades_to_target_weights() {
+    let test = IndexTest::new();
+    let nav = test.index.query_nav();
+
+    test.index
+        .rebalance(&test.admin, &weights(&test, 550_000, 450_000));
+
+    // 5% of the $5,000 NAV moves from ETH to BTC
+    assert_eq!(test.component_balance(&test.btc), 275 * UNIT / 10);
+    assert_eq!(test.component_balance(&test.eth), 1_125 * UNIT / 10);
+    assert_eq!(test.index.query_nav(), nav);
+
+    let result = test.index.query_rebalance_result().unwrap();
+    assert_eq!(result.nav, nav);
+    assert_eq!(result.turnover, 50_000);
+    assert_eq!(result.quote_sold, 500 * UNIT);
+    assert_eq!(result.quote_bought, 500 * UNIT);
+    assert_eq!(result.weights.get_unchecked(0).current_weight, 500_000);
+    assert_eq!(result.weights.get_unchecked(0).target_weight, 550_000);
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.component_assets.get_unchecked(0).weight, 550_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #29)")]
+fn rebalance_above_max_weight_change_should_fail() {
+    let test = IndexTest::new();
+
+    // Default max weight change is 10%
+    test.index
+        .rebalance(&test.admin, &weights(&test, 650_000, 350_000));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #30)")]
+fn rebalance_above_max_turnover_should_fail() {
+    let test = IndexTest::new();
+    test.index
+        .update_rebalance_guardrails(&test.admin, &1_000_000, &100_000);
+
+    test.index
+        .rebalance(&test.admin, &weights(&test, 650_000, 350_000));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn rebalance_with_swap_beyond_max_swap_slippage_should_fail() {
+    let test = IndexTest::new();
+
+    // ETH sells fill 2% short of the oracle value
+    test.eth.client.set_fill_bps(&9_800);
+
+    test.index
+        .rebalance(&test.admin, &weights(&test, 550_000, 450_000));
+}
+
+#[test]
+fn collect_fees_streams_fees_over_time() {
+    let test = IndexTest::with_params(|params| params.manager_fee_bps = 100);
+    test.factory.update_protocol_fee(&50);
+    let anyone = Address::generate(&test.env);
+
+    // Half a year at 1% and 0.5% a year on 50 tokens
+    test.jump(ONE_YEAR as u64 / 2);
+    let fees = test.index.query_accrued_fees();
+    assert_eq!(fees.manager_fee, UNIT / 4);
+    assert_eq!(fees.protocol_fee, UNIT / 8);
+
+    test.index.collect_fees(&anyone);
+
+    assert_eq!(test.index.balance(&test.admin), INITIAL_SUPPLY + UNIT / 4);
+    assert_eq!(test.index.balance(&test.treasury), UNIT / 8);
+    let index = test.index.query_index().index;
+    assert_eq!(index.total_fees, 3 * UNIT / 8);
+    assert_eq!(index.total_supply(), INITIAL_SUPPLY + 3 * UNIT / 8);
+
+    // Nothing more accrues until time passes
+    let fees = test.index.query_accrued_fees();
+    assert_eq!(fees.manager_fee, 0);
+    assert_eq!(fees.protocol_fee, 0);
+
+    // The next half year accrues on the diluted supply
+    test.jump(ONE_YEAR as u64 / 2);
+    test.index.collect_fees(&anyone);
+
+    let supply = INITIAL_SUPPLY + 3 * UNIT / 8;
+    assert_eq!(
+        test.index.balance(&test.admin),
+        INITIAL_SUPPLY + UNIT / 4 + supply / 200
+    );
+    assert_eq!(test.index.balance(&test.treasury), UNIT / 8 + supply / 400);
+}
+
+#[test]
+fn crystallize_performance_fee_charges_gain_above_high_water_mark() {
+    let test = IndexTest::new();
+    test.index
+        .update_performance_fee(&test.admin, &2_000, &ONE_DAY);
+    assert_eq!(
+        test.index.query_index().index.high_water_mark,
+        INITIAL_PRICE
+    );
+
+    // BTC doubles, taking the price from $100 to $150
+    test.set_prices(XLM_PRICE, BTC_PRICE * 2, ETH_PRICE);
+    test.jump(ONE_DAY);
+    test.index.crystallize_performance_fee(&test.admin);
+
+    // 20% of the $2,500 gain is $500, minted net of its own dilution
+    let minted = 500 * UNIT * INITIAL_SUPPLY / (7_500 * UNIT - 500 * UNIT);
+    assert_eq!(test.index.balance(&test.admin), INITIAL_SUPPLY + minted);
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.total_fees, minted);
+    assert_eq!(index.high_water_mark, 140_000_000);
+    assert_eq!(index.high_water_mark, test.index.query_price());
+
+    // No gain above the high-water mark, nothing is charged
+    test.jump(ONE_DAY);
+    test.index.crystallize_performance_fee(&test.admin);
+    assert_eq!(test.index.balance(&test.admin), INITIAL_SUPPLY + minted);
+    assert_eq!(test.index.query_index().index.high_water_mark, 140_000_000);
+
+    // A drawdown and recovery back to the high-water mark isn't charged twice
+    test.set_prices(XLM_PRICE, BTC_PRICE, ETH_PRICE);
+    test.jump(ONE_DAY);
+    test.index.crystallize_performance_fee(&test.admin);
+    test.set_prices(XLM_PRICE, BTC_PRICE * 2, ETH_PRICE);
+    test.jump(ONE_DAY);
+    test.index.crystallize_performance_fee(&test.admin);
+    assert_eq!(test.index.balance(&test.admin), INITIAL_SUPPLY + minted);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #32)")]
+fn crystallize_performance_fee_twice_in_a_period_should_fail() {
+    let test = IndexTest::new();
+    test.index
+        .update_performance_fee(&test.admin, &2_000, &ONE_DAY);
+
+    test.jump(ONE_DAY);
+    test.index.crystallize_performance_fee(&test.admin);
+    test.index.crystallize_performance_fee(&test.admin);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #31)")]
+fn crystallize_performance_fee_by_non_keeper_should_fail() {
+    let test = IndexTest::new();
+    test.index
+        .update_performance_fee(&test.admin, &2_000, &ONE_DAY);
+
+    test.jump(ONE_DAY);
+    test.index
+        .crystallize_performance_fee(&Address::generate(&test.env));
+}
+
+/// Equal weight strategy with BTC up 50%, so the index has drifted to 60/40
+fn drifted_equal_weight_index<'a>() -> (IndexTest<'a>, Address) {
+    let test = IndexTest::new();
+    let keeper = Address::generate(&test.env);
+    test.index.update_keepers(
+        &test.admin,
+        &vec![&test.env, keeper.clone()],
+        &vec![&test.env],
+    );
+    test.index.update_rebalance_strategy(
+        &test.admin,
+        &RebalanceStrategy::EqualWeight,
+        &50_000,
+        &UNIT,
+    );
+
+    test.set_prices(XLM_PRICE, BTC_PRICE * 3 / 2, ETH_PRICE);
+
+    (test, keeper)
+}
+
+#[test]
+fn execute_scheduled_rebalance_trades_drifted_index_back_to_strategy_weights() {
+    let (test, keeper) = drifted_equal_weight_index();
+
+    test.index.execute_scheduled_rebalance(&keeper);
+
+    let result = test.index.query_rebalance_result().unwrap();
+    assert_eq!(result.keeper, keeper);
+    assert_eq!(result.turnover, 100_000);
+    assert_eq!(result.weights.get_unchecked(0).current_weight, 600_000);
+    assert_eq!(result.weights.get_unchecked(0).target_weight, 500_000);
+    assert_eq!(result.weights.get_unchecked(1).current_weight, 400_000);
+    assert_eq!(result.weights.get_unchecked(1).target_weight, 500_000);
+
+    // $625 of BTC sold into ETH, less rounding dust
+    assert!(1_250 * UNIT - result.quote_sold < 1_000);
+    assert_eq!(result.quote_bought, result.quote_sold);
+    assert_eq!(test.index.balance(&keeper), UNIT);
+    assert_eq!(
+        test.index.query_index().index.total_supply(),
+        INITIAL_SUPPLY + UNIT
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #31)")]
+fn execute_scheduled_rebalance_by_non_keeper_should_fail() {
+    let (test, _) = drifted_equal_weight_index();
+
+    test.index
+        .execute_scheduled_rebalance(&Address::generate(&test.env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #35)")]
+fn execute_scheduled_rebalance_within_drift_tolerance_should_fail() {
+    let (test, keeper) = drifted_equal_weight_index();
+
+    // 10% drift is within a 15% tolerance
+    test.index.update_rebalance_strategy(
+        &test.admin,
+        &RebalanceStrategy::EqualWeight,
+        &150_000,
+        &UNIT,
+    );
+
+    test.index.execute_scheduled_rebalance(&keeper);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn execute_scheduled_rebalance_before_threshold_should_fail() {
+    let (test, keeper) = drifted_equal_weight_index();
+    test.index.update_rebalance_threshold(&test.admin, &ONE_DAY);
+
+    test.jump(ONE_DAY - 1);
+    test.index.execute_scheduled_rebalance(&keeper);
+}
+
+#[test]
+fn execute_scheduled_rebalance_after_threshold() {
+    let (test, keeper) = drifted_equal_weight_index();
+    test.index.update_rebalance_threshold(&test.admin, &ONE_DAY);
+
+    test.jump(ONE_DAY);
+    test.index.execute_scheduled_rebalance(&keeper);
+
+    assert_eq!(test.index.query_rebalance_result().unwrap().keeper, keeper);
+}
@@ -1,47 +1,395 @@
-use crate::contract::{IndexFactory, IndexFactoryClient};
-use soroban_sdk::{testutils::Address as _, vec, Address, BytesN, Env, String};
-pub const ONE_DAY: u64 = 86400;
-const TOKEN_WASM: &[u8] =
-    include_bytes!("../../../../target/wasm32-unknown-unknown/release/soroban_token_contract.wasm");
-
-#[allow(clippy::too_many_arguments)]
-pub fn install_index_wasm(env: &Env) -> BytesN<32> {
-    soroban_sdk::contractimport!(
-        file = "../../target/wasm32-unknown-unknown/release/normal_index.wasm"
-    );
-    env.deployer().upload_contract_wasm(WASM)
+use normal::{
+    constants::{PERCENTAGE_PRECISION_I128, REFLECTOR_PRICE_RECORDS},
+    error::ErrorCode,
+    oracle::OracleSource,
+    reflector::{Asset, ReflectorClient},
+    testutils::{MockReflector, MockReflectorClient},
+    types::{
+        index::{IndexAccessMode, IndexAsset, IndexParams, IndexPriceSource},
+        market::MarketPriceData,
+    },
+};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, symbol_short,
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, String, Symbol, Vec,
+};
+
+use crate::{
+    contract::{IndexToken, IndexTokenClient},
+    token_contract,
+};
+
+pub const XLM_PRICE: i128 = 500_000; // $0.50
+pub const BTC_PRICE: i128 = 100_000_000; // $100
+pub const ETH_PRICE: i128 = 20_000_000; // $20
+
+pub const INITIAL_PRICE: i128 = 100_000_000; // $100
+/// One whole token, quote, synth and index tokens all have 7 decimals
+pub const UNIT: i128 = 10_000_000;
+
+pub const INITIAL_DEPOSIT: i128 = 10_000 * UNIT; // 10,000 XLM
+/// Index tokens minted to the admin for `INITIAL_DEPOSIT`
+pub const INITIAL_SUPPLY: i128 = 50 * UNIT;
+
+const LIQUIDITY: i128 = 1_000_000 * UNIT;
+const START_TS: u64 = 1_700_000_000;
+
+pub fn deploy_token_contract<'a>(env: &Env, admin: &Address) -> token_contract::Client<'a> {
+    token_contract::Client::new(
+        env,
+        &env.register_stellar_asset_contract_v2(admin.clone())
+            .address(),
+    )
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn install_index_token_wasm(env: &Env) -> BytesN<32> {
-    soroban_sdk::contractimport!(
-        file = "../../target/wasm32-unknown-unknown/release/normal_index_token.wasm"
-    );
-    env.deployer().upload_contract_wasm(WASM)
+/// Pushes enough identical records of `price` at the current ledger time for
+/// the oracle to be valid
+pub fn set_oracle_price(env: &Env, oracle: &MockReflectorClient, symbol: Symbol, price: i128) {
+    let asset = Asset::Other(symbol);
+    for _ in 0..REFLECTOR_PRICE_RECORDS {
+        oracle.set_price(&asset, &price, &env.ledger().timestamp());
+    }
+}
+
+fn read_oracle_price(env: &Env, oracle: &Address, symbol: Symbol) -> i128 {
+    ReflectorClient::new(env, oracle)
+        .lastprice(&Asset::Other(symbol))
+        .unwrap()
+        .price
+}
+
+/// Stands in for a synth market whose pool trades synth (token a) and quote
+/// (token b) at the oracle price, filling `fill_bps` of the oracle value
+#[contract]
+pub struct MockMarket;
+
+#[contractimpl]
+impl MockMarket {
+    pub fn __constructor(
+        env: Env,
+        synth_token: Address,
+        quote_token: Address,
+        oracle: Address,
+        symbol: Symbol,
+    ) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("synth"), &synth_token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("quote"), &quote_token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("oracle"), &oracle);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("symbol"), &symbol);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fill_bps"), &10_000_i128);
+    }
+
+    pub fn set_fill_bps(env: Env, fill_bps: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fill_bps"), &fill_bps);
+    }
+
+    pub fn set_synth_supply(env: Env, synth_supply: u128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("supply"), &synth_supply);
+    }
+
+    pub fn query_price_data(env: Env) -> MarketPriceData {
+        let oracle: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("oracle"))
+            .unwrap();
+        let symbol: Symbol = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("symbol"))
+            .unwrap();
+
+        MarketPriceData {
+            synth_token: env
+                .storage()
+                .instance()
+                .get(&symbol_short!("synth"))
+                .unwrap(),
+            symbol: symbol.clone(),
+            oracle: oracle.clone(),
+            oracle_source: OracleSource::Reflector,
+            last_oracle_price_twap: read_oracle_price(&env, &oracle, symbol) as i64,
+            sqrt_price: 1 << 64,
+            last_price_ts: env.ledger().timestamp(),
+            synth_supply: env
+                .storage()
+                .instance()
+                .get(&symbol_short!("supply"))
+                .unwrap_or(0),
+            synth_decimals: 7,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap(
+        env: Env,
+        sender: Address,
+        amount: u64,
+        other_amount_threshold: u64,
+        _sqrt_price_limit: u128,
+        _amount_specified_is_input: bool,
+        a_to_b: bool,
+        _tick_array_indexes: Vec<i32>,
+    ) {
+        sender.require_auth();
+
+        let storage = env.storage().instance();
+        let synth_token: Address = storage.get(&symbol_short!("synth")).unwrap();
+        let quote_token: Address = storage.get(&symbol_short!("quote")).unwrap();
+        let oracle: Address = storage.get(&symbol_short!("oracle")).unwrap();
+        let symbol: Symbol = storage.get(&symbol_short!("symbol")).unwrap();
+        let fill_bps: i128 = storage.get(&symbol_short!("fill_bps")).unwrap();
+
+        let synth_price = read_oracle_price(&env, &oracle, symbol);
+        let quote_price = read_oracle_price(&env, &oracle, symbol_short!("XLM"));
+
+        let amount = i128::from(amount);
+        let (offer_token, ask_token, ask_amount) = match a_to_b {
+            true => (synth_token, quote_token, amount * synth_price / quote_price),
+            false => (quote_token, synth_token, amount * quote_price / synth_price),
+        };
+        let ask_amount = ask_amount * fill_bps / 10_000;
+
+        if ask_amount < i128::from(other_amount_threshold) {
+            panic_with_error!(&env, ErrorCode::SlippageToleranceExceeded);
+        }
+
+        let market = env.current_contract_address();
+        token_contract::Client::new(&env, &offer_token).transfer(&sender, &market, &amount);
+        token_contract::Client::new(&env, &ask_token).transfer(&market, &sender, &ask_amount);
+    }
+}
+
+pub struct MockMarketInfo<'a> {
+    pub address: Address,
+    pub client: MockMarketClient<'a>,
+    pub synth_token: token_contract::Client<'a>,
+}
+
+/// Stands in for the index factory: reports the protocol fee and treasury and
+/// records the markets each index holds
+#[contract]
+pub struct MockIndexFactory;
+
+#[contractimpl]
+impl MockIndexFactory {
+    pub fn initialize(env: Env, treasury: Address, protocol_fee_bps: i64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("treasury"), &treasury);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fee_bps"), &protocol_fee_bps);
+    }
+
+    pub fn update_protocol_fee(env: Env, protocol_fee_bps: i64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fee_bps"), &protocol_fee_bps);
+    }
+
+    pub fn query_protocol_fee(env: Env) -> i64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("fee_bps"))
+            .unwrap()
+    }
+
+    pub fn query_treasury(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("treasury"))
+            .unwrap()
+    }
+
+    pub fn sync_index_markets(env: Env, index: Address, markets: Vec<Address>) {
+        env.storage().instance().set(&index, &markets);
+    }
+
+    pub fn query_index_markets(env: Env, index: Address) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&index)
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+/// An index holding BTC and ETH 50/50, bought with XLM at oracle prices
+pub struct IndexTest<'a> {
+    pub env: Env,
+    pub admin: Address,
+    pub treasury: Address,
+    pub factory: MockIndexFactoryClient<'a>,
+    pub oracle: MockReflectorClient<'a>,
+    pub quote_token: token_contract::Client<'a>,
+    pub btc: MockMarketInfo<'a>,
+    pub eth: MockMarketInfo<'a>,
+    pub index: IndexTokenClient<'a>,
+}
+
+impl<'a> IndexTest<'a> {
+    pub fn new() -> Self {
+        Self::with_params(|_| {})
+    }
+
+    /// Deploys the index with the default params as changed by `update_params`
+    pub fn with_params(update_params: impl FnOnce(&mut IndexParams)) -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.cost_estimate().budget().reset_unlimited();
+        env.ledger().set_timestamp(START_TS);
+
+        let admin = Address::generate(&env);
+        let treasury = Address::generate(&env);
+
+        let oracle = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &6, &300);
+
+        let factory = MockIndexFactoryClient::new(&env, &env.register(MockIndexFactory, ()));
+        factory.initialize(&treasury, &0);
+
+        let quote_token = deploy_token_contract(&env, &admin);
+        let btc = deploy_market(&env, &admin, &quote_token, &oracle, symbol_short!("BTC"));
+        let eth = deploy_market(&env, &admin, &quote_token, &oracle, symbol_short!("ETH"));
+
+        let test = IndexTest {
+            index: IndexTokenClient::new(&env, &env.register(IndexToken, ())),
+            env,
+            admin,
+            treasury,
+            factory,
+            oracle,
+            quote_token,
+            btc,
+            eth,
+        };
+        test.set_prices(XLM_PRICE, BTC_PRICE, ETH_PRICE);
+
+        let mut params = test.index_params();
+        update_params(&mut params);
+
+        test.quote_token.mint(&test.admin, &INITIAL_DEPOSIT);
+        test.index.initialize(
+            &test.admin,
+            &test.factory.address,
+            &INITIAL_DEPOSIT,
+            &params,
+        );
+
+        test
+    }
+
+    fn index_params(&self) -> IndexParams {
+        IndexParams {
+            decimal: 7,
+            name: String::from_str(&self.env, "Crypto Index"),
+            symbol: String::from_str(&self.env, "CIX"),
+            quote_token: self.quote_token.address.clone(),
+            oracle: self.oracle.address.clone(),
+            oracle_source: OracleSource::Reflector,
+            oracle_feeds: Vec::new(&self.env),
+            price_source: IndexPriceSource::Oracle,
+            max_price_staleness: 60,
+            is_public: true,
+            access_mode: IndexAccessMode::Public,
+            manager_fee_bps: 0,
+            whitelist: Vec::new(&self.env),
+            blacklist: Vec::new(&self.env),
+            initial_price: INITIAL_PRICE,
+            initial_deposit: INITIAL_DEPOSIT,
+            component_assets: vec![
+                &self.env,
+                IndexAsset {
+                    market: self.btc.address.clone(),
+                    weight: PERCENTAGE_PRECISION_I128 / 2,
+                    last_updated_ts: 0,
+                },
+                IndexAsset {
+                    market: self.eth.address.clone(),
+                    weight: PERCENTAGE_PRECISION_I128 / 2,
+                    last_updated_ts: 0,
+                },
+            ],
+            rebalance_threshold: 0,
+        }
+    }
+
+    /// Sets the USD oracle prices, which the mock markets also trade at
+    pub fn set_prices(&self, xlm_price: i128, btc_price: i128, eth_price: i128) {
+        set_oracle_price(&self.env, &self.oracle, symbol_short!("XLM"), xlm_price);
+        set_oracle_price(&self.env, &self.oracle, symbol_short!("BTC"), btc_price);
+        set_oracle_price(&self.env, &self.oracle, symbol_short!("ETH"), eth_price);
+    }
+
+    /// Moves the ledger forward, republishing the current oracle prices
+    pub fn jump(&self, seconds: u64) {
+        self.env
+            .ledger()
+            .set_timestamp(self.env.ledger().timestamp() + seconds);
+
+        let price = |symbol| read_oracle_price(&self.env, &self.oracle.address, symbol);
+        self.set_prices(
+            price(symbol_short!("XLM")),
+            price(symbol_short!("BTC")),
+            price(symbol_short!("ETH")),
+        );
+    }
+
+    /// Creates an account holding `quote_amount` of the quote token
+    pub fn create_user(&self, quote_amount: i128) -> Address {
+        let user = Address::generate(&self.env);
+        self.quote_token.mint(&user, &quote_amount);
+        user
+    }
+
+    pub fn component_balance(&self, market: &MockMarketInfo) -> i128 {
+        self.index
+            .query_index()
+            .index
+            .component_balances
+            .get(market.synth_token.address.clone())
+            .unwrap_or(0)
+    }
 }
 
-pub fn deploy_index_token_contract<'a>(
+fn deploy_market<'a>(
     env: &Env,
-    admin: impl Into<Option<Address>>,
-    oracle: impl Into<Option<Address>>,
-) -> IndexFactoryClient<'a> {
-    let admin = admin.into().unwrap_or(Address::generate(env));
-    let factory = IndexFactoryClient::new(env, &env.register(IndexFactory, ()));
-
-    let paused_operations = vec![];
-
-    let index_wasm_hash = install_index_wasm(env);
-    let index_token_wasm_hash = install_index_token_wasm(env);
-
-    factory.initialize(
-        &admin,
-        &index_wasm_hash,
-        &index_token_wasm_hash,
-        &paused_operations,
-        500,
-        300,
-        &oracle,
+    admin: &Address,
+    quote_token: &token_contract::Client,
+    oracle: &MockReflectorClient,
+    symbol: Symbol,
+) -> MockMarketInfo<'a> {
+    let synth_token = deploy_token_contract(env, admin);
+    let address = env.register(
+        MockMarket,
+        (
+            synth_token.address.clone(),
+            quote_token.address.clone(),
+            oracle.address.clone(),
+            symbol,
+        ),
     );
+    synth_token.mint(&address, &LIQUIDITY);
+    quote_token.mint(&address, &LIQUIDITY);
 
-    factory
+    MockMarketInfo {
+        client: MockMarketClient::new(env, &address),
+        address,
+        synth_token,
+    }
 }
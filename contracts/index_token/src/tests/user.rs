@@ -0,0 +1,171 @@
+use soroban_sdk::{testutils::Address as _, vec, Address};
+
+use super::setup::{
+    IndexTest, BTC_PRICE, ETH_PRICE, INITIAL_PRICE, INITIAL_SUPPLY, UNIT, XLM_PRICE,
+};
+
+#[test]
+fn mint_in_kind_deposits_basket_share() {
+    let test = IndexTest::new();
+    let participant = Address::generate(&test.env);
+    test.index.update_authorized_participants(
+        &test.admin,
+        &vec![&test.env, participant.clone()],
+        &vec![&test.env],
+    );
+
+    // 10 of 50 tokens is a fifth of the 25 BTC and 125 ETH held
+    test.btc.synth_token.mint(&participant, &(5 * UNIT));
+    test.eth.synth_token.mint(&participant, &(25 * UNIT));
+
+    test.index.mint_in_kind(&participant, &(10 * UNIT)).unwrap();
+
+    assert_eq!(test.index.balance(&participant), 10 * UNIT);
+    assert_eq!(test.btc.synth_token.balance(&participant), 0);
+    assert_eq!(test.eth.synth_token.balance(&participant), 0);
+    assert_eq!(test.component_balance(&test.btc), 30 * UNIT);
+    assert_eq!(test.component_balance(&test.eth), 150 * UNIT);
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.total_supply(), INITIAL_SUPPLY + 10 * UNIT);
+    assert_eq!(test.index.query_price(), INITIAL_PRICE);
+}
+
+#[test]
+fn redeem_in_kind_pays_out_basket_share_and_burns() {
+    let test = IndexTest::new();
+    let participant = Address::generate(&test.env);
+    test.index.update_authorized_participants(
+        &test.admin,
+        &vec![&test.env, participant.clone()],
+        &vec![&test.env],
+    );
+    test.index.transfer(&test.admin, &participant, &(10 * UNIT));
+
+    test.index
+        .redeem_in_kind(&participant, &(10 * UNIT))
+        .unwrap();
+
+    assert_eq!(test.index.balance(&participant), 0);
+    assert_eq!(test.btc.synth_token.balance(&participant), 5 * UNIT);
+    assert_eq!(test.eth.synth_token.balance(&participant), 25 * UNIT);
+    assert_eq!(test.component_balance(&test.btc), 20 * UNIT);
+    assert_eq!(test.component_balance(&test.eth), 100 * UNIT);
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.total_supply(), INITIAL_SUPPLY - 10 * UNIT);
+    assert_eq!(index.total_supply(), test.index.balance(&test.admin));
+    assert_eq!(test.index.query_price(), INITIAL_PRICE);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #24)")]
+fn mint_in_kind_by_unauthorized_participant_should_fail() {
+    let test = IndexTest::new();
+    let user = Address::generate(&test.env);
+    test.btc.synth_token.mint(&user, &(5 * UNIT));
+    test.eth.synth_token.mint(&user, &(25 * UNIT));
+
+    test.index.mint_in_kind(&user, &(10 * UNIT)).unwrap();
+}
+
+#[test]
+fn burn_and_burn_from_reduce_total_supply() {
+    let test = IndexTest::new();
+    let spender = Address::generate(&test.env);
+
+    test.index.burn(&test.admin, &(10 * UNIT));
+    assert_eq!(
+        test.index.query_index().index.total_supply(),
+        INITIAL_SUPPLY - 10 * UNIT
+    );
+
+    test.index
+        .approve(&test.admin, &spender, &(5 * UNIT), &1_000);
+    test.index.burn_from(&spender, &test.admin, &(5 * UNIT));
+
+    let index = test.index.query_index().index;
+    assert_eq!(index.total_supply(), INITIAL_SUPPLY - 15 * UNIT);
+    assert_eq!(index.total_supply(), test.index.balance(&test.admin));
+}
+
+#[test]
+fn mint_and_redeem_with_slippage_within_bounds() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+
+    // 1 token at $100 costs 200 XLM at $0.50
+    test.index
+        .mint_with_slippage(&user, &UNIT, &(200 * UNIT), &test.env.ledger().timestamp())
+        .unwrap();
+    assert_eq!(test.quote_token.balance(&user), 800 * UNIT);
+
+    test.index
+        .redeem_with_slippage(&user, &UNIT, &(200 * UNIT), &test.env.ledger().timestamp())
+        .unwrap();
+    assert_eq!(test.quote_token.balance(&user), 1_000 * UNIT);
+    assert_eq!(test.index.balance(&user), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn mint_with_slippage_above_max_quote_in_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+
+    test.index
+        .mint_with_slippage(&user, &UNIT, &(199 * UNIT), &test.env.ledger().timestamp())
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn redeem_with_slippage_below_min_quote_out_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+    test.index.mint(&user, &UNIT).unwrap();
+
+    // XLM rallies, so the components sell for less quote
+    test.set_prices(XLM_PRICE * 2, BTC_PRICE, ETH_PRICE);
+
+    test.index
+        .redeem_with_slippage(&user, &UNIT, &(200 * UNIT), &test.env.ledger().timestamp())
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #27)")]
+fn mint_with_slippage_after_deadline_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+    let deadline = test.env.ledger().timestamp() - 1;
+
+    test.index
+        .mint_with_slippage(&user, &UNIT, &(200 * UNIT), &deadline)
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #27)")]
+fn redeem_with_slippage_after_deadline_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+    test.index.mint(&user, &UNIT).unwrap();
+    let deadline = test.env.ledger().timestamp() - 1;
+
+    test.index
+        .redeem_with_slippage(&user, &UNIT, &0, &deadline)
+        .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn mint_with_component_swap_beyond_max_swap_slippage_should_fail() {
+    let test = IndexTest::new();
+    let user = test.create_user(1_000 * UNIT);
+
+    // Fills 2% short of the oracle value, past the default 1% max slippage
+    test.btc.client.set_fill_bps(&9_800);
+
+    test.index.mint(&user, &UNIT).unwrap();
+}
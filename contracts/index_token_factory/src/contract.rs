@@ -1,24 +1,24 @@
 use crate::{
     factory::IndexTokenFactoryTrait,
     storage::{
-        get_config, get_index_vec, is_initialized, save_config, save_index_vec,
-        save_index_vec_with_tuple_as_key, set_initialized, Config, IndexInfo, IndexTupleKey,
-        Operation, ADMIN,
+        add_index, get_config, get_index_markets, get_indexes, get_market_indexes, is_initialized,
+        save_config, save_index_vec_with_tuple_as_key, set_initialized, Config, IndexInfo,
+        IndexTupleKey, Operation, ADMIN,
     },
-    utils::deploy_index_token_contract,
+    utils::{deploy_index_token_contract, sync_market_indexes},
 };
 use normal::{
     constants::{
-        INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT,
-        PERSISTENT_LIFETIME_THRESHOLD,
+        INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, MAX_INDEX_PROTOCOL_FEE_BPS,
+        PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD,
     },
     error::ErrorCode,
-    types::IndexParams,
+    types::index::IndexParams,
     validate_bps,
 };
 use soroban_sdk::{
-    contract, contractimpl, contractmeta, log, panic_with_error, Address, BytesN, Env, IntoVal,
-    String, Symbol, Val, Vec,
+    contract, contractimpl, contractmeta, log, panic_with_error, vec, Address, BytesN, Env,
+    IntoVal, String, Symbol, Val, Vec,
 };
 
 contractmeta!(
@@ -40,6 +40,7 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
         paused_operations: Vec<Operation>,
         max_manager_fee_bps: i64,
         protocol_fee_bps: i64,
+        treasury: Address,
         default_oracle: Address,
     ) {
         if is_initialized(&env) {
@@ -50,6 +51,8 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
             panic_with_error!(&env, ErrorCode::AlreadyInitialized);
         }
 
+        validate_protocol_fee(&env, protocol_fee_bps);
+
         set_initialized(&env);
 
         save_config(
@@ -61,12 +64,12 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
                 paused_operations,
                 max_manager_fee_bps,
                 protocol_fee_bps,
+                treasury,
+                governor: None,
                 default_oracle,
             },
         );
 
-        save_index_vec(&env, Vec::new(&env));
-
         env.events()
             .publish(("initialize", "Index token factory contract"), admin);
     }
@@ -107,19 +110,24 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
         let init_fn: Symbol = Symbol::new(&env, "initialize");
         let init_fn_args: Vec<Val> = (
             sender.clone(),
-            params.clone(),
-            initial_deposit,
             factory_addr,
+            initial_deposit,
+            params.clone(),
         )
             .into_val(&env);
 
         env.invoke_contract::<Val>(&index_token_contract_address, &init_fn, init_fn_args);
 
-        let mut index_vec = get_index_vec(&env);
+        add_index(&env, &index_token_contract_address);
 
-        index_vec.push_back(index_token_contract_address.clone());
+        let mut markets: Vec<Address> = Vec::new(&env);
+        for asset in params.component_assets.iter() {
+            if !markets.contains(&asset.market) {
+                markets.push_back(asset.market);
+            }
+        }
+        sync_market_indexes(&env, &index_token_contract_address, &markets);
 
-        save_index_vec(&env, index_vec);
         let symbol = &params.symbol;
         let name = &params.name;
         save_index_vec_with_tuple_as_key(&env, (symbol, name), &index_token_contract_address);
@@ -170,7 +178,7 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
             config.max_manager_fee_bps = max_manager_fee_bps;
         }
         if let Some(protocol_fee_bps) = protocol_fee_bps {
-            validate_bps!(protocol_fee_bps);
+            validate_protocol_fee(&env, protocol_fee_bps);
             config.protocol_fee_bps = protocol_fee_bps;
         }
 
@@ -181,15 +189,110 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
         save_config(&env, config);
     }
 
+    fn update_governor(env: Env, sender: Address, governor: Option<Address>) {
+        sender.require_auth();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let config = get_config(&env);
+        is_admin_or_governor(&env, &config, &sender);
+
+        save_config(&env, Config { governor, ..config });
+    }
+
+    fn update_protocol_fee(env: Env, sender: Address, protocol_fee_bps: i64) {
+        sender.require_auth();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let config = get_config(&env);
+        is_admin_or_governor(&env, &config, &sender);
+
+        validate_protocol_fee(&env, protocol_fee_bps);
+
+        save_config(
+            &env,
+            Config {
+                protocol_fee_bps,
+                ..config
+            },
+        );
+
+        env.events()
+            .publish(("update", "protocol_fee"), protocol_fee_bps);
+    }
+
+    fn update_treasury(env: Env, sender: Address, treasury: Address) {
+        sender.require_auth();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let config = get_config(&env);
+        is_admin_or_governor(&env, &config, &sender);
+
+        save_config(
+            &env,
+            Config {
+                treasury: treasury.clone(),
+                ..config
+            },
+        );
+
+        env.events().publish(("update", "treasury"), treasury);
+    }
+
+    fn sync_index_markets(env: Env, index: Address, markets: Vec<Address>) {
+        index.require_auth();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if get_index_markets(&env, &index).is_none() {
+            log!(
+                &env,
+                "Index Token Factory: {} is not a factory index",
+                index
+            );
+            panic_with_error!(&env, ErrorCode::IndexTokenNotFound);
+        }
+
+        sync_market_indexes(&env, &index, &markets);
+    }
+
+    fn sweep_protocol_fees(env: Env, sender: Address, offset: u32, limit: u32) {
+        sender.require_auth();
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let factory_addr = env.current_contract_address();
+        let collect_fn = Symbol::new(&env, "collect_fees");
+
+        let index_vec = get_indexes(&env, offset, limit);
+        for index_address in index_vec.iter() {
+            env.invoke_contract::<()>(
+                &index_address,
+                &collect_fn,
+                vec![&env, factory_addr.into_val(&env)],
+            );
+        }
+
+        env.events()
+            .publish(("sweep", "protocol_fees"), index_vec.len());
+    }
+
     // ################################################################
     //                             Queries
     // ################################################################
 
-    fn query_indexes(env: Env) -> Vec<Address> {
+    fn query_indexes(env: Env, offset: u32, limit: u32) -> Vec<Address> {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        get_index_vec(&env)
+        get_indexes(&env, offset, limit)
     }
 
     fn query_index_details(env: Env, index_address: Address) -> IndexInfo {
@@ -204,11 +307,11 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
         index_response
     }
 
-    fn query_all_indexes_details(env: Env) -> Vec<IndexInfo> {
+    fn query_all_indexes_details(env: Env, offset: u32, limit: u32) -> Vec<IndexInfo> {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let all_index_vec_addresses = get_index_vec(&env);
+        let all_index_vec_addresses = get_indexes(&env, offset, limit);
         let mut result = Vec::new(&env);
         for address in all_index_vec_addresses {
             let index_response: IndexInfo = env.invoke_contract(
@@ -223,6 +326,13 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
         result
     }
 
+    fn query_indexes_by_market(env: Env, market: Address, offset: u32, limit: u32) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_market_indexes(&env, &market, offset, limit)
+    }
+
     fn query_for_index_by_tuple(env: Env, name: String, symbol: String) -> Address {
         env.storage()
             .instance()
@@ -299,6 +409,20 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
     //     // ...
     // }
 
+    fn query_protocol_fee(env: Env) -> i64 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_config(&env).protocol_fee_bps
+    }
+
+    fn query_treasury(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_config(&env).treasury
+    }
+
     fn get_admin(env: Env) -> Address {
         env.storage()
             .instance()
@@ -323,6 +447,25 @@ impl IndexTokenFactoryTrait for IndexTokenFactory {
     }
 }
 
+fn is_admin_or_governor(env: &Env, config: &Config, sender: &Address) {
+    if *sender != config.admin && config.governor.as_ref() != Some(sender) {
+        log!(env, "Index Token Factory: You are not authorized!");
+        panic_with_error!(env, ErrorCode::NotAuthorized);
+    }
+}
+
+fn validate_protocol_fee(env: &Env, protocol_fee_bps: i64) {
+    if !(0..=MAX_INDEX_PROTOCOL_FEE_BPS).contains(&protocol_fee_bps) {
+        log!(
+            env,
+            "Index Token Factory: protocol fee {} bps out of range [0, {}]",
+            protocol_fee_bps,
+            MAX_INDEX_PROTOCOL_FEE_BPS
+        );
+        panic_with_error!(env, ErrorCode::InvalidFee);
+    }
+}
+
 #[contractimpl]
 impl IndexTokenFactory {
     #[allow(dead_code)]
@@ -1,4 +1,4 @@
-use normal::{error::ErrorCode, types::IndexParams};
+use normal::{error::ErrorCode, types::index::IndexParams};
 use soroban_sdk::{contractclient, Address, BytesN, Env, String, Vec};
 
 use crate::storage::{Config, IndexInfo, Operation};
@@ -14,6 +14,7 @@ pub trait IndexTokenFactoryTrait {
         paused_operations: Vec<Operation>,
         max_manager_fee_bps: i64,
         protocol_fee_bps: i64,
+        treasury: Address,
         default_oracle: Address,
     );
 
@@ -35,21 +36,48 @@ pub trait IndexTokenFactoryTrait {
         default_oracle: Option<Address>,
     );
 
+    fn update_governor(env: Env, sender: Address, governor: Option<Address>);
+
+    /// Sets the annual protocol fee streamed from every index, capped at
+    /// `MAX_INDEX_PROTOCOL_FEE_BPS`. Callable by the admin or governor.
+    fn update_protocol_fee(env: Env, sender: Address, protocol_fee_bps: i64);
+
+    /// Callable by the admin or governor
+    fn update_treasury(env: Env, sender: Address, treasury: Address);
+
+    /// Called by an index after a rebalance to update the markets it's listed under
+    fn sync_index_markets(env: Env, index: Address, markets: Vec<Address>);
+
+    /// Collects the streaming fees of up to `limit` indexes starting at `offset`,
+    /// minting the protocol share to the treasury
+    fn sweep_protocol_fees(env: Env, sender: Address, offset: u32, limit: u32);
+
     // ################################################################
     //                             Queries
     // ################################################################
 
-    fn query_indexes(env: Env) -> Vec<Address>;
+    /// Returns up to `limit` index addresses starting at `offset`
+    fn query_indexes(env: Env, offset: u32, limit: u32) -> Vec<Address>;
 
     fn query_index_details(env: Env, index_address: Address) -> IndexInfo;
 
-    fn query_all_indexes_details(env: Env) -> Vec<IndexInfo>;
+    /// Returns the details of up to `limit` indexes starting at `offset`
+    fn query_all_indexes_details(env: Env, offset: u32, limit: u32) -> Vec<IndexInfo>;
+
+    /// Returns up to `limit` indexes holding the synth `market`, starting at `offset`
+    fn query_indexes_by_market(env: Env, market: Address, offset: u32, limit: u32) -> Vec<Address>;
 
     // For indexes to query AMMs via the Synth Market Factory
     // fn query_for_amm_by_market(env: Env, marekt: Address) -> Address;
 
     fn query_for_index_by_tuple(env: Env, name: String, symbol: String) -> Address;
 
+    /// Returns the annual protocol fee charged by indexes, in basis points
+    fn query_protocol_fee(env: Env) -> i64;
+
+    /// Returns the address protocol fees are minted to
+    fn query_treasury(env: Env) -> Address;
+
     fn get_admin(env: Env) -> Address;
 
     fn get_config(env: Env) -> Config;
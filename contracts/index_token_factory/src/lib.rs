@@ -1,5 +1,6 @@
 #![no_std]
 
+#[allow(clippy::too_many_arguments)]
 mod contract;
 mod factory;
 mod storage;
@@ -12,14 +12,25 @@ use soroban_sdk::{
 
 pub const ADMIN: Symbol = symbol_short!("ADMIN");
 
+/// Max number of entries returned by a paginated query
+pub const MAX_QUERY_LIMIT: u32 = 50;
+
 #[derive(Clone, Copy)]
 #[repr(u32)]
 pub enum DataKey {
     Config = 1,
-    IndexVec = 2,
+    IndexCount = 2,
     Initialized = 3,
 }
 
+/// Indexes created by the factory, stored by sequence so pages can be read
+/// without loading the full list
+#[derive(Clone)]
+#[contracttype]
+pub enum IndexKey {
+    Index(u32),
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct IndexTupleKey {
@@ -27,6 +38,21 @@ pub struct IndexTupleKey {
     pub(crate) name: String,
 }
 
+/// Reverse index between indexes and the synth markets they hold. The indexes
+/// of a market are stored by position so pages can be read without loading
+/// the full list.
+#[derive(Clone)]
+#[contracttype]
+pub enum MarketIndexKey {
+    /// (market, position) -> index
+    MarketIndex(Address, u32),
+    /// market -> number of indexes holding it
+    MarketIndexCount(Address),
+    /// (market, index) -> position
+    MarketIndexPosition(Address, Address),
+    MarketsByIndex(Address),
+}
+
 impl TryFromVal<Env, DataKey> for Val {
     type Error = ConversionError;
 
@@ -54,7 +80,12 @@ pub struct Config {
     pub quote_token_whitelist: Vec<Address>,
     pub paused_operations: Vec<Operation>,
     pub max_manager_fee_bps: i64,
+    /// Annual fee streamed from every index to the treasury
     pub protocol_fee_bps: i64,
+    /// Receives the protocol fees minted by indexes
+    pub treasury: Address,
+    /// Can update the protocol fee and treasury alongside the admin
+    pub governor: Option<Address>,
     pub default_oracle: Address,
 }
 
@@ -119,31 +150,84 @@ pub fn _get_admin(env: &Env) -> Address {
     admin_addr
 }
 
-pub fn get_index_vec(env: &Env) -> Vec<Address> {
-    let index_vec = env
-        .storage()
-        .persistent()
-        .get(&DataKey::IndexVec)
-        .expect("Index Factory: get_index_vec: Liquidity Pool vector not found");
+/// Returns the `[start, end)` sequence range of a page over `count` entries,
+/// with `limit` capped at `MAX_QUERY_LIMIT`
+pub fn page_bounds(count: u32, offset: u32, limit: u32) -> (u32, u32) {
+    let start = offset.min(count);
+    let end = start.saturating_add(limit.min(MAX_QUERY_LIMIT)).min(count);
+
+    (start, end)
+}
 
+fn get_persistent_count<K>(env: &Env, key: &K) -> u32
+where
+    K: soroban_sdk::IntoVal<Env, Val>,
+{
+    let count = env.storage().persistent().get(key).unwrap_or(0);
+
+    env.storage().persistent().has(key).then(|| {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    });
+
+    count
+}
+
+fn save_persistent<K, V>(env: &Env, key: &K, value: &V)
+where
+    K: soroban_sdk::IntoVal<Env, Val>,
+    V: soroban_sdk::IntoVal<Env, Val>,
+{
+    env.storage().persistent().set(key, value);
     env.storage().persistent().extend_ttl(
-        &DataKey::IndexVec,
+        key,
         PERSISTENT_LIFETIME_THRESHOLD,
         PERSISTENT_BUMP_AMOUNT,
     );
-
-    index_vec
 }
 
-pub fn save_index_vec(env: &Env, index_info: Vec<Address>) {
-    env.storage()
+fn get_persistent_address<K>(env: &Env, key: &K) -> Address
+where
+    K: soroban_sdk::IntoVal<Env, Val>,
+{
+    let address = env
+        .storage()
         .persistent()
-        .set(&DataKey::IndexVec, &index_info);
+        .get(key)
+        .expect("Index Factory: index entry not found");
+
     env.storage().persistent().extend_ttl(
-        &DataKey::IndexVec,
+        key,
         PERSISTENT_LIFETIME_THRESHOLD,
         PERSISTENT_BUMP_AMOUNT,
     );
+
+    address
+}
+
+pub fn get_index_count(env: &Env) -> u32 {
+    get_persistent_count(env, &DataKey::IndexCount)
+}
+
+pub fn add_index(env: &Env, index: &Address) {
+    let count = get_index_count(env);
+    save_persistent(env, &IndexKey::Index(count), index);
+    save_persistent(env, &DataKey::IndexCount, &(count + 1));
+}
+
+/// Returns up to `limit` indexes starting at sequence `offset`
+pub fn get_indexes(env: &Env, offset: u32, limit: u32) -> Vec<Address> {
+    let (start, end) = page_bounds(get_index_count(env), offset, limit);
+
+    let mut indexes = Vec::new(env);
+    for id in start..end {
+        indexes.push_back(get_persistent_address(env, &IndexKey::Index(id)));
+    }
+
+    indexes
 }
 
 pub fn save_index_vec_with_tuple_as_key(
@@ -169,6 +253,108 @@ pub fn save_index_vec_with_tuple_as_key(
     );
 }
 
+pub fn get_market_index_count(env: &Env, market: &Address) -> u32 {
+    get_persistent_count(env, &MarketIndexKey::MarketIndexCount(market.clone()))
+}
+
+/// Returns up to `limit` indexes holding `market` starting at `offset`
+pub fn get_market_indexes(env: &Env, market: &Address, offset: u32, limit: u32) -> Vec<Address> {
+    let (start, end) = page_bounds(get_market_index_count(env, market), offset, limit);
+
+    let mut indexes = Vec::new(env);
+    for position in start..end {
+        indexes.push_back(get_persistent_address(
+            env,
+            &MarketIndexKey::MarketIndex(market.clone(), position),
+        ));
+    }
+
+    indexes
+}
+
+pub fn add_market_index(env: &Env, market: &Address, index: &Address) {
+    let position_key = MarketIndexKey::MarketIndexPosition(market.clone(), index.clone());
+    if env.storage().persistent().has(&position_key) {
+        return;
+    }
+
+    let count = get_market_index_count(env, market);
+    save_persistent(
+        env,
+        &MarketIndexKey::MarketIndex(market.clone(), count),
+        index,
+    );
+    save_persistent(env, &position_key, &count);
+    save_persistent(
+        env,
+        &MarketIndexKey::MarketIndexCount(market.clone()),
+        &(count + 1),
+    );
+}
+
+/// Removes `index` from `market`, moving the last index into its position
+pub fn remove_market_index(env: &Env, market: &Address, index: &Address) {
+    let position_key = MarketIndexKey::MarketIndexPosition(market.clone(), index.clone());
+    let position: u32 = match env.storage().persistent().get(&position_key) {
+        Some(position) => position,
+        None => return,
+    };
+
+    let last = get_market_index_count(env, market) - 1;
+    if position != last {
+        let last_index =
+            get_persistent_address(env, &MarketIndexKey::MarketIndex(market.clone(), last));
+        save_persistent(
+            env,
+            &MarketIndexKey::MarketIndex(market.clone(), position),
+            &last_index,
+        );
+        save_persistent(
+            env,
+            &MarketIndexKey::MarketIndexPosition(market.clone(), last_index),
+            &position,
+        );
+    }
+
+    env.storage()
+        .persistent()
+        .remove(&MarketIndexKey::MarketIndex(market.clone(), last));
+    env.storage().persistent().remove(&position_key);
+
+    let count_key = MarketIndexKey::MarketIndexCount(market.clone());
+    if last == 0 {
+        env.storage().persistent().remove(&count_key);
+    } else {
+        save_persistent(env, &count_key, &last);
+    }
+}
+
+/// Returns `None` for addresses that weren't created by the factory
+pub fn get_index_markets(env: &Env, index: &Address) -> Option<Vec<Address>> {
+    let key = MarketIndexKey::MarketsByIndex(index.clone());
+    let markets = env.storage().persistent().get(&key);
+
+    env.storage().persistent().has(&key).then(|| {
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    });
+
+    markets
+}
+
+pub fn save_index_markets(env: &Env, index: &Address, markets: &Vec<Address>) {
+    let key = MarketIndexKey::MarketsByIndex(index.clone());
+    env.storage().persistent().set(&key, markets);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
 pub fn is_initialized(e: &Env) -> bool {
     e.storage()
         .persistent()
@@ -1,94 +1,133 @@
-use super::setup::{install_index_token_contract, install_token_wasm};
-use crate::tests::setup::{
-    deploy_index_token_factory_contract, generate_index_token_init_info, index_token_contract,
-};
+use normal::constants::MAX_INDEX_PROTOCOL_FEE_BPS;
+use soroban_sdk::{testutils::Address as _, vec, Address, BytesN, Env};
 
-use soroban_sdk::{
-    testutils::{arbitrary::std, Address as _},
-    vec, Address, Env, String,
-};
+use super::setup::deploy_index_token_factory_contract;
 
 #[test]
 fn factory_successfully_inits_itself() {
     let env = Env::default();
+    env.mock_all_auths();
+
     let admin = Address::generate(&env);
-    let governor = Address::generate(&env);
+    let treasury = Address::generate(&env);
 
-    let factory =
-        deploy_index_token_factory_contract(&env, Some(admin.clone()), Some(governor.clone()));
+    let factory = deploy_index_token_factory_contract(&env, admin.clone(), &treasury, 50);
 
     assert_eq!(factory.get_admin(), admin);
+    assert_eq!(factory.query_protocol_fee(), 50);
+    assert_eq!(factory.query_treasury(), treasury);
 }
 
 #[test]
-#[should_panic(expected = "Factory: Initialize: initializing contract twice is not allowed")]
-fn test_deploy_factory_twice_should_fail() {
+#[should_panic(expected = "Error(Contract, #43)")]
+fn factory_initializing_twice_should_fail() {
     let env = Env::default();
     env.mock_all_auths();
-    env.cost_estimate().budget().reset_unlimited();
 
     let admin = Address::generate(&env);
-    let governor = Address::generate(&env);
-
-    let auth_user = Address::generate(&env);
-    let index_token_wasm_hash = install_index_token_contract(&env);
+    let treasury = Address::generate(&env);
+
+    let factory = deploy_index_token_factory_contract(&env, admin.clone(), &treasury, 50);
+
+    factory.initialize(
+        &admin,
+        &BytesN::from_array(&env, &[0; 32]),
+        &vec![&env],
+        &vec![&env],
+        &500,
+        &50,
+        &treasury,
+        &Address::generate(&env),
+    );
+}
 
-    let factory = deploy_index_token_factory_contract(&env, admin.clone(), governor.clone());
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn factory_init_with_protocol_fee_above_max_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    factory.initialize(&admin, &index_token_wasm_hash);
-    factory.initialize(&admin, &index_token_wasm_hash);
+    deploy_index_token_factory_contract(
+        &env,
+        None,
+        &Address::generate(&env),
+        MAX_INDEX_PROTOCOL_FEE_BPS + 1,
+    );
 }
 
 #[test]
-fn factory_successfully_inits_market() {
+fn update_protocol_fee_by_admin_and_governor() {
     let env = Env::default();
+    env.mock_all_auths();
+
     let admin = Address::generate(&env);
     let governor = Address::generate(&env);
-    let mut token1_admin = Address::generate(&env);
-    let mut token2_admin = Address::generate(&env);
-    let user = Address::generate(&env);
+    let factory =
+        deploy_index_token_factory_contract(&env, admin.clone(), &Address::generate(&env), 50);
 
-    let mut token1 = Address::generate(&env);
-    let mut token2 = Address::generate(&env);
+    factory.update_protocol_fee(&admin, &100);
+    assert_eq!(factory.query_protocol_fee(), 100);
 
-    env.mock_all_auths();
-    env.cost_estimate().budget().reset_unlimited();
+    factory.update_governor(&admin, &Some(governor.clone()));
+    factory.update_protocol_fee(&governor, &MAX_INDEX_PROTOCOL_FEE_BPS);
+    assert_eq!(factory.query_protocol_fee(), MAX_INDEX_PROTOCOL_FEE_BPS);
+    assert_eq!(
+        factory.get_config().protocol_fee_bps,
+        MAX_INDEX_PROTOCOL_FEE_BPS
+    );
+}
 
-    if token2 < token1 {
-        std::mem::swap(&mut token1, &mut token2);
-        std::mem::swap(&mut token1_admin, &mut token2_admin);
-    }
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn update_protocol_fee_above_max_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
 
+    let admin = Address::generate(&env);
     let factory =
-        deploy_index_token_factory_contract(&env, Some(admin.clone()), Some(governor.clone()));
-    assert_eq!(factory.get_admin(), admin);
+        deploy_index_token_factory_contract(&env, admin.clone(), &Address::generate(&env), 50);
 
-    let index_params = generate_index_token_init_info(
-        token1.clone(),
-        token2.clone(),
-        Address::generate(&env),
-        admin.clone(),
-        user.clone(),
-    );
+    factory.update_protocol_fee(&admin, &(MAX_INDEX_PROTOCOL_FEE_BPS + 1));
+}
 
-    factory.create_index_token(&admin, &100, &index_params);
-    let index_token_contract_addr = factory.query_markets().get(0).unwrap();
+#[test]
+#[should_panic(expected = "Error(Contract, #36)")]
+fn update_protocol_fee_by_non_admin_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    let first_index_token_contract =
-        index_token_contract::Client::new(&env, &index_token_contract_addr);
+    let factory = deploy_index_token_factory_contract(&env, None, &Address::generate(&env), 50);
 
-    assert_eq!(
-        first_index_token_contract.query_index(),
-        index_token_contract::Index {
-            fee_recipient: user,
-            max_allowed_slippage_bps: 5_000,
-            // ...
-        }
-    );
+    factory.update_protocol_fee(&Address::generate(&env), &100);
 }
 
 #[test]
-fn factory_successfully_updates_config() {}
+fn update_treasury_by_admin_and_governor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let factory =
+        deploy_index_token_factory_contract(&env, admin.clone(), &Address::generate(&env), 50);
+
+    let treasury = Address::generate(&env);
+    factory.update_treasury(&admin, &treasury);
+    assert_eq!(factory.query_treasury(), treasury);
+
+    factory.update_governor(&admin, &Some(governor.clone()));
+    let treasury = Address::generate(&env);
+    factory.update_treasury(&governor, &treasury);
+    assert_eq!(factory.query_treasury(), treasury);
+}
 
 #[test]
-fn factory_successfully_updates_wasm_hashes() {}
+#[should_panic(expected = "Error(Contract, #36)")]
+fn update_treasury_by_non_admin_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let factory = deploy_index_token_factory_contract(&env, None, &Address::generate(&env), 50);
+
+    factory.update_treasury(&Address::generate(&env), &Address::generate(&env));
+}
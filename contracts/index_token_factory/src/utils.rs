@@ -1,4 +1,8 @@
-use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, String};
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec};
+
+use crate::storage::{
+    add_market_index, get_index_markets, remove_market_index, save_index_markets,
+};
 
 pub fn deploy_index_token_contract(
     env: &Env,
@@ -15,3 +19,20 @@ pub fn deploy_index_token_contract(
         .with_current_contract(salt)
         .deploy_v2(wasm_hash, ())
 }
+
+/// Points each of `markets` at `index`, dropping the markets it no longer holds
+pub fn sync_market_indexes(env: &Env, index: &Address, markets: &Vec<Address>) {
+    let previous_markets = get_index_markets(env, index).unwrap_or(Vec::new(env));
+
+    for market in previous_markets.iter() {
+        if !markets.contains(&market) {
+            remove_market_index(env, &market, index);
+        }
+    }
+
+    for market in markets.iter() {
+        add_market_index(env, &market, index);
+    }
+
+    save_index_markets(env, index, markets);
+}
@@ -1,4 +1,7 @@
-use soroban_sdk::{Address, Env};
+use normal::types::auction::AuctionType;
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::storage::{Buffer, BufferAuction};
 
 pub trait BufferTrait {
     // ################################################################
@@ -11,21 +14,51 @@ pub trait BufferTrait {
 
     fn execute_buffer_buyback(env: Env, sender: Address, amount: i128);
 
-    fn execute_buffer_auction(env: Env, sender: Address, amount: i128);
+    /// Starts a native Dutch auction that decays from `start_price` to `end_price`
+    /// (PRICE_PRECISION, payment tokens per lot token) over `duration` seconds.
+    ///
+    /// - `Debt` mints `amount` NORM and sells it for quote tokens that are paid to
+    ///   the registered `market` to repay its protocol debt
+    /// - `Surplus` sells `amount` quote tokens held over `max_insurance` for NORM
+    ///   that is burned
+    ///
+    /// Returns the auction id.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_buffer_auction(
+        env: Env,
+        sender: Address,
+        auction_type: AuctionType,
+        market: Option<Address>,
+        amount: i128,
+        start_price: u64,
+        end_price: u64,
+        duration: u64,
+    ) -> u32;
 
     // ################################################################
     //                             USER
     // ################################################################
 
-    // fn bid_buffer_auction(env: Env, user: Address, auction_ts: u64, bid_amount: i128);
+    /// Buys up to `amount` of an auction's lot at its current price. Fails if the
+    /// auction has expired or its price is above `max_price`.
+    fn bid_buffer_auction(env: Env, sender: Address, auction_id: u32, amount: i128, max_price: u64);
+
+    /// Closes an expired auction. Unsold NORM is burned and unsold quote tokens
+    /// are released back to the Insurance Fund. Debt auction proceeds repay the
+    /// market's protocol debt.
+    fn settle_buffer_auction(env: Env, sender: Address, auction_id: u32);
 
     // ################################################################
     //                             QUERIES
     // ################################################################
 
-    // fn query_buffer(env: Env);
+    fn query_buffer(env: Env) -> Buffer;
+
+    /// Returns all live auctions
+    fn query_buffer_auctions(env: Env) -> Vec<BufferAuction>;
 
-    // fn query_buffer_auctions(env: Env);
+    fn query_buffer_auction(env: Env, auction_id: u32) -> BufferAuction;
 
-    // fn query_buffe_balance(env: Env);
+    /// Returns the governance tokens held by the Buffer
+    fn query_buffer_balance(env: Env) -> i128;
 }
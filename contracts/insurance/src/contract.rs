@@ -11,9 +11,10 @@ use crate::{
     insurance_fund::InsuranceFundTrait,
     math,
     storage::{
-        get_buffer, get_insurance_fund, get_stake, save_buffer, save_insurance_fund,
+        get_buffer, get_insurance_fund, get_market_claim, get_stake, is_market_registered,
+        save_buffer, save_insurance_fund, save_market_claim,
         utils::{self, get_admin},
-        Buffer, InsuranceFund, InsuranceFundOperation, Stake,
+        Buffer, BufferAuction, InsuranceFund, InsuranceFundOperation, MarketClaim, Stake,
     },
     token_contract,
 };
@@ -22,6 +23,8 @@ use normal::{
     constants::{
         INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, ONE_MILLION_QUOTE, THIRTEEN_DAY,
     },
+    math::safe_math::SafeMath,
+    types::auction::AuctionType,
     validate,
 };
 
@@ -77,7 +80,7 @@ impl InsuranceFundTrait for Insurance {
         utils::save_governor(&env, &governor_contract);
 
         let insurance_fund = InsuranceFund {
-            deposit_token,
+            deposit_token: deposit_token.clone(),
             stake_token: stake_token_address.clone(),
             unstaking_period: THIRTEEN_DAY,
             revenue_settle_period: THIRTEEN_DAY,
@@ -101,8 +104,9 @@ impl InsuranceFundTrait for Insurance {
         let buffer = Buffer {
             governance_token: governance_token.clone(),
             governance_token_pool: governance_token.clone(), // TODO:
-            quote_token: governance_token.clone(),           // TODO:
+            quote_token: deposit_token,
             auctions: Vec::new(&env),
+            next_auction_id: 0,
             min_auction_duration: 3600,
             max_balance: max_buffer_balance,
             total_burns: 0,
@@ -119,6 +123,98 @@ impl InsuranceFundTrait for Insurance {
         );
     }
 
+    fn deposit_revenue(env: Env, sender: Address, token: Address, amount: i128) {
+        sender.require_auth();
+        utils::check_nonnegative_amount(amount);
+
+        validate!(
+            &env,
+            is_market_registered(&env, &sender),
+            Errors::MarketNotRegistered,
+            "Insurance: Deposit revenue: sender is not a registered market"
+        );
+
+        let now = env.ledger().timestamp();
+        let mut insurance_fund = get_insurance_fund(&env);
+
+        token_contract::Client::new(&env, &token).transfer_from(
+            &env.current_contract_address(),
+            &sender,
+            &env.current_contract_address(),
+            &amount,
+        );
+
+        insurance_fund.last_revenue_settle_ts = now;
+        save_insurance_fund(&env, insurance_fund);
+
+        InsuranceFundEvents::revenue_deposit(&env, sender, token, amount, now);
+    }
+
+    fn update_market_max_insurance(
+        env: Env,
+        sender: Address,
+        market: Address,
+        max_insurance: i128,
+    ) {
+        sender.require_auth();
+        utils::is_admin(&env, sender);
+        utils::check_nonnegative_amount(max_insurance);
+
+        let mut claim = get_market_claim(&env, &market);
+
+        log!(
+            &env,
+            "market claim max_insurance: {} -> {}",
+            claim.max_insurance,
+            max_insurance
+        );
+
+        claim.max_insurance = max_insurance;
+        save_market_claim(&env, &market, &claim);
+    }
+
+    fn cover_bankruptcy(env: Env, market: Address, amount: i128) -> i128 {
+        market.require_auth();
+        utils::check_nonnegative_amount(amount);
+
+        let now = env.ledger().timestamp();
+        let insurance_fund = get_insurance_fund(&env);
+        let mut claim = get_market_claim(&env, &market);
+
+        let insurance_vault_amount =
+            token_contract::Client::new(&env, &insurance_fund.deposit_token)
+                .balance(&env.current_contract_address());
+
+        // keep at least 1 token in the vault so share math stays defined
+        let payment = amount
+            .min(claim.max_insurance.safe_sub(claim.settled_insurance, &env))
+            .min(insurance_vault_amount.saturating_sub(1))
+            .max(0);
+
+        if payment == 0 {
+            return 0;
+        }
+
+        claim.settled_insurance = claim.settled_insurance.safe_add(payment, &env);
+        save_market_claim(&env, &market, &claim);
+
+        token_contract::Client::new(&env, &insurance_fund.deposit_token).transfer(
+            &env.current_contract_address(),
+            &market,
+            &payment,
+        );
+
+        InsuranceFundEvents::bankruptcy_payment(
+            &env,
+            market,
+            payment,
+            claim.settled_insurance,
+            now,
+        );
+
+        payment
+    }
+
     // ################################################################
     //                             USER
     // ################################################################
@@ -322,6 +418,13 @@ impl InsuranceFundTrait for Insurance {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         get_stake(&env, &address)
     }
+
+    fn query_market_claim(env: Env, market: Address) -> MarketClaim {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_market_claim(&env, &market)
+    }
 }
 
 #[contractimpl]
@@ -333,6 +436,7 @@ impl BufferTrait for Insurance {
 
         let mut buffer = get_buffer(&env);
         buffer.max_balance = max_balance;
+        save_buffer(&env, buffer);
     }
 
     fn deposit_into_buffer(env: Env, sender: Address, amount: i128) {
@@ -381,96 +485,128 @@ impl BufferTrait for Insurance {
             .burn(&env.current_contract_address(), &amount);
     }
 
-    fn execute_buffer_auction(env: Env, sender: Address, amount: i128) {
+    #[allow(clippy::too_many_arguments)]
+    fn execute_buffer_auction(
+        env: Env,
+        sender: Address,
+        auction_type: AuctionType,
+        market: Option<Address>,
+        amount: i128,
+        start_price: u64,
+        end_price: u64,
+        duration: u64,
+    ) -> u32 {
         sender.require_auth();
+        utils::is_admin(&env, sender);
         utils::check_nonnegative_amount(amount);
 
-        let buffer = get_buffer(&env);
+        let now = env.ledger().timestamp();
+        let insurance_fund = get_insurance_fund(&env);
+        let mut buffer = get_buffer(&env);
 
-        // Mint gov tokens
-        token_contract::Client::new(&env, &buffer.governance_token)
-            .mint(&env.current_contract_address(), &amount);
-
-        // let out_amount = match buffer.auction_location {
-        //     AuctionLocation::Native => {
-        //         // TODO: run gov token auction
-        //     }
-        //     AuctionLocation::External => {
-        //         // Sell them (https://github.com/AquaToken/soroban-amm/blob/master/liquidity_pool_router/src/contract.rs#L222)
-        //         let x = LiquidityPoolInterfaceTrait::swap(
-        //             e,
-        //             user,
-        //             tokens,
-        //             token_in,
-        //             token_out,
-        //             pool_index,
-        //             in_amount,
-        //             out_min
-        //         );
-
-        //         let out_amt = pool_contract::Client
-        //             ::new(&env, &config.buffer.gov_token_pool)
-        //             .swap(
-        //                 &env,
-        //                 &env.current_contract_address(),
-        //                 vec![&config.buffer.gov_token, &config.buffer.quote_token],
-        //                 &config.buffer.gov_token,
-        //                 &config.buffer.quote_token,
-        //                 &config.buffer.pool_index,
-        //                 &amount,
-        //                 0
-        //             );
-        //         out_amt;
-        //     }
-        // };
+        let insurance_vault_amount =
+            token_contract::Client::new(&env, &insurance_fund.deposit_token)
+                .balance(&env.current_contract_address());
+
+        let auction = controller::buffer::start_buffer_auction(
+            &env,
+            &mut buffer,
+            &insurance_fund,
+            insurance_vault_amount,
+            auction_type,
+            market,
+            amount,
+            start_price,
+            end_price,
+            duration,
+            now,
+        );
+
+        save_buffer(&env, buffer);
+
+        auction.id
     }
 
     // ################################################################
     //                             USER
     // ################################################################
 
-    // fn bid_buffer_auction(env: Env, user: Address, auction_ts: u64, bid_amount: i128) {
-    //     user.require_auth();
-    //     check_nonnegative_amount(bid_amount);
+    fn bid_buffer_auction(
+        env: Env,
+        sender: Address,
+        auction_id: u32,
+        amount: i128,
+        max_price: u64,
+    ) {
+        sender.require_auth();
+        utils::check_nonnegative_amount(amount);
+
+        let now = env.ledger().timestamp();
+        let mut buffer = get_buffer(&env);
 
-    //     let auction = get_auction(&env, auction_ts);
+        controller::buffer::bid_buffer_auction(
+            &env,
+            &mut buffer,
+            auction_id,
+            &sender,
+            amount,
+            max_price,
+            now,
+        );
 
-    //     validate_auction(&env, auction);
+        save_buffer(&env, buffer);
+    }
 
-    //     let config = get_config(&env);
+    fn settle_buffer_auction(env: Env, sender: Address, auction_id: u32) {
+        sender.require_auth();
 
-    //     // user sends quote token
-    //     token_contract::Client::new(&env, &config.buffer.quote_token).transfer(
-    //         &user,
-    //         &env.current_contract_address(),
-    //         &bid_amount,
-    //     );
+        let now = env.ledger().timestamp();
+        let mut buffer = get_buffer(&env);
 
-    //     // buffer sends gov token
-    //     let gov_token_amount = 0; // TODO:
-    //     token_contract::Client::new(&env, &config.buffer.gov_token).transfer(
-    //         &env.current_contract_address(),
-    //         &user,
-    //         &gov_token_amount,
-    //     );
+        controller::buffer::settle_buffer_auction(&env, &mut buffer, auction_id, now);
 
-    //     // update buffer and auction
-    //     auction.available_balance -= gov_token_amount;
-    // }
+        save_buffer(&env, buffer);
+    }
 
     // ################################################################
     //                             QUERIES
     // ################################################################
 
-    // fn query_buffer(env: Env) {}
+    fn query_buffer(env: Env) -> Buffer {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_buffer(&env)
+    }
 
-    // fn query_buffer_auctions(env: Env) {}
+    fn query_buffer_auctions(env: Env) -> Vec<BufferAuction> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        get_buffer(&env).auctions
+    }
 
-    // fn query_buffe_balance(env: Env) {}
-}
+    fn query_buffer_auction(env: Env, auction_id: u32) -> BufferAuction {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        match get_buffer(&env).get_auction(auction_id) {
+            Some(auction) => auction,
+            None => {
+                log!(&env, "Buffer auction {} not found", auction_id);
+                panic_with_error!(&env, Errors::BufferAuctionNotFound);
+            }
+        }
+    }
 
-// fn validate_auction(env: &Env, auction: Auction) {
-//     // check balance
+    fn query_buffer_balance(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-//     // check ts within duration
-// }
+        let buffer = get_buffer(&env);
+        token_contract::Client::new(&env, &buffer.governance_token)
+            .balance(&env.current_contract_address())
+    }
+}
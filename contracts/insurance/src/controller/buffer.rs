@@ -1 +1,284 @@
+use normal::{
+    constants::PRICE_PRECISION_I128,
+    get_then_update_id,
+    math::{
+        auction::{calculate_auction_price, is_auction_complete},
+        casting::Cast,
+        safe_math::SafeMath,
+    },
+    types::{
+        auction::{Auction, AuctionLocation, AuctionType},
+        misc::OrderDirection,
+    },
+    validate,
+};
+use soroban_sdk::{log, panic_with_error, vec, Address, Env, IntoVal, Symbol};
 
+use crate::{
+    errors::Errors,
+    events::BufferEvents,
+    storage::{is_market_registered, Buffer, BufferAuction, InsuranceFund},
+    token_contract,
+};
+
+/// Starts a native Dutch auction that decays from `start_price` to `end_price`
+/// over `duration` seconds.
+///
+/// Debt auctions mint the NORM lot up front and recapitalize a registered `market`.
+/// Surplus auctions reserve quote tokens held over `max_insurance` that aren't already
+/// for sale.
+#[allow(clippy::too_many_arguments)]
+pub fn start_buffer_auction(
+    env: &Env,
+    buffer: &mut Buffer,
+    insurance_fund: &InsuranceFund,
+    insurance_vault_amount: i128,
+    auction_type: AuctionType,
+    market: Option<Address>,
+    amount: i128,
+    start_price: u64,
+    end_price: u64,
+    duration: u64,
+    now: u64,
+) -> BufferAuction {
+    validate!(
+        env,
+        amount > 0 && end_price > 0 && start_price >= end_price,
+        Errors::InvalidBufferAuction,
+        "invalid auction amount {} or prices {} -> {}",
+        amount,
+        start_price,
+        end_price
+    );
+
+    validate!(
+        env,
+        duration >= buffer.min_auction_duration,
+        Errors::BufferAuctionDurationTooShort,
+        "auction duration {} < min {}",
+        duration,
+        buffer.min_auction_duration
+    );
+
+    match auction_type {
+        AuctionType::Debt => {
+            validate!(
+                env,
+                market
+                    .as_ref()
+                    .is_some_and(|market| is_market_registered(env, market)),
+                Errors::MarketNotRegistered,
+                "debt auctions must recapitalize a registered market"
+            );
+
+            token_contract::Client::new(env, &buffer.governance_token)
+                .mint(&env.current_contract_address(), &amount);
+
+            buffer.total_mints = buffer.total_mints.safe_add(amount, env);
+        }
+        AuctionType::Surplus => {
+            let surplus = insurance_vault_amount
+                .safe_sub(insurance_fund.max_insurance.cast(env), env)
+                .safe_sub(buffer.reserved_surplus(), env);
+
+            validate!(
+                env,
+                amount <= surplus,
+                Errors::InsufficientSurplus,
+                "auction amount {} > surplus {}",
+                amount,
+                surplus
+            );
+        }
+        AuctionType::Collateral => {
+            log!(env, "collateral auctions are run by markets");
+            panic_with_error!(env, Errors::InvalidBufferAuction);
+        }
+    }
+
+    let auction = BufferAuction {
+        id: get_then_update_id!(buffer, next_auction_id),
+        auction_type,
+        market,
+        auction: Auction {
+            amount,
+            direction: OrderDirection::Sell,
+            location: AuctionLocation::Native,
+            duration,
+            start_ts: now,
+            total_auctioned: 0,
+            start_price,
+            end_price,
+        },
+        proceeds: 0,
+    };
+    buffer.save_auction(&auction);
+
+    BufferEvents::buffer_auction_start(
+        env,
+        auction_type,
+        auction.id,
+        amount,
+        start_price,
+        end_price,
+        duration,
+    );
+
+    auction
+}
+
+/// Buys up to `amount` of an auction's lot at its current price. Bids beyond the
+/// remaining lot are only charged for what they fill.
+///
+/// NORM paid into surplus auctions is burned. The auction is settled once the lot
+/// sells out.
+///
+/// Returns `(amount_bought, payment)`
+pub fn bid_buffer_auction(
+    env: &Env,
+    buffer: &mut Buffer,
+    auction_id: u32,
+    bidder: &Address,
+    amount: i128,
+    max_price: u64,
+    now: u64,
+) -> (i128, i128) {
+    let mut auction = get_auction(env, buffer, auction_id);
+
+    validate!(
+        env,
+        !is_auction_complete(env, auction.auction.start_ts, auction.auction.duration, now),
+        Errors::BufferAuctionExpired,
+        "buffer auction {} expired",
+        auction_id
+    );
+
+    let price = calculate_auction_price(env, &auction.auction, now, 1);
+
+    validate!(
+        env,
+        price <= max_price,
+        Errors::BufferAuctionPriceAboveLimit,
+        "auction price {} > max price {}",
+        price,
+        max_price
+    );
+
+    let amount_bought = amount.min(auction.remaining_amount());
+    let payment = amount_bought
+        .safe_mul(price.cast(env), env)
+        .safe_div_ceil(PRICE_PRECISION_I128, env);
+
+    validate!(
+        env,
+        amount_bought > 0 && payment > 0,
+        Errors::InvalidBufferAuctionBid,
+        "bid does not buy any of the lot"
+    );
+
+    let payment_token = token_contract::Client::new(env, &auction.payment_token(buffer));
+    payment_token.transfer(bidder, &env.current_contract_address(), &payment);
+
+    if auction.auction_type == AuctionType::Surplus {
+        payment_token.burn(&env.current_contract_address(), &payment);
+        buffer.total_burns = buffer.total_burns.safe_add(payment, env);
+    }
+
+    token_contract::Client::new(env, &auction.lot_token(buffer)).transfer(
+        &env.current_contract_address(),
+        bidder,
+        &amount_bought,
+    );
+
+    auction.auction.total_auctioned = auction.auction.total_auctioned.safe_add(amount_bought, env);
+    auction.proceeds = auction.proceeds.safe_add(payment, env);
+
+    BufferEvents::buffer_auction_bid(
+        env,
+        bidder.clone(),
+        auction.id,
+        amount_bought,
+        payment,
+        price,
+    );
+
+    if auction.remaining_amount() == 0 {
+        close_buffer_auction(env, buffer, &auction);
+    } else {
+        buffer.save_auction(&auction);
+    }
+
+    (amount_bought, payment)
+}
+
+/// Closes an expired auction. Unsold NORM from debt auctions is burned and unsold
+/// quote tokens from surplus auctions are released back to the Insurance Fund.
+///
+/// Debt auction proceeds are paid to the auction's market to repay its protocol debt.
+///
+/// Returns the unsold amount
+pub fn settle_buffer_auction(env: &Env, buffer: &mut Buffer, auction_id: u32, now: u64) -> i128 {
+    let auction = get_auction(env, buffer, auction_id);
+
+    validate!(
+        env,
+        is_auction_complete(env, auction.auction.start_ts, auction.auction.duration, now),
+        Errors::BufferAuctionInProgress,
+        "buffer auction {} still in progress",
+        auction_id
+    );
+
+    close_buffer_auction(env, buffer, &auction)
+}
+
+fn get_auction(env: &Env, buffer: &Buffer, auction_id: u32) -> BufferAuction {
+    match buffer.get_auction(auction_id) {
+        Some(auction) => auction,
+        None => {
+            log!(env, "Buffer auction {} not found", auction_id);
+            panic_with_error!(env, Errors::BufferAuctionNotFound);
+        }
+    }
+}
+
+fn close_buffer_auction(env: &Env, buffer: &mut Buffer, auction: &BufferAuction) -> i128 {
+    let refunded = auction.remaining_amount();
+
+    if refunded > 0 && auction.auction_type == AuctionType::Debt {
+        token_contract::Client::new(env, &buffer.governance_token)
+            .burn(&env.current_contract_address(), &refunded);
+        buffer.total_burns = buffer.total_burns.safe_add(refunded, env);
+    }
+
+    if auction.proceeds > 0 && auction.auction_type == AuctionType::Debt {
+        if let Some(market) = &auction.market {
+            token_contract::Client::new(env, &buffer.quote_token).transfer(
+                &env.current_contract_address(),
+                market,
+                &auction.proceeds,
+            );
+            env.invoke_contract::<()>(
+                market,
+                &Symbol::new(env, "repay_protocol_debt"),
+                vec![
+                    env,
+                    env.current_contract_address().into_val(env),
+                    auction.proceeds.into_val(env),
+                ],
+            );
+        }
+    }
+
+    buffer.remove_auction(auction.id);
+
+    BufferEvents::buffer_auction_settle(
+        env,
+        auction.auction_type,
+        auction.id,
+        auction.auction.total_auctioned,
+        auction.proceeds,
+        refunded,
+    );
+
+    refunded
+}
@@ -1,2 +1,2 @@
-mod buffer;
+pub mod buffer;
 pub mod stake;
@@ -38,4 +38,13 @@ pub enum Errors {
     AlreadyInitialized = 15,
     NotAuthorized = 16,
     AdminNotSet = 17,
+    InvalidBufferAuction = 18,
+    BufferAuctionDurationTooShort = 19,
+    InsufficientSurplus = 20,
+    BufferAuctionNotFound = 21,
+    BufferAuctionExpired = 22,
+    BufferAuctionInProgress = 23,
+    BufferAuctionPriceAboveLimit = 24,
+    InvalidBufferAuctionBid = 25,
+    MarketNotRegistered = 26,
 }
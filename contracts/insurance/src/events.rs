@@ -1,3 +1,4 @@
+use normal::types::auction::AuctionType;
 use soroban_sdk::{Address, Env, Symbol};
 
 use crate::storage::StakeAction;
@@ -25,6 +26,31 @@ impl InsuranceFundEvents {
         env.events().publish(topics, (ts, share_token_address));
     }
 
+    /// Emitted when a market settles revenue to the Insurance Fund
+    ///
+    /// - topics - `["revenue_deposit", sender: Address]`
+    /// - data - `[token: Address, amount: i128, ts: u64]`
+    pub fn revenue_deposit(env: &Env, sender: Address, token: Address, amount: i128, ts: u64) {
+        let topics = (Symbol::new(&env, "revenue_deposit"), sender);
+        env.events().publish(topics, (token, amount, ts));
+    }
+
+    /// Emitted when the Insurance Fund covers a market's bankrupt position
+    ///
+    /// - topics - `["bankruptcy_payment", market: Address]`
+    /// - data - `[amount: i128, settled_insurance: i128, ts: u64]`
+    pub fn bankruptcy_payment(
+        env: &Env,
+        market: Address,
+        amount: i128,
+        settled_insurance: i128,
+        ts: u64,
+    ) {
+        let topics = (Symbol::new(&env, "bankruptcy_payment"), market);
+        env.events()
+            .publish(topics, (amount, settled_insurance, ts));
+    }
+
     // Insurance Stake Events
 
     /// Emitted when a user updates their stake in the Insurance Fund
@@ -64,3 +90,56 @@ impl InsuranceFundEvents {
 }
 
 pub struct BufferEvents {}
+
+impl BufferEvents {
+    /// Emitted when the Buffer starts a debt or surplus auction
+    ///
+    /// - topics - `["buffer_auction_start", auction_type: AuctionType]`
+    /// - data - `[id: u32, amount: i128, start_price: u64, end_price: u64, duration: u64]`
+    pub fn buffer_auction_start(
+        env: &Env,
+        auction_type: AuctionType,
+        id: u32,
+        amount: i128,
+        start_price: u64,
+        end_price: u64,
+        duration: u64,
+    ) {
+        let topics = (Symbol::new(&env, "buffer_auction_start"), auction_type);
+        env.events()
+            .publish(topics, (id, amount, start_price, end_price, duration));
+    }
+
+    /// Emitted when a bid fills part of a Buffer auction
+    ///
+    /// - topics - `["buffer_auction_bid", bidder: Address]`
+    /// - data - `[id: u32, amount: i128, payment: i128, price: u64]`
+    pub fn buffer_auction_bid(
+        env: &Env,
+        bidder: Address,
+        id: u32,
+        amount: i128,
+        payment: i128,
+        price: u64,
+    ) {
+        let topics = (Symbol::new(&env, "buffer_auction_bid"), bidder);
+        env.events().publish(topics, (id, amount, payment, price));
+    }
+
+    /// Emitted when a Buffer auction sells out or is settled after expiry
+    ///
+    /// - topics - `["buffer_auction_settle", auction_type: AuctionType]`
+    /// - data - `[id: u32, total_auctioned: i128, proceeds: i128, refunded: i128]`
+    pub fn buffer_auction_settle(
+        env: &Env,
+        auction_type: AuctionType,
+        id: u32,
+        total_auctioned: i128,
+        proceeds: i128,
+        refunded: i128,
+    ) {
+        let topics = (Symbol::new(&env, "buffer_auction_settle"), auction_type);
+        env.events()
+            .publish(topics, (id, total_auctioned, proceeds, refunded));
+    }
+}
@@ -1,6 +1,6 @@
 use soroban_sdk::{Address, BytesN, Env, String};
 
-use crate::storage::{InsuranceFund, Stake};
+use crate::storage::{InsuranceFund, MarketClaim, Stake};
 
 pub trait InsuranceFundTrait {
     // ################################################################
@@ -20,7 +20,18 @@ pub trait InsuranceFundTrait {
         max_buffer_balance: i128,
     );
 
-    fn deposit_revenue(env: Env);
+    /// Receives revenue settled by a market. The tokens are pulled from `sender`
+    /// using an allowance granted to the Insurance Fund for `amount`.
+    fn deposit_revenue(env: Env, sender: Address, token: Address, amount: i128);
+
+    /// Sets the max amount of deposit tokens `market` can draw to cover bankruptcies
+    fn update_market_max_insurance(env: Env, sender: Address, market: Address, max_insurance: i128);
+
+    /// Pays up to `amount` deposit tokens to `market` to cover a bankrupt position.
+    ///
+    /// Payments are capped at the market's remaining claim and leave at least 1 token in
+    /// the vault. Returns the amount paid.
+    fn cover_bankruptcy(env: Env, market: Address, amount: i128) -> i128;
 
     // ################################################################
     //                             User
@@ -43,4 +54,6 @@ pub trait InsuranceFundTrait {
     fn query_admin(env: Env) -> Address;
 
     fn query_if_stake(env: Env, address: Address) -> Stake;
+
+    fn query_market_claim(env: Env, market: Address) -> MarketClaim;
 }
@@ -1,7 +1,7 @@
 use normal::{
     constants::{PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD},
     safe_decrement, safe_increment,
-    types::OrderDirection,
+    types::auction::{Auction, AuctionType},
     validate,
 };
 use soroban_sdk::{contracttype, log, Address, Env, Vec};
@@ -79,54 +79,105 @@ pub fn get_insurance_fund(env: &Env) -> InsuranceFund {
 }
 
 // ################################################################
-//                             Auction
+//                          Market Claims
 // ################################################################
 
 #[contracttype]
-#[derive(Clone, Copy, PartialEq, Debug, Eq)]
-pub enum AuctionType {
-    /// selling collateral from a liquidation
-    Collateral,
-    /// selling newly minted NORM to cover Protocol Debt (the deficit from Collateral Auctions)
-    Debt,
-    /// selling excess synthetic token proceeds over the Insurance Fund max limit for NORM to be burned
-    Surplus,
+#[derive(Clone, Debug)]
+pub enum MarketClaimKey {
+    Market(Address),
 }
 
-/**
- * Native auctions:
- * - set a balance available for purchase
- * - set a price and bidding config
- * - users can use functions to bid and purchase on the auction
- * - contract simply updates properties as purchases occur
- */
-
+/// A market's claim on the Insurance Fund to cover position bankruptcies
 #[contracttype]
-#[derive(Clone, Copy, PartialEq, Debug, Eq)]
-pub enum AuctionLocation {
-    /// Sell the asset directly to users via Normal interface
-    Native,
-    /// Sell the asset via a 3rd-party DEX
-    External,
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MarketClaim {
+    /// The max amount of deposit tokens the market can draw
+    pub max_insurance: i128,
+    /// The amount of deposit tokens the market has drawn
+    pub settled_insurance: i128,
 }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Auction {
-    pub amount: i128,
-    pub direction: OrderDirection,
-    pub location: AuctionLocation,
-    pub duration: u64,
-    pub start_ts: u64,
-    pub total_auctioned: i128,
-    pub start_price: u64,
-    pub end_price: u64,
+pub fn get_market_claim(env: &Env, market: &Address) -> MarketClaim {
+    let key = MarketClaimKey::Market(market.clone());
+    let claim = match env.storage().persistent().get::<_, MarketClaim>(&key) {
+        Some(claim) => claim,
+        None => MarketClaim::default(),
+    };
+    env.storage().persistent().has(&key).then(|| {
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    });
+
+    claim
+}
+
+/// Markets are registered with the Insurance Fund once the admin sets their claim
+pub fn is_market_registered(env: &Env, market: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&MarketClaimKey::Market(market.clone()))
+}
+
+pub fn save_market_claim(env: &Env, market: &Address, claim: &MarketClaim) {
+    let key = MarketClaimKey::Market(market.clone());
+    env.storage().persistent().set(&key, claim);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
 }
 
 // ################################################################
 //                             Buffer
 // ################################################################
 
+/// Native Dutch auction run by the Buffer
+///
+/// - `Debt` auctions sell newly minted NORM for quote tokens that repay a market's
+///   protocol debt
+/// - `Surplus` auctions sell quote tokens over `max_insurance` for NORM that is burned
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BufferAuction {
+    pub id: u32,
+    pub auction_type: AuctionType,
+    /// The market whose protocol debt a `Debt` auction repays
+    pub market: Option<Address>,
+    /// Lot and price curve. `amount` is the lot for sale and `total_auctioned`
+    /// the lot sold so far
+    /// price precision: PRICE_PRECISION (payment tokens per lot token)
+    pub auction: Auction,
+    /// Payment tokens received from bids
+    pub proceeds: i128,
+}
+
+impl BufferAuction {
+    pub fn remaining_amount(&self) -> i128 {
+        self.auction.amount - self.auction.total_auctioned
+    }
+
+    /// Token sold by the auction
+    pub fn lot_token(&self, buffer: &Buffer) -> Address {
+        match self.auction_type {
+            AuctionType::Surplus => buffer.quote_token.clone(),
+            _ => buffer.governance_token.clone(),
+        }
+    }
+
+    /// Token bidders pay with
+    pub fn payment_token(&self, buffer: &Buffer) -> Address {
+        match self.auction_type {
+            AuctionType::Surplus => buffer.governance_token.clone(),
+            _ => buffer.quote_token.clone(),
+        }
+    }
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Buffer {
@@ -134,7 +185,8 @@ pub struct Buffer {
     pub governance_token_pool: Address, // DEX pool - Aquarius pool router: CBQDHNBFBZYE4MKPWBSJOPIYLW4SFSXAXUTSXJN76GNKYVYPCKWC6QUK
     pub quote_token: Address,
     // Auction
-    pub auctions: Vec<Auction>,
+    pub auctions: Vec<BufferAuction>,
+    pub next_auction_id: u32,
     pub min_auction_duration: u64,
     // other
     pub max_balance: i128,
@@ -142,7 +194,34 @@ pub struct Buffer {
     pub total_mints: i128,
 }
 
-impl Buffer {}
+impl Buffer {
+    pub fn get_auction(&self, id: u32) -> Option<BufferAuction> {
+        self.auctions.iter().find(|a| a.id == id)
+    }
+
+    /// Inserts or replaces the live auction with `auction.id`
+    pub fn save_auction(&mut self, auction: &BufferAuction) {
+        match self.auctions.iter().position(|a| a.id == auction.id) {
+            Some(index) => self.auctions.set(index as u32, auction.clone()),
+            None => self.auctions.push_back(auction.clone()),
+        }
+    }
+
+    pub fn remove_auction(&mut self, id: u32) {
+        if let Some(index) = self.auctions.iter().position(|a| a.id == id) {
+            self.auctions.remove(index as u32);
+        }
+    }
+
+    /// Quote tokens still for sale in live surplus auctions
+    pub fn reserved_surplus(&self) -> i128 {
+        self.auctions
+            .iter()
+            .filter(|a| a.auction_type == AuctionType::Surplus)
+            .map(|a| a.remaining_amount())
+            .sum()
+    }
+}
 
 pub fn save_buffer(env: &Env, buffer: Buffer) {
     env.storage().persistent().set(&DataKey::Buffer, &buffer);
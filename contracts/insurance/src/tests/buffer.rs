@@ -1,6 +1,9 @@
 extern crate std;
 
-use normal::constants::{ONE_MILLION_QUOTE, THIRTEEN_DAY};
+use normal::{
+    constants::{ONE_MILLION_QUOTE, THIRTEEN_DAY},
+    types::auction::AuctionType,
+};
 use pretty_assertions::assert_eq;
 use soroban_sdk::{
     symbol_short,
@@ -8,12 +11,14 @@ use soroban_sdk::{
     vec, Address, Env, IntoVal, Symbol, Vec,
 };
 
-use super::setup::{deploy_insurance_contract, deploy_token_contract};
+use super::setup::{
+    deploy_insurance_contract, deploy_token_contract, MockMarket, MockMarketClient,
+};
 
 use crate::{
     contract::{Insurance, InsuranceClient},
     msg::{ConfigResponse, StakedResponse},
-    storage::{InsuranceFund, Stake},
+    storage::{get_buffer, save_buffer, InsuranceFund, Stake},
     tests::setup::{ONE_DAY, ONE_WEEK},
 };
 
@@ -120,3 +125,252 @@ fn auction() {
     assert_eq!(lp_token.balance(&user), 0);
     assert_eq!(lp_token.balance(&insurance.address), 10_000);
 }
+
+fn use_governance_token(env: &Env, insurance: &InsuranceClient, governance_token: &Address) {
+    env.as_contract(&insurance.address, || {
+        let mut buffer = get_buffer(env);
+        buffer.governance_token = governance_token.clone();
+        save_buffer(env, buffer);
+    });
+}
+
+#[test]
+fn debt_auction_sells_minted_norm_for_quote() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let user = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    let market = MockMarketClient::new(&env, &env.register(MockMarket, ()));
+    insurance.update_market_max_insurance(&admin, &market.address, &0);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = ONE_WEEK;
+    });
+
+    let auction_id = insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Debt,
+        &Some(market.address.clone()),
+        &10_000,
+        &2_000_000,
+        &1_000_000,
+        &3_600,
+    );
+
+    assert_eq!(insurance.query_buffer_balance(), 10_000);
+    assert_eq!(insurance.query_buffer().total_mints, 10_000);
+    assert_eq!(insurance.query_buffer_auctions().len(), 1);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = ONE_WEEK + 1_800;
+    });
+
+    // halfway through the auction NORM sells for 1.5 quote tokens
+    deposit_token.mint(&user, &100_000);
+    insurance.bid_buffer_auction(&user, &auction_id, &4_000, &1_500_000);
+
+    assert_eq!(gov_token.balance(&user), 4_000);
+    assert_eq!(deposit_token.balance(&user), 94_000);
+    assert_eq!(deposit_token.balance(&insurance.address), 6_000);
+
+    let auction = insurance.query_buffer_auction(&auction_id);
+    assert_eq!(auction.auction.total_auctioned, 4_000);
+    assert_eq!(auction.proceeds, 6_000);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = ONE_WEEK + 3_601;
+    });
+
+    // the unsold NORM is burned once the auction expires
+    insurance.settle_buffer_auction(&user, &auction_id);
+
+    let buffer = insurance.query_buffer();
+    assert_eq!(buffer.total_burns, 6_000);
+    assert_eq!(buffer.auctions.len(), 0);
+    assert_eq!(insurance.query_buffer_balance(), 0);
+
+    // the proceeds repay the market's protocol debt
+    assert_eq!(deposit_token.balance(&insurance.address), 0);
+    assert_eq!(deposit_token.balance(&market.address), 6_000);
+    assert_eq!(market.query_repaid(), 6_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn debt_auction_requires_registered_market() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Debt,
+        &Some(Address::generate(&env)),
+        &10_000,
+        &2_000_000,
+        &1_000_000,
+        &3_600,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn collateral_buffer_auction_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Collateral,
+        &None,
+        &10_000,
+        &2_000_000,
+        &1_000_000,
+        &3_600,
+    );
+}
+
+#[test]
+fn surplus_auction_burns_norm_bids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let user = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    deposit_token.mint(&insurance.address, &(ONE_MILLION_QUOTE as i128 + 5_000));
+    gov_token.mint(&user, &10_000);
+
+    let auction_id = insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Surplus,
+        &None,
+        &5_000,
+        &1_000_000,
+        &500_000,
+        &3_600,
+    );
+
+    // bids larger than the lot only pay for what is left
+    insurance.bid_buffer_auction(&user, &auction_id, &8_000, &1_000_000);
+
+    assert_eq!(deposit_token.balance(&user), 5_000);
+    assert_eq!(gov_token.balance(&user), 5_000);
+    assert_eq!(
+        deposit_token.balance(&insurance.address),
+        ONE_MILLION_QUOTE as i128
+    );
+
+    // the auction is settled as soon as it sells out
+    let buffer = insurance.query_buffer();
+    assert_eq!(buffer.total_burns, 5_000);
+    assert_eq!(buffer.auctions.len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #20)")]
+fn surplus_auction_cannot_exceed_excess_over_max_insurance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    deposit_token.mint(&insurance.address, &(ONE_MILLION_QUOTE as i128 + 5_000));
+
+    insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Surplus,
+        &None,
+        &3_000,
+        &1_000_000,
+        &500_000,
+        &3_600,
+    );
+    // only 2_000 of the surplus is left unreserved
+    insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Surplus,
+        &None,
+        &3_000,
+        &1_000_000,
+        &500_000,
+        &3_600,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #22)")]
+fn bid_on_expired_buffer_auction_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor = Address::generate(&env);
+    let user = Address::generate(&env);
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let gov_token = deploy_token_contract(&env, &admin);
+
+    let insurance = deploy_insurance_contract(&env, &admin, &governor, &deposit_token.address);
+    use_governance_token(&env, &insurance, &gov_token.address);
+
+    let market = env.register(MockMarket, ());
+    insurance.update_market_max_insurance(&admin, &market, &0);
+
+    let auction_id = insurance.execute_buffer_auction(
+        &admin,
+        &AuctionType::Debt,
+        &Some(market),
+        &10_000,
+        &2_000_000,
+        &1_000_000,
+        &3_600,
+    );
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 3_601;
+    });
+
+    deposit_token.mint(&user, &100_000);
+    insurance.bid_buffer_auction(&user, &auction_id, &1_000, &2_000_000);
+}
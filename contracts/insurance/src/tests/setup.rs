@@ -1,4 +1,4 @@
-use soroban_sdk::{Address, BytesN, Env, String};
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, String};
 
 use crate::{
     contract::{Insurance, InsuranceClient},
@@ -29,6 +29,32 @@ fn install_insurnace_latest_wasm(env: &Env) -> BytesN<32> {
     env.deployer().upload_contract_wasm(insurnace_latest::WASM)
 }
 
+/// Stands in for a market, recording the protocol debt repaid by debt auctions
+#[contract]
+pub struct MockMarket;
+
+#[contractimpl]
+impl MockMarket {
+    pub fn repay_protocol_debt(env: Env, sender: Address, amount: i128) {
+        sender.require_auth();
+        let repaid: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("repaid"))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("repaid"), &(repaid + amount));
+    }
+
+    pub fn query_repaid(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("repaid"))
+            .unwrap_or(0)
+    }
+}
+
 pub const ONE_WEEK: u64 = 604800;
 pub const ONE_DAY: u64 = 86400;
 pub const SIXTY_DAYS: u64 = 60 * ONE_DAY;
@@ -12,7 +12,7 @@ use super::setup::{deploy_insurance_contract, deploy_token_contract, install_tok
 
 use crate::{
     math::insurance::vault_amount_to_if_shares,
-    storage::{InsuranceFund, Stake},
+    storage::{InsuranceFund, MarketClaim, Stake},
     tests::setup::ONE_DAY,
 };
 
@@ -472,3 +472,99 @@ fn pay_rewards_during_remove_stake() {
         }
     );
 }
+
+#[test]
+fn deposit_revenue_pulls_approved_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor_contract = Address::generate(&env);
+    let market = Address::generate(&env);
+
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let synthetic_token = deploy_token_contract(&env, &admin);
+    let insurance =
+        deploy_insurance_contract(&env, &admin, &governor_contract, &deposit_token.address);
+
+    insurance.update_market_max_insurance(&admin, &market, &0);
+
+    deposit_token.mint(&market, &5_000);
+    synthetic_token.mint(&market, &2_000);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = ONE_DAY;
+    });
+
+    deposit_token.approve(&market, &insurance.address, &5_000, &1_000);
+    insurance.deposit_revenue(&market, &deposit_token.address, &5_000);
+
+    synthetic_token.approve(&market, &insurance.address, &2_000, &1_000);
+    insurance.deposit_revenue(&market, &synthetic_token.address, &2_000);
+
+    assert_eq!(deposit_token.balance(&insurance.address), 5_000);
+    assert_eq!(deposit_token.balance(&market), 0);
+    assert_eq!(synthetic_token.balance(&insurance.address), 2_000);
+    assert_eq!(synthetic_token.balance(&market), 0);
+    assert_eq!(
+        insurance.query_insurance_fund().last_revenue_settle_ts,
+        ONE_DAY
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn deposit_revenue_from_unregistered_market_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor_contract = Address::generate(&env);
+    let sender = Address::generate(&env);
+
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let insurance =
+        deploy_insurance_contract(&env, &admin, &governor_contract, &deposit_token.address);
+
+    deposit_token.mint(&sender, &5_000);
+    deposit_token.approve(&sender, &insurance.address, &5_000, &1_000);
+
+    insurance.deposit_revenue(&sender, &deposit_token.address, &5_000);
+}
+
+#[test]
+fn cover_bankruptcy_capped_by_market_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let governor_contract = Address::generate(&env);
+    let market = Address::generate(&env);
+
+    let deposit_token = deploy_token_contract(&env, &admin);
+    let insurance =
+        deploy_insurance_contract(&env, &admin, &governor_contract, &deposit_token.address);
+
+    deposit_token.mint(&insurance.address, &10_000);
+
+    // markets without a claim can't draw on the fund
+    assert_eq!(insurance.cover_bankruptcy(&market, &5_000), 0);
+
+    insurance.update_market_max_insurance(&admin, &market, &3_000);
+
+    assert_eq!(insurance.cover_bankruptcy(&market, &5_000), 3_000);
+    assert_eq!(insurance.cover_bankruptcy(&market, &5_000), 0);
+
+    assert_eq!(deposit_token.balance(&market), 3_000);
+    assert_eq!(deposit_token.balance(&insurance.address), 7_000);
+    assert_eq!(
+        insurance.query_market_claim(&market),
+        MarketClaim {
+            max_insurance: 3_000,
+            settled_insurance: 3_000,
+        }
+    );
+}
@@ -1,16 +1,24 @@
 use normal::{
     constants::{
         DAY_IN_SECONDS, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD,
-        LIQUIDATION_FEE_PRECISION, SPOT_IMF_PRECISION,
+        LIQUIDATION_FEE_PRECISION, ONE_HOUR, SPOT_IMF_PRECISION,
     },
     get_then_update_id,
-    math::{casting::Cast, safe_math::SafeMath},
+    math::{
+        casting::Cast,
+        oracle::{oracle_validity, validate_oracle_for_action, NormalAction},
+        safe_math::SafeMath,
+    },
     oracle::{
-        get_band_price, get_oracle_price, HistoricalOracleData, OraclePriceData, OracleSource,
+        get_band_price, get_oracle_price, get_oracle_price_with_feeds, HistoricalOracleData,
+        OracleFeed, OraclePriceData, OracleSource, ValidityGuardRails,
     },
     types::{
         auction::Auction,
-        market::{MarketFactoryConfig, MarketInfo, MarketParams, MarketResponse, SynthTier},
+        market::{
+            MarketFactoryConfig, MarketInfo, MarketParams, MarketPriceData, MarketResponse,
+            SynthTier,
+        },
     },
     validate, validate_bps,
 };
@@ -34,6 +42,7 @@ use crate::{
         },
     },
     state::{
+        auction::{get_collateral_auction, get_collateral_auctions, CollateralAuction},
         liquidity_position::{
             get_liquidity_position_by_ts, get_liquidity_position_info, LiquidityPositionUpdate,
         },
@@ -48,8 +57,11 @@ use crate::{
     },
     storage::utils::{self, get_admin, get_factory},
     token_contract,
-    utils::{sparse_swap::SparseSwapTickSequenceBuilder, swap_utils::update_and_swap_amm},
-    validation::margin::validate_margin,
+    utils::{
+        sparse_swap::{get_start_tick_indexes, SparseSwapTickSequenceBuilder},
+        swap_utils::update_and_swap_amm,
+    },
+    validation::{margin::validate_margin, oracle::validate_market_oracles_for_action},
 };
 
 contractmeta!(
@@ -154,6 +166,8 @@ impl MarketTrait for SynthMarket {
                 token: params.quote_token.clone(),
                 oracle: params.oracle.clone(),
                 oracle_source: params.oracle_source,
+                oracle_feeds: params.oracle_feeds.clone(),
+                historical_oracle_data: HistoricalOracleData::default(),
                 balance: 0,
                 token_twap: 0,
                 margin_ratio_initial: params.margin_ratio_initial,
@@ -220,6 +234,7 @@ impl MarketTrait for SynthMarket {
             expiry_price: 0,
             max_position_size: 0,
             next_deposit_record_id: 0,
+            next_auction_id: 0,
             initial_asset_weight: 0,
             maintenance_asset_weight: 0,
             initial_liability_weight: 0,
@@ -495,6 +510,62 @@ impl MarketTrait for SynthMarket {
         save_market(&env, market)
     }
 
+    fn update_revenue_settle_config(
+        env: Env,
+        sender: Address,
+        max_rev_withdraw_per_period: u64,
+        revenue_settle_period: i64,
+    ) {
+        utils::is_admin(&env, &sender, true);
+
+        let mut market = get_market(&env);
+
+        log!(&env, "updating market {} revenue settlement", market.name);
+
+        validate!(
+            &env,
+            revenue_settle_period >= 0,
+            Errors::RevenueSettingsCannotSettleToIF,
+            "Revenue settle period must be non-negative"
+        );
+
+        log!(
+            &env,
+            "market.insurance_claim.max_rev_withdraw_per_period: {} -> {}",
+            market.insurance_claim.max_rev_withdraw_per_period,
+            max_rev_withdraw_per_period
+        );
+
+        log!(
+            &env,
+            "market.insurance_claim.revenue_settle_period: {} -> {}",
+            market.insurance_claim.revenue_settle_period,
+            revenue_settle_period
+        );
+
+        market.insurance_claim.max_rev_withdraw_per_period = max_rev_withdraw_per_period;
+        market.insurance_claim.revenue_settle_period = revenue_settle_period;
+
+        save_market(&env, market)
+    }
+
+    fn update_max_insurance(env: Env, sender: Address, quote_max_insurance: u64) {
+        utils::is_admin(&env, &sender, true);
+
+        let mut market = get_market(&env);
+
+        log!(
+            &env,
+            "market.insurance_claim.quote_max_insurance: {} -> {}",
+            market.insurance_claim.quote_max_insurance,
+            quote_max_insurance
+        );
+
+        market.insurance_claim.quote_max_insurance = quote_max_insurance;
+
+        save_market(&env, market)
+    }
+
     fn update_name(env: Env, sender: Address, name: String) {
         utils::is_admin(&env, &sender, true);
 
@@ -584,6 +655,25 @@ impl MarketTrait for SynthMarket {
         market.collateral.oracle_source = oracle_source;
     }
 
+    fn update_collateral_oracle_feeds(env: Env, sender: Address, oracle_feeds: Vec<OracleFeed>) {
+        sender.require_auth();
+        utils::validate_super_keeper(&env, &sender);
+
+        let mut market = get_market(&env);
+        log!(&env, "market {}", market.name);
+
+        log!(
+            &env,
+            "market.collateral.oracle_feeds: {:?} -> {:?}",
+            market.collateral.oracle_feeds,
+            oracle_feeds
+        );
+
+        market.collateral.oracle_feeds = oracle_feeds;
+
+        save_market(&env, market);
+    }
+
     fn update_collateral_oracle_freeze(env: Env, keeper: Address, frozen: bool) {
         keeper.require_auth();
         utils::validate_super_keeper(env, &super_keeper);
@@ -609,56 +699,27 @@ impl MarketTrait for SynthMarket {
     fn settle_revenue(env: Env, keeper: Address) {
         keeper.require_auth();
 
-        /**
-         * Revenue is
-         */
-        let market = &mut get_market(&env);
+        let mut market = get_market(&env);
 
-        // validate!(
-        //     insurance_fund.revenue_settle_period > 0,
-        //     Errors::RevenueSettingsCannotSettleToIF,
-        //     "invalid revenue_settle_period settings on market"
-        // );
+        let now = env.ledger().timestamp();
 
-        let market_vault_amount = 0;
-        // let insurance_vault_amount = 0;
+        let (synthetic_amount, quote_amount) =
+            controller::insurance::settle_revenue_to_insurance_fund(
+                &env,
+                &mut market,
+                now.cast(&env),
+            );
 
-        let now = env.ledger().timestamp();
+        save_market(&env, market.clone());
 
-        // env.invoke_contract(&market.insurnace, func, args);
-
-        // let time_until_next_update = math::helpers::on_the_hour_update(
-        //     now,
-        //     &market.insurnace.last_revenue_settle_ts,
-        //     &market.insurnace.revenue_settle_period
-        // );
-
-        // validate!(
-        //     time_until_next_update == 0,
-        //     Errors::RevenueSettingsCannotSettleToIF,
-        //     "Must wait {} seconds until next available settlement time",
-        //     time_until_next_update
-        // );
-
-        // // uses proportion of revenue pool allocated to insurance fund
-        // let token_amount = controller::insurance::settle_revenue_to_insurance_fund(
-        //     spot_vault_amount,
-        //     insurance_vault_amount,
-        //     market,
-        //     now,
-        //     true
-        // );
-
-        // insurance_fund.last_revenue_settle_ts = now;
-
-        // token_contract::Client
-        //     ::new(&env, address)
-        //     .transfer(&env.current_contract_address(), &insurance_fund, token_amount);
-
-        // math::spot_withdraw::validate_spot_market_vault_amount(
-        //     spot_market,
-        //     ctx.accounts.spot_market_vault.amount
-        // );
+        MarketEvents::revenue_settlement(
+            &env,
+            market.name,
+            market.insurance,
+            synthetic_amount,
+            quote_amount,
+            now,
+        );
     }
 
     fn liquidate_position(
@@ -671,29 +732,232 @@ impl MarketTrait for SynthMarket {
         liquidator.require_auth();
 
         if user == liquidator {
-            return Err(Errors::UserCantLiquidateThemself);
+            panic_with_error!(&env, Errors::UserCantLiquidateThemself);
         }
 
         let now = env.ledger().timestamp();
-        let mut market = &mut get_market(&env);
+        let mut market = get_market(&env);
 
-        controller::liquidation::liquidate_position(
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = get_oracle_price_with_feeds(
+            &env,
+            &market.collateral.oracle_source,
+            &market.collateral.oracle,
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
+            now,
+            &oracle_guard_rails.validity,
+        );
+        let debt_oracle_price_data = get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        );
+
+        validate_market_oracles_for_action(
             &env,
             &market,
+            &collateral_oracle_price_data,
+            &debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Liquidate,
+        );
+
+        controller::liquidation::liquidate_position(
+            &env,
+            &mut market,
+            collateral_oracle_price_data.price,
+            debt_oracle_price_data.price,
             max_base_asset_amount,
             limit_price,
             &user,
             &liquidator,
             now,
         );
+
+        save_market(&env, market);
+    }
+
+    fn resolve_position_bankruptcy(env: Env, sender: Address, user: Address) {
+        sender.require_auth();
+
+        let now = env.ledger().timestamp();
+        let mut market = get_market(&env);
+
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = get_oracle_price_with_feeds(
+            &env,
+            &market.collateral.oracle_source,
+            &market.collateral.oracle,
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
+            now,
+            &oracle_guard_rails.validity,
+        );
+        let debt_oracle_price_data = get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        );
+
+        validate_market_oracles_for_action(
+            &env,
+            &market,
+            &collateral_oracle_price_data,
+            &debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Liquidate,
+        );
+
+        controller::liquidation::resolve_position_bankruptcy(
+            &env,
+            &mut market,
+            collateral_oracle_price_data.price,
+            debt_oracle_price_data.price,
+            &user,
+            &sender,
+            now,
+        );
+
+        save_market(&env, market);
+    }
+
+    fn repay_protocol_debt(env: Env, sender: Address, amount: i128) {
+        sender.require_auth();
+        utils::check_nonnegative_amount(amount);
+
+        let now = env.ledger().timestamp();
+        let mut market = get_market(&env);
+
+        validate!(
+            &env,
+            sender == market.insurance,
+            Errors::NotAuthorized,
+            "only the insurance fund can repay protocol debt"
+        );
+
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = get_oracle_price_with_feeds(
+            &env,
+            &market.collateral.oracle_source,
+            &market.collateral.oracle,
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
+            now,
+            &oracle_guard_rails.validity,
+        );
+        let debt_oracle_price_data = get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        );
+
+        let debt_repaid = controller::insurance::repay_protocol_debt(
+            &env,
+            &mut market,
+            amount,
+            collateral_oracle_price_data.price,
+            debt_oracle_price_data.price,
+        );
+
+        log!(
+            &env,
+            "repaid {} protocol debt with {} collateral",
+            debt_repaid,
+            amount
+        );
+
+        save_market(&env, market);
+    }
+
+    fn start_collateral_auction(env: Env, sender: Address, user: Address) -> u32 {
+        sender.require_auth();
+
+        let now = env.ledger().timestamp();
+        let mut market = get_market(&env);
+
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = get_oracle_price_with_feeds(
+            &env,
+            &market.collateral.oracle_source,
+            &market.collateral.oracle,
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
+            now,
+            &oracle_guard_rails.validity,
+        );
+        let debt_oracle_price_data = get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        );
+
+        validate_market_oracles_for_action(
+            &env,
+            &market,
+            &collateral_oracle_price_data,
+            &debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Liquidate,
+        );
+
+        let auction = controller::auction::start_collateral_auction(
+            &env,
+            &mut market,
+            collateral_oracle_price_data.price,
+            debt_oracle_price_data.price,
+            &user,
+            now,
+        );
+
+        save_market(&env, market);
+
+        auction.id
+    }
+
+    fn bid_collateral_auction(
+        env: Env,
+        sender: Address,
+        auction_id: u32,
+        collateral_amount: i128,
+        max_price: u64,
+    ) {
+        sender.require_auth();
+        utils::check_nonnegative_amount(collateral_amount);
+
+        let now = env.ledger().timestamp();
+        let mut market = get_market(&env);
+
+        controller::auction::bid_collateral_auction(
+            &env,
+            &mut market,
+            auction_id,
+            &sender,
+            collateral_amount,
+            max_price,
+            now,
+        );
+
+        save_market(&env, market);
     }
 
-    fn resolve_position_bankruptcy(env: Env, sender: Address) {
+    fn settle_collateral_auction(env: Env, sender: Address, auction_id: u32) {
         sender.require_auth();
 
-        // ..
+        let now = env.ledger().timestamp();
+        let mut market = get_market(&env);
+
+        controller::auction::settle_expired_collateral_auction(&env, &mut market, auction_id, now);
 
-        controller::liquidation::resolve_position_bankruptcy(&env);
+        save_market(&env, market);
     }
 
     // ################################################################
@@ -713,19 +977,22 @@ impl MarketTrait for SynthMarket {
         let mut market = get_market(&env);
 
         let now = env.ledger().timestamp();
-        let collateral_oracle_price_data = &get_oracle_price(
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = &get_oracle_price_with_feeds(
             &env,
             &market.collateral.oracle_source,
             &market.collateral.oracle,
-            (market.collateral.symbol, symbol_short!("USD")),
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
             now,
+            &oracle_guard_rails.validity,
         )
         .clone();
         let debt_oracle_price_data = &get_oracle_price(
             &env,
             &market.synthetic.oracle_source,
             &market.synthetic.oracle,
-            (market.synthetic.symbol, symbol_short!("USD")),
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
             now,
         )
         .clone();
@@ -744,6 +1011,15 @@ impl MarketTrait for SynthMarket {
             "Market collateral deposits paused"
         );
 
+        validate_market_oracles_for_action(
+            &env,
+            &market,
+            collateral_oracle_price_data,
+            debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Deposit,
+        );
+
         controller::balance::update_market_twap_stats(
             &env,
             &mut market,
@@ -854,23 +1130,35 @@ impl MarketTrait for SynthMarket {
         );
 
         let now = env.ledger().timestamp();
-        let collateral_oracle_price_data = &get_oracle_price(
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = &get_oracle_price_with_feeds(
             &env,
             &market.collateral.oracle_source,
             &market.collateral.oracle,
-            (market.collateral.symbol, symbol_short!("USD")),
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
             now,
+            &oracle_guard_rails.validity,
         )
         .clone();
         let debt_oracle_price_data = &get_oracle_price(
             &env,
             &market.synthetic.oracle_source,
             &market.synthetic.oracle,
-            (market.synthetic.symbol, symbol_short!("USD")),
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
             now,
         )
         .clone();
 
+        validate_market_oracles_for_action(
+            &env,
+            &market,
+            collateral_oracle_price_data,
+            debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Withdraw,
+        );
+
         let market_is_reduce_only = {
             controller::balance::update_market_twap_stats(
                 &env,
@@ -886,18 +1174,19 @@ impl MarketTrait for SynthMarket {
         let amount = {
             let reduce_only = reduce_only || market_is_reduce_only;
 
-            let mut amount = if reduce_only {
+            let amount = if reduce_only {
                 validate!(
                     &env,
                     position.balance_type == BalanceType::Deposit,
                     Errors::ReduceOnlyWithdrawIncreasedRisk
-                )?;
+                );
 
                 let max_withdrawable_amount =
-                    calculate_max_withdrawable_amount(&env, market, &position);
+                    calculate_max_withdrawable_amount(&env, market.clone(), &position)
+                        .cast::<i128>(&env);
 
                 let existing_deposit_amount =
-                    position.get_token_amount(&env, &market).cast::<u64>(&env);
+                    position.get_token_amount(&env, &market).cast::<i128>(&env);
 
                 amount
                     .min(max_withdrawable_amount)
@@ -908,7 +1197,7 @@ impl MarketTrait for SynthMarket {
 
             position.increment_total_withdraws(
                 &env,
-                amount,
+                amount.cast::<u64>(&env),
                 collateral_oracle_price_data.price,
                 market.get_precision().cast(&env),
             );
@@ -916,8 +1205,8 @@ impl MarketTrait for SynthMarket {
             // prevents withdraw when limits hit
             controller::market_position::update_balances_and_cumulative_deposits_with_limits(
                 &env,
-                amount as u128,
-                &BalanceType::Borrow,
+                amount,
+                &BalanceType::Deposit,
                 &mut market,
                 &mut position,
             );
@@ -928,8 +1217,8 @@ impl MarketTrait for SynthMarket {
         position.meets_withdraw_margin_requirement(
             &env,
             MarginRequirementType::Initial,
-            amount as u128,
-            now,
+            amount.cast::<u128>(&env),
+            now.cast::<i64>(&env),
         );
 
         // validate_spot_margin_trading(user, &perp_market_map, &spot_market_map, &mut oracle_map)?;
@@ -940,13 +1229,23 @@ impl MarketTrait for SynthMarket {
 
         position.update_last_active_ts(now);
 
-        // ....
+        market.validate_max_token_deposits_and_borrows(&env, false);
+
+        // Return the collateral token
+        token_contract::Client::new(&env, &market.collateral.token).transfer(
+            &env.current_contract_address(),
+            &sender,
+            &amount,
+        );
+
+        save_market_position(&env, &sender, &position);
+        save_market(&env, market.clone());
 
         MarketEvents::collateral_withdrawal(
             &env,
             market.name,
             sender,
-            market.collateral_token,
+            market.collateral.token,
             amount,
         );
     }
@@ -979,6 +1278,36 @@ impl MarketTrait for SynthMarket {
             Errors::PositionBankrupt
         );
 
+        let now = env.ledger().timestamp();
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let collateral_oracle_price_data = &get_oracle_price_with_feeds(
+            &env,
+            &market.collateral.oracle_source,
+            &market.collateral.oracle,
+            &market.collateral.oracle_feeds,
+            (market.collateral.symbol.clone(), symbol_short!("USD")),
+            now,
+            &oracle_guard_rails.validity,
+        )
+        .clone();
+        let debt_oracle_price_data = &get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        )
+        .clone();
+
+        validate_market_oracles_for_action(
+            &env,
+            &market,
+            collateral_oracle_price_data,
+            debt_oracle_price_data,
+            &oracle_guard_rails.validity,
+            NormalAction::Borrow,
+        );
+
         // validate debt ceiling floor
 
         let max_mintable_amount = calculate_calculate_max_mintable_amount(&env, market, &position);
@@ -999,17 +1328,14 @@ impl MarketTrait for SynthMarket {
 
         // update posiiton and market
 
-        let now = env.ledger().timestamp();
         position.update_last_active_slot(now);
 
         token_contract::Client::new(&env, &market.synthetic.token)
             .mint(&env.current_contract_address(), &amount);
 
-        MarketEvents::mint_synthetic(&env);
-
         controller::market_position::provide_liquidity(&env, &market, &position);
 
-        MarketEvents::mint_synthetic(&env);
+        MarketEvents::mint_synthetic(&env, market.name.clone(), sender.clone(), amount);
 
         //     let liquidity_position = get_liquidity_position_by_ts(&env, market.liquidity_position_ts);
 
@@ -1033,7 +1359,135 @@ impl MarketTrait for SynthMarket {
         //     // }
     }
 
-    fn remove_liquidity_and_repay(env: Env, sender: Address, amount: i128) {}
+    fn remove_liquidity_and_repay(env: Env, sender: Address, amount: i128, repay_all: bool) {
+        sender.require_auth();
+        utils::check_nonnegative_amount(amount);
+
+        let mut market = get_market(&env);
+
+        validate!(
+            &env,
+            !market.is_operation_paused(MarketOperation::Repay),
+            Errors::MarketOperationPaused,
+            "Market debt repayment paused"
+        );
+
+        let mut position = get_market_position(&env, &sender);
+
+        validate!(&env, !position.is_bankrupt(), Errors::PositionBankrupt);
+
+        let debt_balance: i128 = position.debt_balance.cast(&env);
+        let repay_amount = if repay_all {
+            debt_balance
+        } else {
+            amount.min(debt_balance)
+        };
+
+        validate!(
+            &env,
+            repay_amount > 0,
+            Errors::InvalidAmount,
+            "Position has no debt to repay"
+        );
+
+        let now = env.ledger().timestamp();
+        let debt_oracle_price_data = get_oracle_price(
+            &env,
+            &market.synthetic.oracle_source,
+            &market.synthetic.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            now,
+        );
+
+        // The share of protocol liquidity removed is proportional to the debt repaid
+        let (lp_shares_removed, collateral_lp_removed) = if repay_amount == debt_balance {
+            (position.lp_shares, position.collateral_lp_balance)
+        } else {
+            (
+                position
+                    .lp_shares
+                    .cast::<i128>(&env)
+                    .safe_mul(repay_amount, &env)
+                    .safe_div(debt_balance, &env)
+                    .cast::<u64>(&env),
+                position
+                    .collateral_lp_balance
+                    .cast::<i128>(&env)
+                    .safe_mul(repay_amount, &env)
+                    .safe_div(debt_balance, &env)
+                    .cast::<u128>(&env),
+            )
+        };
+
+        let (synthetic_released, quote_released) = if lp_shares_removed > 0 {
+            controller::market_position::remove_protocol_liquidity(
+                &env,
+                &mut market,
+                lp_shares_removed.cast(&env),
+                now,
+            )
+        } else {
+            (0, 0)
+        };
+
+        let synthetic_token = token_contract::Client::new(&env, &market.synthetic.token);
+
+        if synthetic_released < repay_amount {
+            synthetic_token.transfer(
+                &sender,
+                &env.current_contract_address(),
+                &repay_amount.safe_sub(synthetic_released, &env),
+            );
+        }
+
+        synthetic_token.burn(&env.current_contract_address(), &repay_amount);
+
+        let synthetic_returned = synthetic_released.saturating_sub(repay_amount).max(0);
+        if synthetic_returned > 0 {
+            synthetic_token.transfer(
+                &env.current_contract_address(),
+                &sender,
+                &synthetic_returned,
+            );
+        }
+
+        // Update position
+        position.debt_balance = position
+            .debt_balance
+            .safe_sub(repay_amount.cast(&env), &env);
+        position.lp_shares = position.lp_shares.safe_sub(lp_shares_removed, &env);
+        position.collateral_lp_balance = position
+            .collateral_lp_balance
+            .safe_sub(collateral_lp_removed, &env);
+        position.collateral_balance = position
+            .collateral_balance
+            .safe_add(quote_released.cast(&env), &env);
+        // Collateral released from the pool is credited back to the position's deposit, so the
+        // market's total deposits grow with it
+        market.collateral.balance = market
+            .collateral
+            .balance
+            .safe_add(quote_released.cast(&env), &env);
+        position.increment_total_burns(
+            &env,
+            repay_amount.cast(&env),
+            debt_oracle_price_data.price,
+            market.get_precision().cast(&env),
+        );
+        position.update_last_active_ts(now);
+
+        save_market_position(&env, &sender, &position);
+        save_market(&env, market.clone());
+
+        MarketEvents::repay_synthetic(
+            &env,
+            market.name,
+            sender,
+            repay_amount,
+            quote_released,
+            synthetic_returned,
+        );
+    }
 
     // ################################################################
     //                             Queries
@@ -1085,6 +1539,47 @@ impl MarketTrait for SynthMarket {
         }
     }
 
+    fn query_price_data(env: Env) -> MarketPriceData {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let market = get_market(&env);
+        let synth_supply = market.get_debt(&env);
+
+        MarketPriceData {
+            synth_token: market.synthetic.token,
+            symbol: market.synthetic.symbol,
+            oracle: market.amm.oracle,
+            oracle_source: market.amm.oracle_source,
+            last_oracle_price_twap: market.amm.historical_oracle_data.last_oracle_price_twap,
+            sqrt_price: market.amm.sqrt_price,
+            last_price_ts: market.amm.last_price_twap_ts.cast(&env),
+            synth_supply,
+            synth_decimals: market.decimals,
+        }
+    }
+
+    fn query_collateral_auctions(env: Env, start_id: u32, limit: u32) -> Vec<CollateralAuction> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let market = get_market(&env);
+
+        get_collateral_auctions(&env, market.next_auction_id, start_id, limit)
+    }
+
+    fn query_collateral_auction(env: Env, auction_id: u32) -> CollateralAuction {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        match get_collateral_auction(&env, auction_id) {
+            Some(auction) => auction,
+            None => panic_with_error!(&env, Errors::AuctionNotFound),
+        }
+    }
+
     // fn migrate_admin_key(env: Env) -> Result<(), ErrorCode> {
     //     let admin = get_admin(&env);
     //     env.storage().instance().set(&ADMIN, &admin);
@@ -1601,10 +2096,35 @@ impl PoolTrait for SynthMarket {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
         let timestamp = env.ledger().timestamp();
-        let market = get_market(&env);
+        let mut market = get_market(&env);
+
+        let oracle_guard_rails = utils::get_oracle_guard_rails(&env);
+        let oracle_price_data = get_oracle_price(
+            &env,
+            &market.amm.oracle_source,
+            &market.amm.oracle,
+            (market.synthetic.symbol.clone(), symbol_short!("USD")),
+            timestamp,
+        );
+        update_amm_and_check_validity(
+            &env,
+            &mut market,
+            &oracle_price_data,
+            &oracle_guard_rails.validity,
+            timestamp,
+            NormalAction::Swap,
+        );
 
         controller::pool::update_pool_price(&env, &market.amm);
 
+        // Callers that don't track ticks (e.g. index tokens) swap through the
+        // arrays around the current tick
+        let tick_array_indexes = if tick_array_indexes.is_empty() {
+            get_start_tick_indexes(&market.amm, a_to_b)
+        } else {
+            tick_array_indexes
+        };
+
         let tick_arrays = tick_array_indexes
             .into_iter()
             .map(|index| {
@@ -1645,7 +2165,10 @@ impl PoolTrait for SynthMarket {
             panic_with_error!(&env, Errors::AmountInAboveMaximum);
         }
 
-        update_and_swap_amm(&env, &market.amm, sender, swap_update, a_to_b, timestamp);
+        update_and_swap_amm(&env, &mut market.amm, sender, swap_update, a_to_b, timestamp);
+
+        // Persist the pool state and the oracle history folded in above
+        save_market(&env, market.clone());
 
         PoolEvents::swap(
             &env,
@@ -1723,34 +2246,33 @@ impl SynthMarket {
     }
 }
 
-// TODO: do we need to update the AMM?
+/// Classifies the AMM oracle reading against its twap, rejects the action if the
+/// oracle is unusable for it, then records the reading in the AMM oracle history
 pub fn update_amm_and_check_validity(
+    env: &Env,
     market: &mut Market,
     oracle_price_data: &OraclePriceData,
-    now: i64,
-    action: Option<NormalAction>,
+    validity_guard_rails: &ValidityGuardRails,
+    now: u64,
+    action: NormalAction,
 ) {
-    // _update_amm(market, oracle_price_data, state, now, clock_slot)?;
-
     // 1 hour EMA
-    // let risk_ema_price = market.amm.historical_oracle_data.last_oracle_price_twap;
-
-    // let oracle_validity = oracle_validity(
-    //     market.name,
-    //     risk_ema_price,
-    //     oracle_price_data,
-    //     oracle_guard_rails().validity, // import from Oracle module
-    //     market.get_max_confidence_interval_multiplier()?,
-    //     false
-    // );
-
-    // validate!(
-    //     is_oracle_valid_for_action(oracle_validity, action)?,
-    //     Errors::InvalidOracle,
-    //     "Invalid Oracle ({:?} vs ema={:?}) for perp market index={} and action={:?}",
-    //     oracle_price_data,
-    //     risk_ema_price,
-    //     market.name,
-    //     action
-    // );
+    let risk_ema_price = market.amm.historical_oracle_data.last_oracle_price_twap;
+
+    let oracle_validity = oracle_validity(
+        env,
+        market.amm.oracle.clone(),
+        risk_ema_price,
+        oracle_price_data,
+        validity_guard_rails,
+        market.get_max_confidence_interval_multiplier(),
+        false,
+    );
+
+    validate_oracle_for_action(env, oracle_validity, action);
+
+    market
+        .amm
+        .historical_oracle_data
+        .update(env, oracle_price_data, now, ONE_HOUR.cast(env));
 }
@@ -0,0 +1,325 @@
+use normal::{
+    constants::{AUCTION_DERIVE_PRICE_FRACTION, PRICE_PRECISION},
+    get_then_update_id,
+    math::{
+        auction::{calculate_auction_price, is_auction_complete},
+        casting::Cast,
+        safe_math::SafeMath,
+    },
+    types::{
+        auction::{Auction, AuctionLocation},
+        misc::OrderDirection,
+    },
+    validate,
+};
+use soroban_sdk::{log, panic_with_error, Address, Env};
+
+use crate::{
+    errors::Errors,
+    events::MarketEvents,
+    math::liquidation::calculate_position_total_collateral_and_margin_requirement,
+    state::{
+        auction::{
+            get_collateral_auction, remove_collateral_auction, save_collateral_auction,
+            CollateralAuction,
+        },
+        market::{Market, MarketOperation},
+        market_position::{get_market_position, save_market_position},
+    },
+    token_contract,
+};
+
+/// Seizes the collateral and debt of a position below maintenance margin into a
+/// Dutch auction.
+///
+/// The auction starts just above the oracle price and decays over
+/// `collateral.auction_config.duration` to a tier-dependent discount.
+pub fn start_collateral_auction(
+    env: &Env,
+    market: &mut Market,
+    collateral_oracle_price: i64,
+    debt_oracle_price: i64,
+    user: &Address,
+    now: u64,
+) -> CollateralAuction {
+    let mut position = get_market_position(env, user);
+
+    validate!(
+        env,
+        !position.is_bankrupt(),
+        Errors::PositionBankrupt,
+        "position bankrupt"
+    );
+
+    validate!(
+        env,
+        !market.is_operation_paused(MarketOperation::Liquidation),
+        Errors::MarketOperationPaused,
+        "Liquidation operation is paused for market {}",
+        market.name
+    );
+
+    validate!(
+        env,
+        position.debt_balance > 0 && position.collateral_balance > 0,
+        Errors::InvalidPosition,
+        "position has no collateral or debt to auction"
+    );
+
+    validate!(
+        env,
+        market.collateral.auction_config.duration > 0,
+        Errors::InvalidAuctionDuration,
+        "collateral auctions need a duration to accept bids"
+    );
+
+    validate!(
+        env,
+        market.collateral.auction_config.location == AuctionLocation::Native,
+        Errors::InvalidAuctionLocation,
+        "collateral auctions are only supported natively"
+    );
+
+    let (total_collateral, margin_requirement) =
+        calculate_position_total_collateral_and_margin_requirement(
+            env,
+            market,
+            &position,
+            collateral_oracle_price,
+            debt_oracle_price,
+            market.collateral.margin_ratio_maintenance,
+        );
+
+    if total_collateral >= margin_requirement.cast(env) {
+        log!(
+            env,
+            "total_collateral {} >= margin_requirement {}",
+            total_collateral,
+            margin_requirement
+        );
+        panic_with_error!(env, Errors::SufficientCollateral);
+    }
+
+    // synthetic tokens per collateral token
+    let oracle_price = collateral_oracle_price
+        .cast::<u128>(env)
+        .safe_mul(PRICE_PRECISION, env)
+        .safe_div(debt_oracle_price.cast(env), env)
+        .cast::<u64>(env);
+
+    let (_, max_divisor) = market.get_auction_end_min_max_divisors();
+    let start_price = oracle_price.safe_add(
+        oracle_price.safe_div(AUCTION_DERIVE_PRICE_FRACTION.cast(env), env),
+        env,
+    );
+    let end_price = oracle_price.safe_sub(oracle_price.safe_div(max_divisor, env), env);
+
+    let collateral = position.collateral_balance;
+    let debt = position.debt_balance;
+
+    let auction = CollateralAuction {
+        id: get_then_update_id!(market, next_auction_id),
+        user: user.clone(),
+        auction: Auction {
+            amount: collateral.cast(env),
+            direction: OrderDirection::Sell,
+            location: AuctionLocation::Native,
+            duration: market.collateral.auction_config.duration,
+            start_ts: now,
+            total_auctioned: 0,
+            start_price,
+            end_price,
+        },
+        debt,
+        debt_repaid: 0,
+    };
+
+    // Collateral and debt now belong to the auction
+    position.collateral_balance = 0;
+    position.debt_balance = 0;
+    position.exit_liquidation();
+    save_market_position(env, user, &position);
+
+    market.collateral.balance = market.collateral.balance.saturating_sub(collateral);
+
+    save_collateral_auction(env, &auction);
+
+    MarketEvents::collateral_auction_start(
+        env,
+        market.name.clone(),
+        auction.id,
+        user.clone(),
+        collateral,
+        debt,
+        start_price,
+        end_price,
+    );
+
+    auction
+}
+
+/// Buys up to `collateral_amount` from an open auction at its current price, paying
+/// with synthetic tokens that are burned to repay the auctioned debt.
+///
+/// The auction settles once the debt is repaid or the collateral sells out. Bids are
+/// rejected once the auction has run its `duration`.
+///
+/// Returns `(collateral_bought, debt_repaid)`
+pub fn bid_collateral_auction(
+    env: &Env,
+    market: &mut Market,
+    auction_id: u32,
+    bidder: &Address,
+    collateral_amount: i128,
+    max_price: u64,
+    now: u64,
+) -> (i128, u128) {
+    let mut auction = match get_collateral_auction(env, auction_id) {
+        Some(auction) => auction,
+        None => {
+            log!(env, "Collateral auction {} not found", auction_id);
+            panic_with_error!(env, Errors::AuctionNotFound);
+        }
+    };
+
+    validate!(
+        env,
+        !is_auction_complete(env, auction.auction.start_ts, auction.auction.duration, now),
+        Errors::AuctionExpired,
+        "collateral auction {} expired",
+        auction_id
+    );
+
+    let price = calculate_auction_price(env, &auction.auction, now, 1);
+
+    validate!(
+        env,
+        price <= max_price,
+        Errors::AuctionPriceAboveLimit,
+        "auction price {} > max price {}",
+        price,
+        max_price
+    );
+
+    let remaining_debt = auction.remaining_debt();
+    let collateral_to_cover_debt = remaining_debt
+        .safe_mul(PRICE_PRECISION, env)
+        .safe_div_ceil(price.cast(env), env)
+        .cast::<i128>(env);
+
+    let collateral_bought = collateral_amount
+        .min(auction.remaining_collateral())
+        .min(collateral_to_cover_debt);
+
+    validate!(
+        env,
+        collateral_bought > 0,
+        Errors::InvalidAmount,
+        "bid does not buy any collateral"
+    );
+
+    let debt_repaid = collateral_bought
+        .cast::<u128>(env)
+        .safe_mul(price.cast(env), env)
+        .safe_div_ceil(PRICE_PRECISION, env)
+        .min(remaining_debt);
+
+    let synthetic_token = token_contract::Client::new(env, &market.synthetic.token);
+    synthetic_token.transfer(
+        bidder,
+        &env.current_contract_address(),
+        &debt_repaid.cast(env),
+    );
+    synthetic_token.burn(&env.current_contract_address(), &debt_repaid.cast(env));
+
+    token_contract::Client::new(env, &market.collateral.token).transfer(
+        &env.current_contract_address(),
+        bidder,
+        &collateral_bought,
+    );
+
+    auction.auction.total_auctioned = auction
+        .auction
+        .total_auctioned
+        .safe_add(collateral_bought, env);
+    auction.debt_repaid = auction.debt_repaid.safe_add(debt_repaid, env);
+    market.synthetic.balance = market.synthetic.balance.saturating_sub(debt_repaid);
+
+    MarketEvents::collateral_auction_bid(
+        env,
+        market.name.clone(),
+        auction.id,
+        bidder.clone(),
+        collateral_bought,
+        debt_repaid,
+        price,
+    );
+
+    if auction.remaining_debt() == 0 || auction.remaining_collateral() == 0 {
+        settle_collateral_auction(env, market, &auction);
+    } else {
+        save_collateral_auction(env, &auction);
+    }
+
+    (collateral_bought, debt_repaid)
+}
+
+/// Closes an auction that ran its `duration` without repaying the debt
+pub fn settle_expired_collateral_auction(
+    env: &Env,
+    market: &mut Market,
+    auction_id: u32,
+    now: u64,
+) -> CollateralAuction {
+    let auction = match get_collateral_auction(env, auction_id) {
+        Some(auction) => auction,
+        None => {
+            log!(env, "Collateral auction {} not found", auction_id);
+            panic_with_error!(env, Errors::AuctionNotFound);
+        }
+    };
+
+    validate!(
+        env,
+        is_auction_complete(env, auction.auction.start_ts, auction.auction.duration, now),
+        Errors::AuctionInProgress,
+        "collateral auction {} still in progress",
+        auction_id
+    );
+
+    settle_collateral_auction(env, market, &auction);
+
+    auction
+}
+
+/// Returns the unsold collateral and the unpaid debt to the position. A position left
+/// with debt but no collateral enters bankruptcy, so the shortfall is covered through
+/// `resolve_position_bankruptcy` (Insurance Fund first, then socialized).
+fn settle_collateral_auction(env: &Env, market: &mut Market, auction: &CollateralAuction) {
+    let collateral_returned = auction.remaining_collateral().cast::<u128>(env);
+    let shortfall = auction.remaining_debt();
+
+    let mut position = get_market_position(env, &auction.user);
+    position.collateral_balance = position
+        .collateral_balance
+        .safe_add(collateral_returned, env);
+    position.debt_balance = position.debt_balance.safe_add(shortfall, env);
+    if position.collateral_balance == 0 && position.debt_balance > 0 {
+        position.enter_bankruptcy();
+    }
+    save_market_position(env, &auction.user, &position);
+
+    market.collateral.balance = market.collateral.balance.safe_add(collateral_returned, env);
+
+    remove_collateral_auction(env, auction.id);
+
+    MarketEvents::collateral_auction_settle(
+        env,
+        market.name.clone(),
+        auction.id,
+        auction.user.clone(),
+        auction.debt_repaid,
+        collateral_returned,
+        shortfall,
+    );
+}
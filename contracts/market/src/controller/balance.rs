@@ -1,5 +1,5 @@
 use normal::{
-    constants::{FIVE_MINUTE, SPOT_MARKET_TOKEN_TWAP_WINDOW},
+    constants::{FIVE_MINUTE, ONE_HOUR, SPOT_MARKET_TOKEN_TWAP_WINDOW},
     math::{casting::Cast, safe_math::SafeMath},
     oracle::OraclePriceData,
     validate,
@@ -98,6 +98,13 @@ pub fn update_market_twap_stats(
     market.amm.historical_oracle_data.last_oracle_delay = debt_oracle_price_data.delay;
     market.amm.historical_oracle_data.last_oracle_price_twap_ts = now;
 
+    market.collateral.historical_oracle_data.update(
+        env,
+        collateral_oracle_price_data,
+        now.cast(env),
+        ONE_HOUR.cast(env),
+    );
+
     market.last_twap_ts = now.cast(env);
 }
 
@@ -0,0 +1,143 @@
+use normal::{
+    math::{casting::Cast, helpers::on_the_hour_update, safe_math::SafeMath},
+    validate,
+};
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+use crate::{
+    errors::Errors, math::bankruptcy::calculate_debt_covered, state::market::Market, token_contract,
+};
+
+/// Sweeps protocol swap fees and liquidation fees owed to the insurance fund.
+///
+/// Quote revenue (swap fees in the quote token plus liquidation fees) is capped at
+/// `max_rev_withdraw_per_period`, with anything above the cap left owed for the next
+/// period. Synthetic swap fees are swept in full.
+///
+/// Returns the (synthetic, quote) amounts sent to the insurance fund
+pub fn settle_revenue_to_insurance_fund(env: &Env, market: &mut Market, now: i64) -> (i128, i128) {
+    let claim = &mut market.insurance_claim;
+
+    validate!(
+        env,
+        claim.revenue_settle_period > 0,
+        Errors::RevenueSettingsCannotSettleToIF,
+        "invalid revenue_settle_period settings on market"
+    );
+
+    let time_until_next_update = on_the_hour_update(
+        env,
+        now,
+        claim.last_revenue_withdraw_ts,
+        claim.revenue_settle_period,
+    );
+
+    validate!(
+        env,
+        time_until_next_update == 0,
+        Errors::RevenueSettingsCannotSettleToIF,
+        "Must wait {} seconds until next available settlement time",
+        time_until_next_update
+    );
+
+    let synthetic_amount = market.amm.protocol_fee_owed_a;
+
+    let quote_owed = market
+        .amm
+        .protocol_fee_owed_b
+        .safe_add(claim.liquidation_fees_owed, env);
+    let quote_amount = if claim.max_rev_withdraw_per_period > 0 {
+        quote_owed.min(claim.max_rev_withdraw_per_period)
+    } else {
+        quote_owed
+    };
+
+    // swap fees are settled before liquidation fees
+    let swap_fees_settled = quote_amount.min(market.amm.protocol_fee_owed_b);
+    let liquidation_fees_settled = quote_amount.safe_sub(swap_fees_settled, env);
+
+    market.amm.protocol_fee_owed_a = 0;
+    market.amm.protocol_fee_owed_b = market
+        .amm
+        .protocol_fee_owed_b
+        .safe_sub(swap_fees_settled, env);
+    claim.liquidation_fees_owed = claim
+        .liquidation_fees_owed
+        .safe_sub(liquidation_fees_settled, env);
+
+    claim.rev_withdraw_since_last_settle = quote_amount.cast(env);
+    claim.last_revenue_withdraw_ts = now;
+
+    let synthetic_amount: i128 = synthetic_amount.cast(env);
+    let quote_amount: i128 = quote_amount.cast(env);
+
+    if synthetic_amount > 0 {
+        deposit_revenue(env, market, &market.synthetic.token, synthetic_amount);
+    }
+    if quote_amount > 0 {
+        deposit_revenue(env, market, &market.collateral.token, quote_amount);
+    }
+
+    (synthetic_amount, quote_amount)
+}
+
+fn deposit_revenue(env: &Env, market: &Market, token: &Address, amount: i128) {
+    // the insurance fund pulls the tokens from the market
+    token_contract::Client::new(env, token).approve(
+        &env.current_contract_address(),
+        &market.insurance,
+        &amount,
+        &env.ledger().sequence(),
+    );
+
+    env.invoke_contract::<()>(
+        &market.insurance,
+        &Symbol::new(env, "deposit_revenue"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            token.into_val(env),
+            amount.into_val(env),
+        ],
+    );
+}
+
+/// Draws up to `amount` collateral tokens from the market's Insurance Fund claim
+pub fn cover_bankruptcy_from_insurance_fund(env: &Env, market: &Market, amount: i128) -> i128 {
+    env.invoke_contract(
+        &market.insurance,
+        &Symbol::new(env, "cover_bankruptcy"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            amount.into_val(env),
+        ],
+    )
+}
+
+/// Books `amount` collateral tokens paid in by the insurance fund (e.g. debt auction
+/// proceeds) against the market's protocol debt. The tokens stay in the market as
+/// collateral backing the previously unbacked synthetic tokens.
+///
+/// Returns the protocol debt repaid
+pub fn repay_protocol_debt(
+    env: &Env,
+    market: &mut Market,
+    amount: i128,
+    collateral_oracle_price: i64,
+    debt_oracle_price: i64,
+) -> u64 {
+    let debt_repaid = calculate_debt_covered(
+        env,
+        amount.cast(env),
+        collateral_oracle_price,
+        debt_oracle_price,
+    )
+    .min(market.protocol_debt.cast(env))
+    .cast::<u64>(env);
+
+    market.protocol_debt = market.protocol_debt.safe_sub(debt_repaid, env);
+    market.collateral.balance = market.collateral.balance.safe_add(amount.cast(env), env);
+
+    debt_repaid
+}
@@ -1,343 +1,367 @@
-use normal::{math::casting::Cast, oracle::is_oracle_too_divergent_with_twap_5min, validate};
+use normal::{
+    constants::{
+        BASE_PRECISION, DEFAULT_LIQUIDATION_MARGIN_BUFFER_RATIO, LIQUIDATION_FEE_PRECISION,
+        PRICE_PRECISION_I64,
+    },
+    math::{casting::Cast, safe_math::SafeMath},
+    validate,
+};
 use soroban_sdk::{log, panic_with_error, Address, Env};
 
 use crate::{
-    contract::update_amm_and_check_validity,
+    controller::insurance::cover_bankruptcy_from_insurance_fund,
     errors::Errors,
+    events::MarketEvents,
     math::{
-        bankruptcy::is_position_bankrupt,
+        bankruptcy::{calculate_bankruptcy_loss, calculate_debt_covered, is_position_bankrupt},
         liquidation::{
-            calculate_base_asset_amount_to_cover_margin_shortage,
-            calculate_position_insurance_fund_fee,
-        },
-        margin::{
-            calculate_margin_requirement_and_total_collateral_and_liability_info,
-            MarginRequirementType,
+            calculate_asset_transfer_for_liability_transfer,
+            calculate_base_asset_amount_to_cover_margin_shortage, calculate_liquidation_multiplier,
+            calculate_position_total_collateral_and_margin_requirement,
+            validate_transfer_satisfies_limit_price, LiquidationMultiplierType,
         },
+        margin::calculate_margin_requirement_and_total_collateral_and_liability_info,
     },
     state::{
         margin_calculation::{MarginCalculation, MarginContext},
-        market::{Market, MarketOperation, MarketStatus},
-        market_position::{get_market_position, MarketPosition},
+        market::{Market, MarketOperation},
+        market_position::{get_market_position, save_market_position, MarketPosition},
     },
+    token_contract,
 };
 
 pub fn liquidate_position(
     env: &Env,
-    market: &Market,
-    position: &MarketOperation,
+    market: &mut Market,
+    collateral_oracle_price: i64,
+    debt_oracle_price: i64,
     liquidator_max_base_asset_amount: u64,
     limit_price: Option<u64>,
     user: &Address,
     liquidator: &Address,
-    now: i64,
+    now: u64,
 ) {
-    // TODO: do we define these per market or at the factory level?
-    let liquidation_margin_buffer_ratio = state.liquidation_margin_buffer_ratio;
-    let initial_pct_to_liquidate = state.initial_pct_to_liquidate as u128;
-    let liquidation_duration = state.liquidation_duration as u128;
+    let liquidation_margin_buffer_ratio = DEFAULT_LIQUIDATION_MARGIN_BUFFER_RATIO;
 
-    let mut position = get_market_position(&env, &user);
+    let mut position = get_market_position(env, user);
 
     validate!(
         env,
         !position.is_bankrupt(),
-        Errors::UserBankrupt,
-        "user bankrupt"
+        Errors::PositionBankrupt,
+        "position bankrupt"
     );
 
     validate!(
         env,
         !market.is_operation_paused(MarketOperation::Liquidation),
-        Errors::InvalidLiquidation,
+        Errors::MarketOperationPaused,
         "Liquidation operation is paused for market {}",
         market.name
     );
 
-    let margin_calculation = calculate_margin_requirement_and_total_collateral_and_liability_info(
+    validate!(
         env,
-        user,
-        MarginContext::liquidation(liquidation_margin_buffer_ratio)
-            .track_market_margin_requirement(MarketIdentifier::perp(market_index))?,
-    )?;
+        position.debt_balance > 0,
+        Errors::InvalidPosition,
+        "position has no debt to liquidate"
+    );
 
-    if !position.is_being_liquidated() && margin_calculation.meets_margin_requirement() {
-        log!(env, "margin calculation: {:?}", margin_calculation);
+    let margin_ratio = market.collateral.margin_ratio_maintenance;
+    let margin_ratio_with_buffer = margin_ratio.safe_add(liquidation_margin_buffer_ratio, env);
+
+    let (total_collateral, margin_requirement) =
+        calculate_position_total_collateral_and_margin_requirement(
+            env,
+            market,
+            &position,
+            collateral_oracle_price,
+            debt_oracle_price,
+            margin_ratio,
+        );
+    let (_, margin_requirement_plus_buffer) =
+        calculate_position_total_collateral_and_margin_requirement(
+            env,
+            market,
+            &position,
+            collateral_oracle_price,
+            debt_oracle_price,
+            margin_ratio_with_buffer,
+        );
+
+    if !position.is_being_liquidated() && total_collateral >= margin_requirement.cast(env) {
+        log!(
+            env,
+            "total_collateral {} >= margin_requirement {}",
+            total_collateral,
+            margin_requirement
+        );
         panic_with_error!(env, Errors::SufficientCollateral);
-    } else if position.is_being_liquidated() && margin_calculation.can_exit_liquidation()? {
+    } else if position.is_being_liquidated()
+        && total_collateral >= margin_requirement_plus_buffer.cast(env)
+    {
         position.exit_liquidation();
+        save_market_position(env, user, &position);
         return;
     }
 
-    let liquidation_id = position.enter_liquidation()?;
-    let mut margin_freed = 0_u64;
-
-    validate!(
-       position.is_open_position()
-        Errors::PositionDoesntHaveOpenPositionOrOrders
-    )?;
-
-    // ...
+    let liquidation_id = position.enter_liquidation(env);
 
-    let oracle_price_data = get_oralce_price_data(&market.amm.oracle)?;
+    let margin_shortage = margin_requirement_plus_buffer
+        .cast::<i128>(env)
+        .safe_sub(total_collateral, env)
+        .unsigned_abs();
 
-    update_amm_and_check_validity(
-        &mut market,
-        oracle_price_data,
-        now,
-        Some(NormalAction::Liquidate),
-    )?;
+    let liquidator_fee = market.liquidator_fee;
+    let if_liquidation_fee = market.if_liquidation_fee;
 
-    let oracle_price = if market.status == MarketStatus::Settlement {
-        market.expiry_price
+    // Debt needed to bring the position back above maintenance plus buffer
+    let base_asset_amount_to_cover_margin_shortage =
+        calculate_base_asset_amount_to_cover_margin_shortage(
+            env,
+            margin_shortage,
+            margin_ratio_with_buffer,
+            liquidator_fee,
+            if_liquidation_fee,
+            debt_oracle_price,
+            PRICE_PRECISION_I64,
+        );
+    let debt_to_cover_margin_shortage = if base_asset_amount_to_cover_margin_shortage == u64::MAX {
+        position.debt_balance
     } else {
-        oracle_price_data.price
+        base_asset_amount_to_cover_margin_shortage
+            .cast::<u128>(env)
+            .safe_mul(market.get_precision().cast(env), env)
+            .safe_div_ceil(BASE_PRECISION, env)
     };
 
-    let oracle_price_too_divergent = is_oracle_too_divergent_with_twap_5min(
+    let debt_transfer = debt_to_cover_margin_shortage
+        .min(liquidator_max_base_asset_amount.cast(env))
+        .min(position.debt_balance);
+
+    validate!(
         env,
-        oracle_price,
-        market.amm.historical_oracle_data.last_oracle_price_twap,
-        state
-            .oracle_guard_rails
-            .max_oracle_twap_5min_percent_divergence()
-            .cast(),
+        debt_transfer > 0,
+        Errors::InvalidLiquidation,
+        "debt_transfer is zero"
     );
 
-    validate!(env, !oracle_price_too_divergent, Errors::PriceBandsBreached)?;
-
-    let user_base_asset_amount = position.cumulative_deposits.unsigned_abs();
+    // Collateral owed for the repaid debt plus the liquidator's fee, capped at the position's balance
+    let liquidator_collateral_transfer = calculate_asset_transfer_for_liability_transfer(
+        env,
+        position.collateral_balance,
+        calculate_liquidation_multiplier(env, liquidator_fee, LiquidationMultiplierType::Premium),
+        market.decimals,
+        collateral_oracle_price,
+        debt_transfer,
+        LIQUIDATION_FEE_PRECISION,
+        market.decimals,
+        debt_oracle_price,
+    )
+    .min(position.collateral_balance);
+
+    let if_collateral_transfer = debt_transfer
+        .safe_mul(debt_oracle_price.cast(env), env)
+        .safe_mul(if_liquidation_fee.cast(env), env)
+        .safe_div(
+            collateral_oracle_price
+                .cast::<u128>(env)
+                .safe_mul(LIQUIDATION_FEE_PRECISION.cast(env), env),
+            env,
+        )
+        .min(
+            position
+                .collateral_balance
+                .safe_sub(liquidator_collateral_transfer, env),
+        );
 
-    let margin_ratio = Market::get_margin_ratio(
-        user_base_asset_amount.cast(env),
-        MarginRequirementType::Maintenance,
-    )?;
+    validate_transfer_satisfies_limit_price(
+        env,
+        liquidator_collateral_transfer,
+        debt_transfer,
+        market.decimals,
+        market.decimals,
+        limit_price,
+    );
 
-    let margin_ratio_with_buffer = margin_ratio.safe_add(liquidation_margin_buffer_ratio)?;
+    // The liquidator repays the debt with their own synthetic tokens
+    let synthetic_token = token_contract::Client::new(env, &market.synthetic.token);
+    synthetic_token.transfer(
+        liquidator,
+        &env.current_contract_address(),
+        &debt_transfer.cast(env),
+    );
+    synthetic_token.burn(&env.current_contract_address(), &debt_transfer.cast(env));
 
-    let margin_shortage = margin_calculation.margin_shortage()?;
+    token_contract::Client::new(env, &market.collateral.token).transfer(
+        &env.current_contract_address(),
+        liquidator,
+        &liquidator_collateral_transfer.cast(env),
+    );
 
-    // ...
+    // Update position
+    position.debt_balance = position.debt_balance.safe_sub(debt_transfer, env);
+    position.collateral_balance = position.collateral_balance.safe_sub(
+        liquidator_collateral_transfer.safe_add(if_collateral_transfer, env),
+        env,
+    );
 
-    let quote_oracle_price = get_oracle_price_data(&market.quote_oracle)?.price;
-    let liquidator_fee = market.liquidator_fee;
-    let if_liquidation_fee = calculate_position_insurance_fund_fee(
+    // Update market
+    market.synthetic.balance = market.synthetic.balance.saturating_sub(debt_transfer);
+    market.collateral.balance = market.collateral.balance.safe_sub(
+        liquidator_collateral_transfer.safe_add(if_collateral_transfer, env),
         env,
-        margin_calculation.tracked_market_margin_shortage(margin_shortage),
-        user_base_asset_amount,
-        margin_ratio_with_buffer,
-        liquidator_fee,
-        oracle_price,
-        quote_oracle_price,
-        market.if_liquidation_fee,
-    )?;
-    let base_asset_amount_to_cover_margin_shortage = standardize_base_asset_amount_ceil(
-        calculate_base_asset_amount_to_cover_margin_shortage(
+    );
+    market.insurance_claim.liquidation_fees_owed = market
+        .insurance_claim
+        .liquidation_fees_owed
+        .safe_add(if_collateral_transfer.cast(env), env);
+
+    let (total_collateral_after, margin_requirement_plus_buffer_after) =
+        calculate_position_total_collateral_and_margin_requirement(
             env,
-            margin_shortage,
+            market,
+            &position,
+            collateral_oracle_price,
+            debt_oracle_price,
             margin_ratio_with_buffer,
-            liquidator_fee,
-            if_liquidation_fee,
-            oracle_price,
-            quote_oracle_price,
-        )?,
-        market.amm.order_step_size, // TODO: is this the tick spacing?
-    )?;
+        );
+    let margin_shortage_after = margin_requirement_plus_buffer_after
+        .cast::<i128>(env)
+        .safe_sub(total_collateral_after, env)
+        .max(0)
+        .unsigned_abs();
+
+    let margin_freed = margin_shortage
+        .saturating_sub(margin_shortage_after)
+        .cast::<u64>(env);
+    position.increment_margin_freed(env, margin_freed);
+
+    if margin_shortage_after == 0 {
+        position.exit_liquidation();
+    } else if position.collateral_balance == 0 && position.debt_balance > 0 {
+        position.enter_bankruptcy();
+    }
 
-    // ...
+    save_market_position(env, user, &position);
+
+    MarketEvents::liquidation(
+        env,
+        market.name.clone(),
+        user.clone(),
+        liquidator.clone(),
+        margin_requirement,
+        total_collateral,
+        margin_freed,
+        liquidation_id,
+    );
 }
 
 pub fn resolve_position_bankruptcy(
     env: &Env,
-    market: &Market,
-    position: &MarketOperation,
+    market: &mut Market,
+    collateral_oracle_price: i64,
+    debt_oracle_price: i64,
+    user: &Address,
     liquidator: &Address,
-    now: i64,
-    insurance_fund_vault_balance: u64,
-) -> u64 {
-    if !position.is_bankrupt() && is_position_bankrupt(position) {
+    now: u64,
+) -> (i128, u128) {
+    let mut position = get_market_position(env, user);
+
+    if !position.is_bankrupt() && is_position_bankrupt(&position) {
         position.enter_bankruptcy();
     }
 
     validate!(
         env,
         position.is_bankrupt(),
-        Errors::UserNotBankrupt,
+        Errors::PositionNotBankrupt,
         "position not bankrupt"
-    )?;
+    );
 
     validate!(
         env,
         !market.is_operation_paused(MarketOperation::Liquidation),
-        Errors::InvalidLiquidation,
+        Errors::MarketOperationPaused,
         "Liquidation operation is paused for market {}",
         market.name
     );
 
-    drop(market);
-
-    // position.get_perp_position(market_index).map_err(|e| {
-    //     log(
-    //         "User does not have a position for perp market {}",
-    //         market_index
-    //     );
-    //     e
-    // })?;
-
-    // let loss = user
-    //     .get_perp_position(market_index)?
-    //     .quote_asset_amount
-    //     .cast::<i128>()?;
-
-    // validate!(
-    //     env,
-    //     loss < 0,
-    //     Errors::InvalidPerpPositionToLiquidate,
-    //     "user must have negative pnl"
-    // );
-
-    // let MarginCalculation {
-    //     margin_requirement,
-    //     total_collateral,
-    //     ..
-    // } = calculate_margin_requirement_and_total_collateral_and_liability_info(
-    //     env,
-    //     position,
-    //     MarginContext::standard(MarginRequirementType::Maintenance),
-    // )?;
-
-    // // spot market's insurance fund draw attempt here (before social loss)
-    // // subtract 1 from available insurance_fund_vault_balance so deposits in insurance vault always remains >= 1
-
-    // let if_payment = {
-    //     let mut perp_market = perp_market_map.get_ref_mut(&market_index)?;
-    //     let max_insurance_withdraw = perp_market
-    //         .insurance_claim
-    //         .quote_max_insurance
-    //         .safe_sub(perp_market.insurance_claim.quote_settled_insurance)?
-    //         .cast::<u128>()?;
-
-    //     let if_payment = loss
-    //         .unsigned_abs()
-    //         .min(insurance_fund_vault_balance.saturating_sub(1).cast()?)
-    //         .min(max_insurance_withdraw);
-
-    //     perp_market.insurance_claim.quote_settled_insurance = perp_market
-    //         .insurance_claim
-    //         .quote_settled_insurance
-    //         .safe_add(if_payment.cast()?)?;
-
-    //     // move if payment to pnl pool
-    //     let spot_market = &mut spot_market_map.get_ref_mut(&QUOTE_SPOT_MARKET_INDEX)?;
-    //     let oracle_price_data = oracle_map.get_price_data(&spot_market.oracle)?;
-    //     update_spot_market_cumulative_interest(spot_market, Some(oracle_price_data), now)?;
-
-    //     update_spot_balances(
-    //         if_payment,
-    //         &BalanceType::Deposit,
-    //         spot_market,
-    //         &mut perp_market.pnl_pool,
-    //         false,
-    //     )?;
-
-    //     if_payment
-    // };
-
-    // let losses_remaining: i128 = loss.safe_add(if_payment.cast::<i128>()?)?;
-    // validate!(
-    //     losses_remaining <= 0,
-    //     Errors::InvalidPerpPositionToLiquidate,
-    //     "losses_remaining must be non-positive"
-    // )?;
-
-    // let fee_pool_payment: i128 = if losses_remaining < 0 {
-    //     let perp_market = &mut perp_market_map.get_ref_mut(&market_index)?;
-    //     let spot_market = &mut spot_market_map.get_ref_mut(&QUOTE_SPOT_MARKET_INDEX)?;
-    //     let fee_pool_tokens = get_fee_pool_tokens(perp_market, spot_market)?;
-    //     log!(env, "fee_pool_tokens={:?}", fee_pool_tokens);
-
-    //     losses_remaining.abs().min(fee_pool_tokens.cast()?)
-    // } else {
-    //     0
-    // };
-    // validate!(
-    //     fee_pool_payment >= 0,
-    //     Errors::InvalidPerpPositionToLiquidate,
-    //     "fee_pool_payment must be non-negative"
-    // )?;
-
-    // if fee_pool_payment > 0 {
-    //     // let perp_market = &mut perp_market_map.get_ref_mut(&market_index)?;
-    //     // let spot_market = &mut spot_market_map.get_ref_mut(&QUOTE_SPOT_MARKET_INDEX)?;
-    //     log!(env, "fee_pool_payment={:?}", fee_pool_payment);
-    //     update_spot_balances(
-    //         fee_pool_payment.unsigned_abs(),
-    //         &BalanceType::Borrow,
-    //         spot_market,
-    //         &mut perp_market.amm.fee_pool,
-    //         false,
-    //     )?;
-    // }
-
-    // let loss_to_socialize = losses_remaining.safe_add(fee_pool_payment.cast::<i128>()?)?;
-    // validate!(
-    //     loss_to_socialize <= 0,
-    //     Errors::InvalidPerpPositionToLiquidate,
-    //     "loss_to_socialize must be non-positive"
-    // )?;
-
-    // // socialize loss
-    // if loss_to_socialize < 0 {
-    //     // let mut market = perp_market_map.get_ref_mut(&market_index)?;
-
-    //     market.amm.total_social_loss = market
-    //         .amm
-    //         .total_social_loss
-    //         .safe_add(loss_to_socialize.unsigned_abs())?;
-    // }
-
-    // // clear bad debt
-    // // {
-    // //     let mut market = perp_market_map.get_ref_mut(&market_index)?;
-    // //     // let position_index = get_position_index(&user.perp_positions, market_index)?;
-    // //     let quote_asset_amount = user.perp_positions[position_index].quote_asset_amount;
-    // //     update_quote_asset_amount(
-    // //         &mut user.perp_positions[position_index],
-    // //         &mut market,
-    // //         -quote_asset_amount,
-    // //     );
-
-    // //     position.increment_total_socialized_loss(quote_asset_amount.unsigned_abs())?;
-    // // }
-
-    // // exit bankruptcy
-    // if !is_position_bankrupt(position) {
-    //     position.exit_bankruptcy();
-    // }
-
-    // let liquidation_id = position.next_liquidation_id.safe_sub(1)?;
-
-    // // emit!(LiquidationRecord {
-    // //     ts: now,
-    // //     liquidation_id,
-    // //     liquidation_type: LiquidationType::PerpBankruptcy,
-    // //     user: *user_key,
-    // //     liquidator: *liquidator_key,
-    // //     margin_requirement,
-    // //     total_collateral,
-    // //     bankrupt: true,
-    // //     perp_bankruptcy: PerpBankruptcyRecord {
-    // //         market_index,
-    // //         if_payment,
-    // //         pnl: loss,
-    // //         clawback_user: None,
-    // //         clawback_user_payment: None,
-    // //         cumulative_funding_rate_delta,
-    // //     },
-    // //     ..LiquidationRecord::default()
-    // // });
-
-    // if_payment.cast()
+    let debt = position.debt_balance;
+
+    // insurance fund draw attempt here (before social loss)
+    let max_insurance_withdraw = market
+        .insurance_claim
+        .quote_max_insurance
+        .saturating_sub(market.insurance_claim.quote_settled_insurance)
+        .cast::<u128>(env);
+
+    let loss = calculate_bankruptcy_loss(env, debt, debt_oracle_price, collateral_oracle_price);
+    let if_request = loss.min(max_insurance_withdraw).cast::<i128>(env);
+
+    let if_payment = if if_request > 0 {
+        cover_bankruptcy_from_insurance_fund(env, market, if_request)
+    } else {
+        0
+    };
+
+    market.insurance_claim.quote_settled_insurance = market
+        .insurance_claim
+        .quote_settled_insurance
+        .safe_add(if_payment.cast(env), env);
+
+    // The payment stays in the market as collateral backing the synthetic tokens that were
+    // minted against the bankrupt position
+    market.collateral.balance = market
+        .collateral
+        .balance
+        .safe_add(if_payment.cast(env), env);
+
+    let debt_covered = calculate_debt_covered(
+        env,
+        if_payment.cast(env),
+        collateral_oracle_price,
+        debt_oracle_price,
+    )
+    .min(debt);
+
+    // socialize loss
+    let loss_to_socialize = debt.safe_sub(debt_covered, env);
+    if loss_to_socialize > 0 {
+        market.protocol_debt = market
+            .protocol_debt
+            .safe_add(loss_to_socialize.cast(env), env);
+        market.total_gov_token_inflation = market.total_gov_token_inflation.safe_add(
+            loss_to_socialize
+                .safe_mul(debt_oracle_price.cast(env), env)
+                .safe_div(market.get_precision().cast(env), env),
+            env,
+        );
+    }
+
+    // clear bad debt
+    market.synthetic.balance = market.synthetic.balance.saturating_sub(debt);
+    position.debt_balance = 0;
+
+    // exit bankruptcy
+    if !is_position_bankrupt(&position) {
+        position.exit_bankruptcy();
+    }
+    position.update_last_active_ts(now);
+
+    save_market_position(env, user, &position);
+
+    MarketEvents::bankruptcy(
+        env,
+        market.name.clone(),
+        user.clone(),
+        liquidator.clone(),
+        debt,
+        if_payment,
+        loss_to_socialize,
+    );
+
+    (if_payment, loss_to_socialize)
 }
 
 pub fn calculate_margin_freed(
@@ -388,6 +412,6 @@ pub fn set_position_status_to_being_liquidated(env: &Env, position: &mut MarketP
         log!(env, "margin calculation: {:?}", margin_calculation);
         panic_with_error!(env, Errors::SufficientCollateral);
     } else {
-        position.enter_liquidation(now);
+        position.enter_liquidation(env);
     }
 }
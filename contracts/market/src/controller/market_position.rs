@@ -1,11 +1,15 @@
 use normal::{math::casting::Cast, safe_decrement, safe_increment, validate};
-use soroban_sdk::Env;
+use soroban_sdk::{panic_with_error, Env};
 
 use crate::{
-    math::{balance::BalanceType, withdraw::check_withdraw_limits},
+    controller,
+    errors::{Errors, PoolErrors},
+    math::{self, balance::BalanceType, withdraw::check_withdraw_limits},
     state::{
+        liquidity_position::{get_liquidity_position_info, save_liquidity_position_info},
         market::{Market, MarketOperation, MarketStatus},
         market_position::MarketPosition,
+        tick::TICK_ARRAY_SIZE,
     },
 };
 
@@ -40,33 +44,51 @@ pub fn update_balances_and_cumulative_deposits(
     }
 }
 
+/// Withdraws `token_amount` from the position's `balance_type` balance and checks the
+/// market's withdraw limits. The withdrawal may not exceed the existing balance, so a
+/// deposit is never flipped into a borrow (or vice versa).
 pub fn update_balances_and_cumulative_deposits_with_limits(
     env: &Env,
-    token_amount: u128,
-    update_direction: &BalanceType,
+    token_amount: i128,
+    balance_type: &BalanceType,
     market: &mut Market,
     position: &mut MarketPosition,
 ) {
-    update_balances_and_cumulative_deposits(
+    let existing_token_amount = position.get_token_amount(env, market).cast::<i128>(env);
+
+    validate!(
         env,
+        position.balance_type == *balance_type && token_amount <= existing_token_amount,
+        Errors::InsufficientBalance,
+        "Withdraw amount {} exceeds existing balance of {}",
         token_amount,
-        update_direction,
+        existing_token_amount
+    );
+
+    let update_direction = match balance_type {
+        BalanceType::Deposit => BalanceType::Borrow,
+        BalanceType::Borrow => BalanceType::Deposit,
+    };
+
+    update_balances_and_cumulative_deposits(
+        env,
+        token_amount.cast::<u128>(env),
+        &update_direction,
         market,
-        &mut position,
+        position,
         true,
         None,
     );
 
-    let valid_withdraw = check_withdraw_limits(env, market, position, Some(token_amount));
+    let valid_withdraw = check_withdraw_limits(env, market, position);
 
     validate!(
         env,
         valid_withdraw,
         Errors::DailyWithdrawLimit,
-        "Market {} has hit daily withdraw limit. Attempted withdraw amount of {} by {}",
+        "Market {} has hit daily withdraw limit. Attempted withdraw amount of {}",
         market.name,
-        token_amount,
-        user.authority
+        token_amount
     );
 
     validate!(
@@ -88,3 +110,91 @@ pub fn update_balances_and_cumulative_deposits_with_limits(
         market.name
     );
 }
+
+/// Removes `liquidity_amount` from the protocol-owned AMM position and returns the
+/// (synthetic, quote) token amounts released by the pool
+pub fn remove_protocol_liquidity(
+    env: &Env,
+    market: &mut Market,
+    liquidity_amount: u128,
+    now: u64,
+) -> (i128, i128) {
+    let protocol = env.current_contract_address();
+    let mut position_info = get_liquidity_position_info(env, &protocol);
+    let position_index = match position_info
+        .positions
+        .iter()
+        .position(|p| p.position_ts == market.lp_ts)
+    {
+        Some(index) => index as u32,
+        None => panic_with_error!(env, Errors::InvalidPosition),
+    };
+    let mut lp_position = position_info.positions.get_unchecked(position_index);
+
+    validate!(
+        env,
+        lp_position.liquidity >= liquidity_amount,
+        PoolErrors::LiquidityUnderflow,
+        "protocol liquidity ({}) < liquidity to remove ({})",
+        lp_position.liquidity,
+        liquidity_amount
+    );
+
+    let ticks_in_array = TICK_ARRAY_SIZE * (market.amm.tick_spacing as i32);
+    let lower_start_index =
+        lp_position.tick_lower_index - lp_position.tick_lower_index.rem_euclid(ticks_in_array);
+    let upper_start_index =
+        lp_position.tick_upper_index - lp_position.tick_upper_index.rem_euclid(ticks_in_array);
+
+    let mut tick_array_lower = match market.amm.tick_arrays.get(lower_start_index) {
+        Some(ta) => ta,
+        None => panic_with_error!(env, PoolErrors::TickArrayIndexOutofBounds),
+    };
+    let mut tick_array_upper = match market.amm.tick_arrays.get(upper_start_index) {
+        Some(ta) => ta,
+        None => panic_with_error!(env, PoolErrors::TickArrayIndexOutofBounds),
+    };
+
+    let liquidity_delta =
+        math::liquidity_math::convert_to_liquidity_delta(env, liquidity_amount, false);
+
+    let update = controller::liquidity::calculate_modify_liquidity(
+        env,
+        &market.amm,
+        &lp_position,
+        &tick_array_lower,
+        &tick_array_upper,
+        liquidity_delta,
+        now,
+    );
+
+    controller::liquidity::sync_modify_liquidity_values(
+        &mut market.amm,
+        &mut lp_position,
+        &mut tick_array_lower,
+        &mut tick_array_upper,
+        update,
+        now,
+    );
+
+    let (delta_a, delta_b) = controller::liquidity::calculate_liquidity_token_deltas(
+        market.amm.tick_current_index,
+        market.amm.sqrt_price,
+        &lp_position,
+        liquidity_delta,
+    );
+
+    market
+        .amm
+        .tick_arrays
+        .set(lower_start_index, tick_array_lower);
+    market
+        .amm
+        .tick_arrays
+        .set(upper_start_index, tick_array_upper);
+
+    position_info.positions.set(position_index, lp_position);
+    save_liquidity_position_info(env, &protocol, &position_info);
+
+    (delta_a, delta_b)
+}
@@ -1,4 +1,6 @@
+pub mod auction;
 pub mod balance;
+pub mod insurance;
 pub mod liquidation;
 pub mod liquidity;
 pub mod liquidity_position;
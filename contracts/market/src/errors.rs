@@ -25,6 +25,22 @@ pub enum Errors {
     PositionIsBeingLiquidated = 17,
     MarketOperationPaused = 18,
     InvalidPosition = 19,
+    DailyWithdrawLimit = 20,
+    MarketWithdrawPaused = 21,
+    MaxDeposit = 22,
+    MaxBorrows = 23,
+    ReduceOnlyWithdrawIncreasedRisk = 24,
+    RevenueSettingsCannotSettleToIF = 25,
+    InvalidLiquidation = 26,
+    SufficientCollateral = 27,
+    LiquidationDoesntSatisfyLimitPrice = 28,
+    PositionNotBankrupt = 29,
+    AuctionNotFound = 30,
+    AuctionPriceAboveLimit = 31,
+    InvalidAuctionLocation = 32,
+    AuctionExpired = 33,
+    AuctionInProgress = 34,
+    InvalidAuctionDuration = 35,
 }
 
 #[contracterror]
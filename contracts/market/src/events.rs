@@ -112,8 +112,53 @@ impl MarketEvents {
             .publish(topics, (user, collateral_type, amount));
     }
 
+    // Debt Events
+
+    /// Emitted when a user mints synthetic tokens and provides them as liquidity
+    ///
+    /// - topics - `["mint_synthetic", market_name: String]`
+    /// - data - `[user: Address, amount: i128]`
+    pub fn mint_synthetic(env: &Env, market_name: String, user: Address, amount: i128) {
+        let topics = (Symbol::new(&env, "mint_synthetic"), market_name);
+        env.events().publish(topics, (user, amount));
+    }
+
+    /// Emitted when a user removes liquidity and burns synthetic tokens to repay debt
+    ///
+    /// - topics - `["repay_synthetic", market_name: String]`
+    /// - data - `[user: Address, amount: i128, quote_returned: i128, synthetic_returned: i128]`
+    pub fn repay_synthetic(
+        env: &Env,
+        market_name: String,
+        user: Address,
+        amount: i128,
+        quote_returned: i128,
+        synthetic_returned: i128,
+    ) {
+        let topics = (Symbol::new(&env, "repay_synthetic"), market_name);
+        env.events()
+            .publish(topics, (user, amount, quote_returned, synthetic_returned));
+    }
+
     // Keeper Events
 
+    /// Emitted when revenue is settled to the insurance fund
+    ///
+    /// - topics - `["revenue_settlement", market_name: String]`
+    /// - data - `[insurance: Address, synthetic_amount: i128, quote_amount: i128, ts: u64]`
+    pub fn revenue_settlement(
+        env: &Env,
+        market_name: String,
+        insurance: Address,
+        synthetic_amount: i128,
+        quote_amount: i128,
+        ts: u64,
+    ) {
+        let topics = (Symbol::new(&env, "revenue_settlement"), market_name);
+        env.events()
+            .publish(topics, (insurance, synthetic_amount, quote_amount, ts));
+    }
+
     /// Emitted when a position is liquidated
     ///
     /// - topics - `["liquidation", market_name: u32]`
@@ -142,19 +187,91 @@ impl MarketEvents {
         );
     }
 
-    /// Emitted when a position is bankrupt
+    /// Emitted when a bankrupt position's debt is cleared
     ///
-    /// - topics - `["bankruptcy", market_name: u32]`
-    /// - data - ()
+    /// - topics - `["bankruptcy", market_name: String]`
+    /// - data - `[user: Address, liquidator: Address, debt: u128, if_payment: i128, socialized_loss: u128]`
     pub fn bankruptcy(
         env: &Env,
         market_name: String,
-        depositor: Address,
-        collateral_type: Address,
-        amount: u64,
+        user: Address,
+        liquidator: Address,
+        debt: u128,
+        if_payment: i128,
+        socialized_loss: u128,
     ) {
         let topics = (Symbol::new(&env, "bankruptcy"), market_name);
-        env.events().publish(topics, ());
+        env.events().publish(
+            topics,
+            (user, liquidator, debt, if_payment, socialized_loss),
+        );
+    }
+
+    /// Emitted when a position's collateral is seized into a Dutch auction
+    ///
+    /// - topics - `["collateral_auction_start", market_name: String]`
+    /// - data - `[auction_id: u32, user: Address, collateral: u128, debt: u128, start_price: u64, end_price: u64]`
+    pub fn collateral_auction_start(
+        env: &Env,
+        market_name: String,
+        auction_id: u32,
+        user: Address,
+        collateral: u128,
+        debt: u128,
+        start_price: u64,
+        end_price: u64,
+    ) {
+        let topics = (Symbol::new(&env, "collateral_auction_start"), market_name);
+        env.events().publish(
+            topics,
+            (auction_id, user, collateral, debt, start_price, end_price),
+        );
+    }
+
+    /// Emitted when collateral is bought from an auction
+    ///
+    /// - topics - `["collateral_auction_bid", market_name: String]`
+    /// - data - `[auction_id: u32, bidder: Address, collateral_bought: i128, debt_repaid: u128, price: u64]`
+    pub fn collateral_auction_bid(
+        env: &Env,
+        market_name: String,
+        auction_id: u32,
+        bidder: Address,
+        collateral_bought: i128,
+        debt_repaid: u128,
+        price: u64,
+    ) {
+        let topics = (Symbol::new(&env, "collateral_auction_bid"), market_name);
+        env.events().publish(
+            topics,
+            (auction_id, bidder, collateral_bought, debt_repaid, price),
+        );
+    }
+
+    /// Emitted when an auction closes
+    ///
+    /// - topics - `["collateral_auction_settle", market_name: String]`
+    /// - data - `[auction_id: u32, user: Address, debt_repaid: u128, collateral_returned: u128, shortfall: u128]`
+    pub fn collateral_auction_settle(
+        env: &Env,
+        market_name: String,
+        auction_id: u32,
+        user: Address,
+        debt_repaid: u128,
+        collateral_returned: u128,
+        shortfall: u128,
+    ) {
+        let topics = (Symbol::new(&env, "collateral_auction_settle"), market_name);
+        env.events().publish(
+            topics,
+            (
+                auction_id,
+                user,
+                debt_repaid,
+                collateral_returned,
+                shortfall,
+            ),
+        );
     }
 }
 
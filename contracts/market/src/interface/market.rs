@@ -1,10 +1,13 @@
 use normal::{
-    oracle::OracleSource,
-    types::market::{MarketInfo, MarketParams, MarketResponse, SynthTier},
+    oracle::{OracleFeed, OracleSource},
+    types::market::{MarketInfo, MarketParams, MarketPriceData, MarketResponse, SynthTier},
 };
 use soroban_sdk::{contractclient, Address, BytesN, Env, String, Vec};
 
-use crate::state::market::{Market, MarketOperation, MarketStatus};
+use crate::state::{
+    auction::CollateralAuction,
+    market::{Market, MarketOperation, MarketStatus},
+};
 
 #[contractclient(name = "MarketClient")]
 pub trait MarketTrait {
@@ -50,6 +53,19 @@ pub trait MarketTrait {
         liquidation_penalty: Option<u32>,
     );
 
+    /// Sets how much quote revenue can be settled to the insurance fund per period
+    /// and how often settlement can happen. A period of 0 disables settlement.
+    fn update_revenue_settle_config(
+        env: Env,
+        sender: Address,
+        max_rev_withdraw_per_period: u64,
+        revenue_settle_period: i64,
+    );
+
+    /// Sets the max amount the market can draw from the Insurance Fund to
+    /// cover bankrupt positions
+    fn update_max_insurance(env: Env, sender: Address, quote_max_insurance: u64);
+
     fn update_name(env: Env, sender: Address, name: String);
 
     fn update_status(env: Env, sender: Address, status: MarketStatus);
@@ -69,19 +85,21 @@ pub trait MarketTrait {
         oracle_source: OracleSource,
     );
 
+    /// Replaces the feeds aggregated with the primary collateral oracle.
+    /// An empty list reads the primary oracle on its own.
+    fn update_collateral_oracle_feeds(env: Env, sender: Address, oracle_feeds: Vec<OracleFeed>);
+
     fn update_collateral_oracle_freeze(env: Env, sender: Address, frozen: bool);
 
     // ################################################################
     //                             Keeper
     // ################################################################
 
-    /// Revenue is settled to 2 places: Normal Insurance and the Governor.
-    /// A portion of revenue is sent to the Normal Buffer is filled to its max balance,
-    /// while any overflow goes to the Insurance Fund.
-    ///
-    /// The remaining majority of revenue is sent to the Governor to be
-    /// distributed to voters/
+    /// Sweeps protocol swap fees and liquidation fees to the Insurance Fund.
     ///
+    /// Can be called once every `insurance_claim.revenue_settle_period` (aligned to
+    /// the hour). Quote revenue is capped at `insurance_claim.max_rev_withdraw_per_period`
+    /// with the remainder carried over to the next period.
     fn settle_revenue(env: Env, sender: Address);
 
     /// Deposits excess/idle collateral to lending/borrowing markets
@@ -96,6 +114,13 @@ pub trait MarketTrait {
     ///
     fn unlend_collateral(env: Env, sender: Address);
 
+    /// Repays part of an undercollateralized position's debt with the liquidator's
+    /// synthetic tokens in exchange for its collateral plus `liquidator_fee`.
+    ///
+    /// Only the debt needed to restore maintenance margin (plus buffer) is repaid,
+    /// capped at `max_base_asset_amount`. `limit_price` is the minimum collateral
+    /// (PRICE_PRECISION) the liquidator accepts per synthetic token repaid.
+    /// The `if_liquidation_fee` share of collateral is owed to the Insurance Fund.
     fn liquidate_position(
         env: Env,
         liquidator: Address,
@@ -104,7 +129,43 @@ pub trait MarketTrait {
         limit_price: Option<u64>,
     );
 
-    fn resolve_position_bankruptcy(env: Env, sender: Address);
+    /// Clears the remaining debt of a position with no collateral left.
+    ///
+    /// The loss is first covered by the Insurance Fund, up to
+    /// `insurance_claim.quote_max_insurance`. The payment is credited to the
+    /// market's collateral and any remainder is socialized by recording it in
+    /// `protocol_debt`.
+    fn resolve_position_bankruptcy(env: Env, sender: Address, user: Address);
+
+    /// Books collateral tokens already paid in by the Insurance Fund (debt auction
+    /// proceeds) against `protocol_debt`. Only callable by the Insurance Fund.
+    fn repay_protocol_debt(env: Env, sender: Address, amount: i128);
+
+    /// Seizes the collateral and debt of a position below maintenance margin into
+    /// a Dutch auction that decays from just above the oracle price to a
+    /// tier-dependent discount over `collateral.auction_config.duration`.
+    ///
+    /// Returns the auction id.
+    fn start_collateral_auction(env: Env, sender: Address, user: Address) -> u32;
+
+    /// Buys up to `collateral_amount` from an auction at its current price, paid
+    /// in synthetic tokens that are burned against the auctioned debt.
+    /// Fails if the current price is above `max_price` (PRICE_PRECISION).
+    ///
+    /// The auction settles once the debt is repaid or the collateral sells out:
+    /// unsold collateral and any unpaid debt go back to the position, which
+    /// enters bankruptcy if it is left with debt but no collateral.
+    fn bid_collateral_auction(
+        env: Env,
+        sender: Address,
+        auction_id: u32,
+        collateral_amount: i128,
+        max_price: u64,
+    );
+
+    /// Settles an auction that ran its full duration without repaying the debt,
+    /// the same way a completed auction settles.
+    fn settle_collateral_auction(env: Env, sender: Address, auction_id: u32);
 
     // ################################################################
     //                             User
@@ -144,7 +205,20 @@ pub trait MarketTrait {
     /// Provide tokens as LP
     fn borrow_and_increase_liquidity(env: Env, sender: Address, amount: i128);
 
-    fn remove_liquidity_and_repay(env: Env, sender: Address, amount: i128);
+    /// Unwinds a position opened with `borrow_and_increase_liquidity`. Removes the
+    /// user's share of the Protocol Pool liquidity position, burns synthetic tokens
+    /// to repay debt and credits the released collateral back to the position.
+    ///
+    /// Released quote is not transferred out. It stays in the market as the
+    /// position's deposited collateral, no longer counted as LP collateral, and can
+    /// be taken out with `withdraw_collateral`.
+    ///
+    /// Any synthetic tokens released beyond the repayment are sent to the user. If the
+    /// pool releases fewer synthetic tokens than owed, the shortfall is taken from the
+    /// user's wallet.
+    ///
+    /// `repay_all` ignores `amount` and repays the full `debt_balance`.
+    fn remove_liquidity_and_repay(env: Env, sender: Address, amount: i128, repay_all: bool);
 
     // ################################################################
     //                             Queries
@@ -164,5 +238,14 @@ pub trait MarketTrait {
 
     fn query_market_info_for_factory(env: Env) -> MarketInfo;
 
+    /// Returns the synthetic's oracle config and pool price, used to value
+    /// index components
+    fn query_price_data(env: Env) -> MarketPriceData;
+
+    /// Returns the open collateral auctions among ids `[start_id, start_id + limit)`
+    fn query_collateral_auctions(env: Env, start_id: u32, limit: u32) -> Vec<CollateralAuction>;
+
+    fn query_collateral_auction(env: Env, auction_id: u32) -> CollateralAuction;
+
     // fn migrate_admin_key(env: Env) -> Result<(), ErrorCode>;
 }
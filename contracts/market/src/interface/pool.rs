@@ -88,6 +88,9 @@ pub trait PoolTrait {
         tick_array_upper_index: i32,
     );
 
+    /// Swaps through the pool. An empty `tick_array_indexes` uses the current
+    /// tick array and the next two in the swap direction, and a
+    /// `sqrt_price_limit` of 0 applies no price limit.
     fn swap(
         env: Env,
         sender: Address,
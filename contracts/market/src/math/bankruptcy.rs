@@ -1,21 +1,40 @@
+use normal::math::{casting::Cast, safe_math::SafeMath};
+use soroban_sdk::Env;
+
 use crate::state::market_position::MarketPosition;
 
 pub fn is_position_bankrupt(position: &MarketPosition) -> bool {
-    // user is bankrupt iff they have spot liabilities, no spot assets, and no perp exposure
-
-    let mut has_liability = false;
+    // position is bankrupt iff it has debt and no collateral left to liquidate
 
-    if position.base_asset_amount != 0
-        || position.quote_asset_amount > 0
-        || position.has_open_order()
-        || position.is_lp()
+    if position.collateral_balance != 0
+        || position.collateral_lp_balance != 0
+        || position.lp_shares != 0
     {
         return false;
     }
 
-    if position.quote_asset_amount < 0 {
-        has_liability = true;
-    }
+    position.debt_balance > 0
+}
+
+/// Collateral tokens needed to cover `debt` at oracle prices
+pub fn calculate_bankruptcy_loss(
+    env: &Env,
+    debt: u128,
+    debt_price: i64,
+    collateral_price: i64,
+) -> u128 {
+    debt.safe_mul(debt_price.cast(env), env)
+        .safe_div_ceil(collateral_price.cast(env), env)
+}
 
-    has_liability
+/// Synthetic debt covered by `collateral_amount` at oracle prices
+pub fn calculate_debt_covered(
+    env: &Env,
+    collateral_amount: u128,
+    collateral_price: i64,
+    debt_price: i64,
+) -> u128 {
+    collateral_amount
+        .safe_mul(collateral_price.cast(env), env)
+        .safe_div(debt_price.cast(env), env)
 }
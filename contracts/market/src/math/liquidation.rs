@@ -5,8 +5,8 @@ use crate::state::market_position::MarketPosition;
 use normal::constants::{
     BASE_PRECISION, LIQUIDATION_FEE_INCREASE_PER_SLOT, LIQUIDATION_FEE_PRECISION,
     LIQUIDATION_FEE_PRECISION_U128, LIQUIDATION_FEE_TO_MARGIN_PRECISION_RATIO,
-    LIQUIDATION_PCT_PRECISION, PRICE_PRECISION, PRICE_TIMES_AMM_TO_QUOTE_PRECISION_RATIO,
-    QUOTE_PRECISION, SPOT_WEIGHT_PRECISION_U128,
+    LIQUIDATION_PCT_PRECISION, MARGIN_PRECISION_U128, PRICE_PRECISION,
+    PRICE_TIMES_AMM_TO_QUOTE_PRECISION_RATIO, QUOTE_PRECISION, SPOT_WEIGHT_PRECISION_U128,
 };
 use normal::error::NormalResult;
 use normal::math::casting::Cast;
@@ -286,13 +286,13 @@ pub fn validate_transfer_satisfies_limit_price(
         }
     };
 
-    let swap_price = 1;
-    // let swap_price = calculate_swap_price(
-    //     asset_transfer,
-    //     liability_transfer,
-    //     asset_decimals,
-    //     liability_decimals
-    // );
+    let swap_price = calculate_swap_price(
+        env,
+        asset_transfer,
+        liability_transfer,
+        asset_decimals,
+        liability_decimals,
+    );
 
     validate!(
         env,
@@ -304,6 +304,58 @@ pub fn validate_transfer_satisfies_limit_price(
     )
 }
 
+/// Price paid for the liability in units of the asset
+/// precision: PRICE_PRECISION
+pub fn calculate_swap_price(
+    env: &Env,
+    asset_amount: u128,
+    liability_amount: u128,
+    asset_decimals: u32,
+    liability_decimals: u32,
+) -> u128 {
+    asset_amount
+        .safe_mul(PRICE_PRECISION, env)
+        .safe_mul(10_u128.pow(liability_decimals), env)
+        .safe_div(
+            liability_amount.safe_mul(10_u128.pow(asset_decimals), env),
+            env,
+        )
+}
+
+/// Values a position's collateral and debt at the given oracle prices
+///
+/// Returns `(total_collateral, margin_requirement)` where total collateral is the
+/// collateral value net of debt and the requirement is the debt value scaled by `margin_ratio`
+/// precision: QUOTE_PRECISION
+pub fn calculate_position_total_collateral_and_margin_requirement(
+    env: &Env,
+    market: &Market,
+    position: &MarketPosition,
+    collateral_price: i64,
+    debt_price: i64,
+    margin_ratio: u32,
+) -> (i128, u128) {
+    let precision = market.get_precision().cast::<u128>(env);
+
+    let collateral_value = position
+        .collateral_balance
+        .safe_mul(collateral_price.cast(env), env)
+        .safe_div(precision, env);
+    let debt_value = position
+        .debt_balance
+        .safe_mul(debt_price.cast(env), env)
+        .safe_div(precision, env);
+
+    let total_collateral = collateral_value
+        .cast::<i128>(env)
+        .safe_sub(debt_value.cast(env), env);
+    let margin_requirement = debt_value
+        .safe_mul(margin_ratio.cast(env), env)
+        .safe_div(MARGIN_PRECISION_U128, env);
+
+    (total_collateral, margin_requirement)
+}
+
 pub fn calculate_max_pct_to_liquidate(
     env: &Env,
     position: &MarketPosition,
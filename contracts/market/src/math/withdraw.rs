@@ -1,39 +1,46 @@
-// pub fn calculate_min_deposit_token_amount(
-//     deposit_token_twap: u128,
-//     withdraw_guard_threshold: u128,
-// ) -> DriftResult<u128> {
-//     // minimum required deposit amount after withdrawal
-//     // minimum deposit amount lower of 75% of TWAP or withdrawal guard threshold below TWAP
-//     // for high withdrawal guard threshold, minimum deposit amount is 0
+use normal::math::{casting::Cast, safe_math::SafeMath};
+use soroban_sdk::Env;
 
-//     let min_deposit_token = deposit_token_twap
-//         .safe_sub((deposit_token_twap / 4).max(withdraw_guard_threshold.min(deposit_token_twap)))?;
+use crate::state::{market::Market, market_position::MarketPosition};
 
-//     Ok(min_deposit_token)
-// }
+use super::balance::{get_token_amount, BalanceType};
+
+pub fn calculate_min_deposit_token_amount(
+    env: &Env,
+    deposit_token_twap: u128,
+    withdraw_guard_threshold: u128,
+) -> u128 {
+    // minimum required deposit amount after withdrawal
+    // minimum deposit amount lower of 75% of TWAP or withdrawal guard threshold below TWAP
+    // for high withdrawal guard threshold, minimum deposit amount is 0
+
+    deposit_token_twap.safe_sub(
+        (deposit_token_twap / 4).max(withdraw_guard_threshold.min(deposit_token_twap)),
+        env,
+    )
+}
 
 pub fn calculate_max_borrow_token_amount(
+    env: &Env,
     deposit_token_amount: u128,
     deposit_token_twap: u128,
     borrow_token_twap: u128,
     withdraw_guard_threshold: u128,
     max_token_borrows: u128,
-) -> DriftResult<u128> {
+) -> u128 {
     // maximum permitted borrows after withdrawal
     // allows at least up to the withdraw_guard_threshold
     // and between ~15-80% utilization with friction on twap in 10% increments
 
     let lesser_deposit_amount = deposit_token_amount.min(deposit_token_twap);
 
-    let max_borrow_token = withdraw_guard_threshold
+    withdraw_guard_threshold
         .max(
             (lesser_deposit_amount / 6)
-                .max(borrow_token_twap.safe_add(lesser_deposit_amount / 10)?)
-                .min(lesser_deposit_amount.safe_sub(lesser_deposit_amount / 5)?),
+                .max(borrow_token_twap.safe_add(lesser_deposit_amount / 10, env))
+                .min(lesser_deposit_amount.safe_sub(lesser_deposit_amount / 5, env)),
         )
-        .min(max_token_borrows);
-
-    Ok(max_borrow_token)
+        .min(max_token_borrows)
 }
 
 // pub fn calculate_token_utilization_limits(
@@ -74,20 +81,9 @@ pub fn calculate_max_borrow_token_amount(
 //     ))
 // }
 
-use soroban_sdk::Env;
-
-use crate::state::{market::Market, market_position::MarketPosition};
-
-use super::balance::{get_token_amount, BalanceType};
-
-pub fn check_withdraw_limits(
-    env: &Env,
-    market: &Market,
-    position: &MarketPosition,
-    token_amount_withdrawn: Option<u128>,
-) -> bool {
+pub fn check_withdraw_limits(env: &Env, market: &Market, position: &MarketPosition) -> bool {
     // calculates min/max deposit/borrow amounts permitted for immediate withdraw
-    // takes the stricter of absolute caps on level changes and utilization changes vs 24hr moving averrages
+    // takes the stricter of absolute caps on level changes vs 24hr moving averages
     let deposit_token_amount = get_token_amount(
         env,
         market.collateral.balance,
@@ -97,49 +93,46 @@ pub fn check_withdraw_limits(
     let borrow_token_amount =
         get_token_amount(env, market.synthetic.balance, market, &BalanceType::Borrow);
 
-    let max_token_borrows: u128 = if market.max_token_borrows_fraction > 0 {
+    let withdraw_guard_threshold: u128 = market.collateral.withdraw_guard_threshold.cast(env);
+
+    // dont block withdraws for deposit sizes below guard threshold
+    if deposit_token_amount <= withdraw_guard_threshold {
+        return true;
+    }
+
+    let max_token_borrows: u128 = if market.collateral.max_token_borrows_fraction > 0 {
         market
+            .collateral
             .max_token_deposits
-            .safe_mul(market.max_token_borrows_fraction.cast())
-            .safe_div(10000)
-            .cast()
+            .safe_mul(market.collateral.max_token_borrows_fraction.cast(env), env)
+            .safe_div(10000, env)
+            .cast(env)
     } else {
         u128::MAX
     };
 
-    let max_borrow_token_for_twap = calculate_max_borrow_token_amount(
+    let max_borrow_token = calculate_max_borrow_token_amount(
+        env,
         deposit_token_amount,
-        market.deposit_token_twap.cast(),
-        market.borrow_token_twap.cast(),
-        market.withdraw_guard_threshold.cast(),
+        market.collateral.token_twap.cast(env),
+        market.synthetic.token_twap.cast(env),
+        withdraw_guard_threshold,
         max_token_borrows,
     );
 
-    let (min_deposit_token_for_utilization, max_borrow_token_for_utilization) =
-        calculate_token_utilization_limits(deposit_token_amount, borrow_token_amount, market);
-
-    let max_borrow_token = max_borrow_token_for_twap.min(max_borrow_token_for_utilization);
-
-    let min_deposit_token_for_twap = calculate_min_deposit_token_amount(
-        market.deposit_token_twap.cast(),
-        market.withdraw_guard_threshold.cast(),
+    let min_deposit_token = calculate_min_deposit_token_amount(
+        env,
+        market.collateral.token_twap.cast(env),
+        withdraw_guard_threshold,
     );
 
-    let min_deposit_token = min_deposit_token_for_twap.max(min_deposit_token_for_utilization);
-
     // for resulting deposit or ZERO, check if deposits above minimum
     // for resulting borrow, check both deposit and borrow constraints
-    let valid_global_withdrawal = if let Some(user) = user {
-        if position.balance_type == &BalanceType::Borrow {
-            borrow_token_amount <= max_borrow_token && deposit_token_amount >= min_deposit_token
-        } else {
-            deposit_token_amount >= min_deposit_token
-        }
+    if position.balance_type == BalanceType::Borrow {
+        borrow_token_amount <= max_borrow_token && deposit_token_amount >= min_deposit_token
     } else {
-        deposit_token_amount >= min_deposit_token && borrow_token_amount <= max_borrow_token
-    };
-
-    valid_global_withdrawal
+        deposit_token_amount >= min_deposit_token
+    }
 }
 
 // pub fn get_max_withdraw_for_market_with_token_amount(
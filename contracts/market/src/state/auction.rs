@@ -0,0 +1,89 @@
+use normal::{
+    constants::{PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD},
+    types::auction::Auction,
+};
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Dutch auction selling collateral seized from an undercollateralized position
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollateralAuction {
+    pub id: u32,
+    /// The position the collateral was seized from
+    pub user: Address,
+    /// Collateral lot and price curve. `amount` is the collateral for sale and
+    /// `total_auctioned` the collateral sold so far
+    /// price precision: PRICE_PRECISION (synthetic tokens per collateral token)
+    pub auction: Auction,
+    /// The synthetic debt the auction needs to raise
+    /// precision: token mint precision
+    pub debt: u128,
+    /// The synthetic debt repaid by bids so far
+    /// precision: token mint precision
+    pub debt_repaid: u128,
+}
+
+impl CollateralAuction {
+    pub fn remaining_collateral(&self) -> i128 {
+        self.auction.amount - self.auction.total_auctioned
+    }
+
+    pub fn remaining_debt(&self) -> u128 {
+        self.debt - self.debt_repaid
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CollateralAuctionKey {
+    Auction(u32),
+}
+
+pub fn get_collateral_auction(env: &Env, id: u32) -> Option<CollateralAuction> {
+    let key = CollateralAuctionKey::Auction(id);
+    let auction = env.storage().persistent().get::<_, CollateralAuction>(&key);
+    auction.is_some().then(|| {
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    });
+
+    auction
+}
+
+/// Returns the open auctions among ids `[start_id, start_id + limit)`
+pub fn get_collateral_auctions(
+    env: &Env,
+    next_auction_id: u32,
+    start_id: u32,
+    limit: u32,
+) -> Vec<CollateralAuction> {
+    let end_id = start_id.saturating_add(limit).min(next_auction_id);
+
+    let mut auctions = Vec::new(env);
+    for id in start_id..end_id {
+        if let Some(auction) = get_collateral_auction(env, id) {
+            auctions.push_back(auction);
+        }
+    }
+
+    auctions
+}
+
+pub fn save_collateral_auction(env: &Env, auction: &CollateralAuction) {
+    let key = CollateralAuctionKey::Auction(auction.id);
+    env.storage().persistent().set(&key, auction);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+pub fn remove_collateral_auction(env: &Env, id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&CollateralAuctionKey::Auction(id));
+}
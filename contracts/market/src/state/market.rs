@@ -5,7 +5,7 @@ use normal::{
         PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD,
     },
     math::{casting::Cast, safe_math::SafeMath},
-    oracle::OracleSource,
+    oracle::{HistoricalOracleData, OracleFeed, OracleSource},
     types::{auction::Auction, market::SynthTier},
     validate,
 };
@@ -96,6 +96,11 @@ pub struct InsuranceClaim {
     pub quote_settled_insurance: u64,
     /// The last time revenue was settled in/out of market
     pub last_revenue_withdraw_ts: i64,
+    /// How often revenue can be settled to the insurance fund. 0 disables settlement
+    pub revenue_settle_period: i64,
+    /// Liquidation fees owed to the insurance fund
+    /// precision: token mint precision
+    pub liquidation_fees_owed: u64,
 }
 
 #[contracttype]
@@ -106,7 +111,11 @@ pub struct Collateral {
     pub oracle: Address,
     /// the oracle provider information. used to decode/scale the oracle data
     pub oracle_source: OracleSource,
+    /// additional feeds aggregated with `oracle`. empty to read `oracle` on its own
+    pub oracle_feeds: Vec<OracleFeed>,
     pub oracle_frozen: bool,
+    /// stores historically witnessed collateral oracle data
+    pub historical_oracle_data: HistoricalOracleData,
     /// The sum of the balances for collateral deposits across users
     /// precision: SPOT_BALANCE_PRECISION
     pub balance: u128,
@@ -195,6 +204,8 @@ pub struct Market {
     pub next_deposit_record_id: u64,
     /// The next liquidation id to be used for user
     pub next_liquidation_id: u32,
+    /// The next collateral auction id to be used
+    pub next_auction_id: u32,
 
     /// The initial asset weight used to calculate a deposits contribution to a users initial total collateral
     /// e.g. if the asset weight is .8, $100 of deposits contributes $80 to the users initial total collateral
@@ -244,7 +255,7 @@ pub struct Market {
 impl Market {
     // spot
 
-    pub fn get_precision(self) -> u64 {
+    pub fn get_precision(&self) -> u64 {
         (10_u64).pow(self.decimals)
     }
 
@@ -267,7 +278,7 @@ impl Market {
         MarketOperation::is_operation_paused(self.paused_operations, operation)
     }
 
-    pub fn get_max_confidence_interval_multiplier(self) -> u64 {
+    pub fn get_max_confidence_interval_multiplier(&self) -> u64 {
         // assuming validity_guard_rails max confidence pct is 2%
         match self.synthetic.tier {
             SynthTier::A => 1,                  // 2%
@@ -290,7 +301,7 @@ impl Market {
         }
     }
 
-    pub fn get_auction_end_min_max_divisors(self) -> (u64, u64) {
+    pub fn get_auction_end_min_max_divisors(&self) -> (u64, u64) {
         match self.synthetic.tier {
             SynthTier::A => (1000, 50),              // 10 bps, 2%
             SynthTier::B => (1000, 20),              // 10 bps, 5%
@@ -353,14 +364,17 @@ impl Market {
             deposits
         );
 
-        if do_max_borrow_check && self.max_token_borrows_fraction > 0 && self.max_token_deposits > 0
+        if do_max_borrow_check
+            && self.collateral.max_token_borrows_fraction > 0
+            && self.collateral.max_token_deposits > 0
         {
             let borrows = self.get_debt(env);
             let max_token_borrows = self
+                .collateral
                 .max_token_deposits
-                .safe_mul(self.max_token_borrows_fraction.cast()?)?
-                .safe_div(10000)?
-                .cast::<u128>()?;
+                .safe_mul(self.collateral.max_token_borrows_fraction.cast(env), env)
+                .safe_div(10000, env)
+                .cast::<u128>(env);
 
             validate!(
                 env,
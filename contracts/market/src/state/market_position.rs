@@ -1,6 +1,7 @@
 use normal::{
     constants::{PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD},
     error::NormalResult,
+    get_then_update_id,
     math::{casting::Cast, safe_math::SafeMath},
     validate,
 };
@@ -70,6 +71,8 @@ pub struct MarketPosition {
     pub liquidation_margin_freed: u128,
     /// Custom max initial margin ratio for the user
     pub max_margin_ratio: u32,
+    /// The next liquidation id to be used for the position
+    pub next_liquidation_id: u32,
 
     /// The last slot a user was active. Used to determine if a user is idle
     pub last_active_ts: u64,
@@ -166,7 +169,16 @@ impl MarketPosition {
         self.total_withdraws = self.total_withdraws.saturating_add(value);
     }
 
-    pub fn enter_liquidation(&mut self) -> u32 {
+    pub fn increment_total_burns(&mut self, env: &Env, amount: u64, price: i64, precision: u128) {
+        let value = amount
+            .cast::<u128>(env)
+            .safe_mul(price.cast(env), env)
+            .safe_div(precision, env)
+            .cast::<u64>(env);
+        self.total_burns = self.total_burns.saturating_add(value);
+    }
+
+    pub fn enter_liquidation(&mut self, env: &Env) -> u32 {
         if self.is_being_liquidated() {
             return self.next_liquidation_id.safe_sub(1, env);
         }
@@ -193,8 +205,10 @@ impl MarketPosition {
         self.liquidation_margin_freed = 0;
     }
 
-    pub fn increment_margin_freed(&mut self, margin_free: u64) {
-        self.liquidation_margin_freed = self.liquidation_margin_freed.safe_add(margin_free, env);
+    pub fn increment_margin_freed(&mut self, env: &Env, margin_free: u64) {
+        self.liquidation_margin_freed = self
+            .liquidation_margin_freed
+            .safe_add(margin_free.cast(env), env);
     }
 
     pub fn update_last_active_ts(&mut self, ts: u64) {
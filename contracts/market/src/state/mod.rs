@@ -1,3 +1,4 @@
+pub mod auction;
 pub mod liquidity_position;
 pub mod margin_calculation;
 pub mod market;
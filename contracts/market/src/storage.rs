@@ -19,7 +19,7 @@ impl TryFromVal<Env, DataKey> for Val {
 }
 
 pub mod utils {
-    use normal::{error::ErrorCode, types::market::MarketFactoryConfig};
+    use normal::{error::ErrorCode, oracle::OracleGuardRails, types::market::MarketFactoryConfig};
     use soroban_sdk::{log, panic_with_error, String, Symbol, Vec};
 
     use crate::{errors::Errors, token_contract};
@@ -68,6 +68,16 @@ pub mod utils {
         }
     }
 
+    pub fn get_oracle_guard_rails(env: &Env) -> OracleGuardRails {
+        let factory_config: MarketFactoryConfig = env.invoke_contract(
+            &get_factory(env),
+            &Symbol::new(env, "query_config"),
+            Vec::new(env),
+        );
+
+        factory_config.oracle_guard_rails
+    }
+
     pub fn get_balance(env: &Env, contract: &Address) -> i128 {
         token_contract::Client::new(env, contract).balance(&env.current_contract_address())
     }
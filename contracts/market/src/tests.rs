@@ -1,4 +1,7 @@
+mod auction;
+mod collateral;
 mod config;
+mod liquidation;
 mod liquidity;
 mod setup;
 mod swap;
@@ -0,0 +1,223 @@
+extern crate std;
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+use super::setup::{
+    deploy_market_contract, deploy_token_contract, get_position, open_position,
+    set_collateral_price,
+};
+use crate::contract::SynthMarketClient;
+use crate::state::market::{get_market, save_market};
+
+fn set_auction_duration(env: &Env, market: &SynthMarketClient, duration: u64) {
+    env.as_contract(&market.address, || {
+        let mut market = get_market(env);
+        market.collateral.auction_config.duration = duration;
+        save_market(env, market);
+    });
+}
+
+#[test]
+fn collateral_auction_repays_debt_and_returns_leftover_collateral() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let bidder = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    set_auction_duration(&env, &market, 1_000);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&bidder, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    let auction_id = market.start_collateral_auction(&keeper, &user);
+
+    // starts 0.5% above the 0.9 oracle price and decays to a 2% discount (tier A)
+    let auction = market.query_collateral_auction(&auction_id);
+    assert_eq!(auction.auction.amount, 1_000_000);
+    assert_eq!(auction.auction.start_price, 904_500);
+    assert_eq!(auction.auction.end_price, 882_000);
+    assert_eq!(auction.debt, 800_000);
+    assert_eq!(market.query_collateral_auctions(&0, &10).len(), 1);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.collateral_balance, 0);
+    assert_eq!(position.debt_balance, 0);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 500;
+    });
+
+    // halfway through the auction the price is 0.89325
+    market.bid_collateral_auction(&bidder, &auction_id, &2_000_000, &900_000);
+
+    assert_eq!(collateral_token.balance(&bidder), 895_606);
+    assert_eq!(synth_token.balance(&bidder), 200_000);
+
+    // debt is repaid in full so the rest of the collateral goes back to the position
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.collateral_balance, 1_000_000 - 895_606);
+    assert_eq!(market.query_collateral_auctions(&0, &10).len(), 0);
+    assert_eq!(market.query_market().protocol_debt, 0);
+}
+
+#[test]
+fn collateral_auction_shortfall_becomes_protocol_debt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let bidder = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&bidder, &1_000_000);
+
+    set_auction_duration(&env, &market, 1_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 5_000_000);
+
+    let auction_id = market.start_collateral_auction(&keeper, &user);
+
+    // at the end of the auction the lot sells at the end price
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1_000;
+    });
+
+    market.bid_collateral_auction(&bidder, &auction_id, &1_000_000, &490_000);
+
+    assert_eq!(collateral_token.balance(&bidder), 1_000_000);
+    assert_eq!(synth_token.balance(&bidder), 510_000);
+    assert_eq!(market.query_collateral_auctions(&0, &10).len(), 0);
+
+    // the unpaid debt goes back to the position, which is now bankrupt
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.collateral_balance, 0);
+    assert_eq!(position.debt_balance, 310_000);
+    assert!(position.is_bankrupt());
+
+    // without an insurance claim the shortfall is socialized
+    market.resolve_position_bankruptcy(&keeper, &user);
+
+    assert_eq!(market.query_market().protocol_debt, 310_000);
+    assert_eq!(get_position(&env, &market, &user).debt_balance, 0);
+}
+
+#[test]
+fn expired_collateral_auction_returns_lot_to_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let keeper = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    set_auction_duration(&env, &market, 1_000);
+
+    collateral_token.mint(&user, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    let auction_id = market.start_collateral_auction(&keeper, &user);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1_001;
+    });
+
+    market.settle_collateral_auction(&keeper, &auction_id);
+
+    assert_eq!(market.query_collateral_auctions(&0, &10).len(), 0);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.collateral_balance, 1_000_000);
+    assert_eq!(position.debt_balance, 800_000);
+    assert_eq!(market.query_market().collateral.balance, 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #33)")]
+fn collateral_auction_bid_fails_after_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let bidder = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    set_auction_duration(&env, &market, 1_000);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&bidder, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    let auction_id = market.start_collateral_auction(&keeper, &user);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1_001;
+    });
+
+    market.bid_collateral_auction(&bidder, &auction_id, &1_000_000, &900_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #31)")]
+fn collateral_auction_bid_fails_above_max_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let bidder = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    set_auction_duration(&env, &market, 1_000);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&bidder, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    let auction_id = market.start_collateral_auction(&keeper, &user);
+
+    market.bid_collateral_auction(&bidder, &auction_id, &1_000_000, &800_000);
+}
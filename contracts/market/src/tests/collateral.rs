@@ -0,0 +1,164 @@
+extern crate std;
+
+use soroban_sdk::{testutils::Address as _, vec, Address, Env};
+
+use super::setup::{deploy_market_contract, deploy_token_contract, get_position, open_position};
+use crate::state::market::MarketOperation;
+
+#[test]
+fn withdraw_collateral_returns_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    market.deposit_collateral(&user, &1_000_000, &false);
+
+    assert_eq!(collateral_token.balance(&user), 0);
+    assert_eq!(collateral_token.balance(&market.address), 1_000_000);
+
+    market.withdraw_collateral(&user, &400_000, &false);
+
+    assert_eq!(collateral_token.balance(&user), 400_000);
+    assert_eq!(collateral_token.balance(&market.address), 600_000);
+    assert_eq!(market.query_market().collateral.balance, 600_000);
+
+    // remaining position is persisted and can be fully withdrawn
+    market.withdraw_collateral(&user, &600_000, &false);
+
+    assert_eq!(collateral_token.balance(&user), 1_000_000);
+    assert_eq!(collateral_token.balance(&market.address), 0);
+    assert_eq!(market.query_market().collateral.balance, 0);
+}
+
+#[test]
+fn withdraw_collateral_reduce_only_caps_at_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    market.deposit_collateral(&user, &500_000, &false);
+
+    market.withdraw_collateral(&user, &900_000, &true);
+
+    assert_eq!(collateral_token.balance(&user), 1_000_000);
+    assert_eq!(collateral_token.balance(&market.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn withdraw_collateral_fails_when_paused() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    market.deposit_collateral(&user, &1_000_000, &false);
+
+    market.update_paused_operations(&admin, &vec![&env, MarketOperation::Withdraw]);
+
+    market.withdraw_collateral(&user, &1_000_000, &false);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn withdraw_collateral_fails_above_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    market.deposit_collateral(&user, &500_000, &false);
+
+    // a withdrawal may not flip the deposit into a borrow
+    market.withdraw_collateral(&user, &900_000, &false);
+}
+
+#[test]
+fn remove_liquidity_and_repay_keeps_market_collateral_in_sync() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    open_position(&env, &market, &user, 1_000_000, 200_000);
+    synth_token.mint(&user, &200_000);
+
+    market.remove_liquidity_and_repay(&user, &0, &true);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.debt_balance, 0);
+    assert_eq!(synth_token.balance(&user), 0);
+    // every unit of collateral credited to the position is reflected in the market total
+    assert_eq!(
+        market.query_market().collateral.balance,
+        position.collateral_balance
+    );
+}
+
+#[test]
+fn remove_liquidity_and_repay_partially_repays_debt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    open_position(&env, &market, &user, 1_000_000, 200_000);
+    synth_token.mint(&user, &200_000);
+
+    market.remove_liquidity_and_repay(&user, &50_000, &false);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.debt_balance, 150_000);
+    assert_eq!(synth_token.balance(&user), 150_000);
+    // released collateral stays deposited in the market rather than being paid out
+    assert_eq!(collateral_token.balance(&user), 0);
+    assert_eq!(position.collateral_balance, 1_000_000);
+    assert_eq!(
+        market.query_market().collateral.balance,
+        position.collateral_balance
+    );
+}
@@ -0,0 +1,229 @@
+extern crate std;
+
+use soroban_sdk::{testutils::Address as _, Address, Env};
+
+use super::setup::{
+    deploy_market_contract, deploy_token_contract, get_position, open_position,
+    set_collateral_price,
+};
+use crate::state::market_position::{
+    get_market_position, save_market_position, MarketPositionStatus,
+};
+
+#[test]
+#[should_panic(expected = "Error(Contract, #27)")]
+fn liquidate_healthy_position_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&liquidator, &1_000_000);
+
+    // $100 of collateral against $80 of debt clears the 20% maintenance margin
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+
+    market.liquidate_position(&liquidator, &user, &1_000_000, &None);
+}
+
+#[test]
+fn partial_liquidation_capped_by_max_base_asset_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&liquidator, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    market.liquidate_position(&liquidator, &user, &100_000, &None);
+
+    // 100_000 debt repaid at $1 buys $100.01 of collateral priced at $0.90
+    assert_eq!(synth_token.balance(&liquidator), 900_000);
+    assert_eq!(collateral_token.balance(&liquidator), 111_122);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.debt_balance, 700_000);
+    assert_eq!(position.collateral_balance, 1_000_000 - 111_122 - 11);
+    assert_eq!(position.status, MarketPositionStatus::BeingLiquidated);
+    assert!(position.liquidation_margin_freed > 0);
+
+    let market_state = market.query_market();
+    assert_eq!(market_state.insurance_claim.liquidation_fees_owed, 11);
+    // both the liquidator's collateral and the insurance fee leave the market's deposits
+    assert_eq!(market_state.collateral.balance, position.collateral_balance);
+}
+
+#[test]
+fn liquidation_restores_position_margin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&liquidator, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    market.liquidate_position(&liquidator, &user, &1_000_000, &None);
+
+    // only the debt needed to cover the margin shortage (plus buffer) is repaid
+    assert_eq!(synth_token.balance(&liquidator), 1_000_000 - 345_769);
+    assert_eq!(collateral_token.balance(&liquidator), 384_226);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.debt_balance, 800_000 - 345_769);
+    assert_eq!(position.collateral_balance, 1_000_000 - 384_226 - 38);
+    assert_eq!(position.status, MarketPositionStatus::Active);
+    assert_eq!(position.liquidation_margin_freed, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #28)")]
+fn liquidation_fails_below_limit_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    synth_token.mint(&liquidator, &1_000_000);
+
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+    set_collateral_price(&env, &market, 9_000_000);
+
+    // ~1.111 collateral per synthetic is on offer, the liquidator asks for 1.2
+    market.liquidate_position(&liquidator, &user, &100_000, &Some(1_200_000));
+}
+
+#[test]
+fn resolve_bankruptcy_socializes_uncovered_debt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    // debt left behind once all collateral has been liquidated
+    env.as_contract(&market.address, || {
+        let mut position = get_market_position(&env, &user);
+        position.debt_balance = 500_000;
+        save_market_position(&env, &user, &position);
+    });
+
+    // without an insurance claim the whole loss is socialized
+    market.resolve_position_bankruptcy(&liquidator, &user);
+
+    let position = get_position(&env, &market, &user);
+    assert_eq!(position.debt_balance, 0);
+    assert_eq!(position.status, MarketPositionStatus::Active);
+
+    let market_info = market.query_market();
+    assert_eq!(market_info.protocol_debt, 500_000);
+    assert_eq!(market_info.total_gov_token_inflation, 50_000);
+    assert_eq!(market_info.collateral.balance, 0);
+    assert_eq!(market_info.insurance_claim.quote_settled_insurance, 0);
+}
+
+#[test]
+fn resolve_bankruptcy_draws_insurance_up_to_max_insurance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    market.update_max_insurance(&admin, &100_000);
+
+    env.as_contract(&market.address, || {
+        let mut position = get_market_position(&env, &user);
+        position.debt_balance = 500_000;
+        save_market_position(&env, &user, &position);
+    });
+
+    market.resolve_position_bankruptcy(&liquidator, &user);
+
+    let market_info = market.query_market();
+    assert_eq!(market_info.insurance_claim.quote_settled_insurance, 100_000);
+    assert_eq!(market_info.collateral.balance, 100_000);
+
+    // the claim is used up, so later losses are socialized in full
+    env.as_contract(&market.address, || {
+        let mut position = get_market_position(&env, &user);
+        position.debt_balance = 500_000;
+        save_market_position(&env, &user, &position);
+    });
+
+    market.resolve_position_bankruptcy(&liquidator, &user);
+
+    let market_info = market.query_market();
+    assert_eq!(market_info.insurance_claim.quote_settled_insurance, 100_000);
+    assert_eq!(market_info.collateral.balance, 100_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #29)")]
+fn resolve_bankruptcy_fails_for_collateralized_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let liquidator = Address::generate(&env);
+
+    let collateral_token = deploy_token_contract(&env, &admin);
+    let synth_token = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &collateral_token.address, &synth_token.address);
+
+    collateral_token.mint(&user, &1_000_000);
+    open_position(&env, &market, &user, 1_000_000, 800_000);
+
+    market.resolve_position_bankruptcy(&liquidator, &user);
+}
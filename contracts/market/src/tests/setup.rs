@@ -1,11 +1,163 @@
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, String};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, testutils::Address as _, Address, BytesN, Env, String,
+    Symbol, Vec,
+};
 
 use crate::{
-    contract::{SynthPool, SynthPoolClient},
+    contract::{SynthMarket, SynthMarketClient, SynthPool, SynthPoolClient},
+    state::market_position::{get_market_position, save_market_position, MarketPosition},
     token_contract,
 };
 
-use normal::utils::{SynthPoolInitInfo, TokenInitInfo};
+use normal::{
+    oracle::{OracleGuardRails, OracleSource},
+    reflector::Asset,
+    testutils::{MockReflector, MockReflectorClient},
+    types::{
+        market::{MarketFactoryConfig, MarketParams, SynthTier},
+        pool::PoolParams,
+    },
+    utils::{SynthPoolInitInfo, TokenInitInfo},
+};
+
+/// Stands in for the market factory, which markets query for their oracle guard rails
+#[contract]
+pub struct MockMarketFactory;
+
+#[contractimpl]
+impl MockMarketFactory {
+    pub fn query_config(env: Env) -> MarketFactoryConfig {
+        MarketFactoryConfig {
+            admin: env.current_contract_address(),
+            governor: env.current_contract_address(),
+            insurance: env.current_contract_address(),
+            market_wasm_hash: BytesN::from_array(&env, &[0; 32]),
+            token_wasm_hash: BytesN::from_array(&env, &[0; 32]),
+            super_keepers: Vec::new(&env),
+            oracle_guard_rails: OracleGuardRails::default(),
+        }
+    }
+}
+
+/// Stands in for an Insurance Fund that reports every requested bankruptcy payment
+/// as paid, without moving tokens
+#[contract]
+pub struct MockInsurance;
+
+#[contractimpl]
+impl MockInsurance {
+    pub fn cover_bankruptcy(_env: Env, market: Address, amount: i128) -> i128 {
+        market.require_auth();
+        amount
+    }
+}
+
+/// Deploys a Reflector mock quoting `symbols` in USD at `price` (7 decimals)
+pub fn deploy_reflector_oracle<'a>(
+    env: &Env,
+    symbols: &[Symbol],
+    price: i128,
+) -> MockReflectorClient<'a> {
+    let oracle = MockReflectorClient::new(env, &env.register(MockReflector, ()));
+    oracle.initialize(&Asset::Other(symbol_short!("USD")), &7, &300);
+    for symbol in symbols {
+        oracle.set_price(
+            &Asset::Other(symbol.clone()),
+            &price,
+            &env.ledger().timestamp(),
+        );
+    }
+    oracle
+}
+
+pub fn deploy_market_contract<'a>(
+    env: &Env,
+    collateral_token: &Address,
+    synth_token: &Address,
+) -> SynthMarketClient<'a> {
+    let market = SynthMarketClient::new(env, &env.register(SynthMarket, ()));
+    let factory = env.register(MockMarketFactory, ());
+    let insurance = env.register(MockInsurance, ());
+    let oracle = deploy_reflector_oracle(
+        env,
+        &[symbol_short!("XLM"), symbol_short!("BTC")],
+        10_000_000,
+    );
+
+    let params = MarketParams {
+        token_decimals: 7,
+        synth_token_name: String::from_str(env, "Normal Bitcoin"),
+        synth_token_symbol: String::from_str(env, "nBTC"),
+        synth_target_token_symbol: String::from_str(env, "BTC"),
+        quote_token: collateral_token.clone(),
+        quote_token_symbol: String::from_str(env, "XLM"),
+        lp_token_symbol: String::from_str(env, "nBTC-XLM LP"),
+        name: String::from_str(env, "nBTC-XLM"),
+        decimals: 7,
+        active_status: true,
+        tier: SynthTier::A,
+        oracle_source: OracleSource::Reflector,
+        oracle: oracle.address.clone(),
+        oracle_feeds: Vec::new(env),
+        margin_ratio_initial: 5_000,
+        margin_ratio_maintenance: 2_000,
+        imf_factor: 0,
+        liquidation_penalty: 500,
+        liquidator_fee: 100,
+        if_liquidation_fee: 100,
+        debt_ceiling: 0,
+        debt_floor: 0,
+        pool: PoolParams {
+            oracle: oracle.address.clone(),
+            oracle_source: OracleSource::Reflector,
+            tick_spacing: 64,
+            initial_sqrt_price: 1 << 64,
+            fee_rate: 3_000,
+            protocol_fee_rate: 100,
+            max_allowed_slippage_bps: 5_000,
+            max_allowed_variance_bps: 1_000,
+        },
+    };
+
+    market.initialize(
+        &params,
+        synth_token,
+        &Address::generate(env),
+        &factory,
+        &insurance,
+    );
+    market
+}
+
+/// Deposits `collateral` for `user` and records `debt` against it
+pub fn open_position(
+    env: &Env,
+    market: &SynthMarketClient,
+    user: &Address,
+    collateral: i128,
+    debt: u128,
+) {
+    market.deposit_collateral(user, &collateral, &false);
+
+    env.as_contract(&market.address, || {
+        let mut position = get_market_position(env, user);
+        position.debt_balance = debt;
+        save_market_position(env, user, &position);
+    });
+}
+
+pub fn get_position(env: &Env, market: &SynthMarketClient, user: &Address) -> MarketPosition {
+    env.as_contract(&market.address, || get_market_position(env, user))
+}
+
+/// Moves the collateral (XLM) oracle price, 7 decimals
+pub fn set_collateral_price(env: &Env, market: &SynthMarketClient, price: i128) {
+    MockReflectorClient::new(env, &market.query_market().collateral.oracle).set_price(
+        &Asset::Other(symbol_short!("XLM")),
+        &price,
+        &env.ledger().timestamp(),
+    );
+}
 
 pub fn deploy_token_contract<'a>(env: &Env, admin: &Address) -> token_contract::Client<'a> {
     token_contract::Client::new(
@@ -323,7 +323,8 @@ impl SparseSwapTickSequenceBuilder {
 //     })
 // }
 
-fn get_start_tick_indexes(pool: &Pool, a_to_b: bool) -> Vec<i32> {
+/// Start indexes of the current tick array and the next two in the swap direction
+pub fn get_start_tick_indexes(pool: &Pool, a_to_b: bool) -> Vec<i32> {
     let tick_current_index = pool.tick_current_index;
     let tick_spacing_u16 = pool.tick_spacing;
     let tick_spacing_i32 = pool.tick_spacing as i32;
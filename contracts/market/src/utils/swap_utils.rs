@@ -6,7 +6,7 @@ use crate::{controller::swap::PostSwapUpdate, state::pool::Pool};
 #[allow(clippy::too_many_arguments)]
 pub fn update_and_swap_amm(
     env: &Env,
-    pool: &Pool,
+    pool: &mut Pool,
     user: Address,
     swap_update: PostSwapUpdate,
     is_token_fee_in_a: bool,
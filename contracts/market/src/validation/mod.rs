@@ -1 +1,2 @@
 pub mod margin;
+pub mod oracle;
@@ -0,0 +1,45 @@
+use normal::{
+    math::oracle::{oracle_validity, validate_oracle_for_action, NormalAction},
+    oracle::{OraclePriceData, ValidityGuardRails},
+};
+use soroban_sdk::Env;
+
+use crate::state::market::Market;
+
+/// Classifies the collateral and synthetic oracle readings against their last twap
+/// and rejects the action if either is not valid for it
+pub fn validate_market_oracles_for_action(
+    env: &Env,
+    market: &Market,
+    collateral_oracle_price_data: &OraclePriceData,
+    debt_oracle_price_data: &OraclePriceData,
+    validity_guard_rails: &ValidityGuardRails,
+    action: NormalAction,
+) {
+    let max_confidence_interval_multiplier = market.get_max_confidence_interval_multiplier();
+
+    let collateral_oracle_validity = oracle_validity(
+        env,
+        market.collateral.oracle.clone(),
+        market
+            .collateral
+            .historical_oracle_data
+            .last_oracle_price_twap,
+        collateral_oracle_price_data,
+        validity_guard_rails,
+        max_confidence_interval_multiplier,
+        true,
+    );
+    validate_oracle_for_action(env, collateral_oracle_validity, action);
+
+    let debt_oracle_validity = oracle_validity(
+        env,
+        market.amm.oracle.clone(),
+        market.amm.historical_oracle_data.last_oracle_price_twap,
+        debt_oracle_price_data,
+        validity_guard_rails,
+        max_confidence_interval_multiplier,
+        true,
+    );
+    validate_oracle_for_action(env, debt_oracle_validity, action);
+}
@@ -1,21 +1,32 @@
 use normal::{
-    constants::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD},
-    error::{ErrorCode, NormalResult},
-    math::{casting::Cast, safe_math::SafeMath},
+    constants::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, PRICE_PRECISION_I128},
+    error::ErrorCode,
+    math::{
+        casting::Cast,
+        helpers::sqrt_price_to_price,
+        oracle::{oracle_validity, validate_oracle_for_action, NormalAction},
+        safe_math::SafeMath,
+    },
+    oracle::{get_oracle_price, OracleGuardRails},
+    types::{market::MarketPriceData, misc::OrderDirection},
     validate_bps,
 };
 use soroban_sdk::{
-    contract, contractimpl, contractmeta, log, panic_with_error, Address, Env, Map, Vec,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contract, contractimpl, contractmeta, log, panic_with_error, vec, Address, Env, IntoVal, Map,
+    Symbol, Vec,
 };
 
 use crate::{
     events::SchedulerEvents,
-    msg::{ConfigResponse, ScheduledResponse},
+    msg::{ConfigResponse, DueSchedule, ScheduledResponse},
     scheduler::SchedulerTrait,
     storage::{
-        get_config, get_keeper, get_schedules, save_config, save_keeper, save_schedules, utils,
-        Config, Schedule, ScheduleParams, ScheduleType,
+        get_config, get_keeper, get_scheduled_users, get_schedules, save_config, save_keeper,
+        save_schedules, utils, Config, KeeperInfo, OrderType, Schedule, ScheduleParams,
+        ScheduleType, TriggerPriceSource, USD,
     },
+    token_contract,
 };
 
 contractmeta!(
@@ -127,12 +138,17 @@ impl SchedulerTrait for Scheduler {
         sender.require_auth();
         utils::is_admin(&env, sender);
 
-        let config = get_config(&env);
+        let mut config = get_config(&env);
 
-        for (address, amount) in config.protocol_fees_to_collect.iter() {
-            utils::transfer_token(&env, &address, &env.current_contract_address(), &to, amount);
-            // TODO: set fee to collect to zero
+        for (asset, amount) in config.protocol_fees_to_collect.iter() {
+            if amount > 0 {
+                utils::transfer_token(&env, &asset, &env.current_contract_address(), &to, amount);
+                SchedulerEvents::collect_protocol_fees(&env, to.clone(), asset, amount);
+            }
         }
+
+        config.protocol_fees_to_collect = Map::new(&env);
+        save_config(&env, config);
     }
 
     // ################################################################
@@ -142,7 +158,7 @@ impl SchedulerTrait for Scheduler {
     fn execute_schedule(env: Env, sender: Address, user: Address, schedule_timestamp: u64) {
         sender.require_auth();
 
-        let config = get_config(&env);
+        let mut config = get_config(&env);
         let now = env.ledger().timestamp();
 
         if !config.keepers.contains(sender.clone()) {
@@ -153,101 +169,89 @@ impl SchedulerTrait for Scheduler {
             panic_with_error!(&env, ErrorCode::NotAuthorized);
         }
 
-        let schedules = get_schedules(&env, &user);
+        let mut schedules = get_schedules(&env, &user);
 
-        let mut target_schedule = match schedules
+        let schedule_index = match schedules
             .schedules
             .iter()
-            .find(|s| s.schedule_timestamp == schedule_timestamp)
+            .position(|s| s.schedule_timestamp == schedule_timestamp)
         {
-            Some(schedule) => schedule,
-            None => panic_with_error!(&env, ErrorCode::AdminNotSet), // TODO:
+            Some(index) => index as u32,
+            None => panic_with_error!(&env, ErrorCode::ScheduleNotFound),
         };
+        let mut target_schedule = schedules.schedules.get_unchecked(schedule_index);
 
-        // TODO: Validate the schedule needs to be executed
+        validate_schedule_is_due(&env, &target_schedule, now);
 
-        // TODO: Compute protocol and keeper fee
-        let protocol_fee: u64 = 0;
-        let keeper_fee: u64 = 0;
-
-        // Calculate order amount
-        let order_quote_asset_amount = calculate_order_amount(&env, &target_schedule);
-
-        // Validate available balance compared to order amount
-        let current_balance = schedules
-            .balances
-            .get(target_schedule.clone().quote_asset)
-            .unwrap_or(0);
-
-        if order_quote_asset_amount > current_balance {
-            panic_with_error!(&env, ErrorCode::InsufficientFunds);
+        // Orders waiting on their trigger revert, so they pay no fees and keep the interval
+        if !is_order_triggered(&env, &target_schedule, now) {
+            log!(
+                &env,
+                "Scheduler: Execute Schedule: schedule {} trigger price not met",
+                schedule_timestamp
+            );
+            panic_with_error!(&env, ErrorCode::OrderNotTriggered);
         }
 
         // Execute the order
-        match target_schedule.schedule_type {
+        let order_quote_asset_amount = match target_schedule.schedule_type {
             ScheduleType::Asset => {
-                // TODO:
-                // let amm_response: SwapResponse = env.invoke_contract(
-                //     &target_schedule.target_contract_address,
-                //     &Symbol::new(&env, "swap"),
-                //     vec![
-                //         &env,
-                //         user.into_val(&env),
-                //         amount,
-                //         other_amount_threshold,
-                //         sqrt_price_limit,
-                //         amount_specified_is_input,
-                //         a_to_b
-                //     ]
-                // );
-                // assert!(
-                //     amm_response.ask_amount.is_some(),
-                //     "Scheduler: Create Schedule: Invalid AMM response"
-                // );
+                execute_market_order(&env, &config, &target_schedule, &mut schedules.balances)
             }
-            ScheduleType::Index => {
-                // TODO:
-                // let index_response: MintResponse = env.invoke_contract(
-                //     &target_schedule.target_contract_address,
-                //     &Symbol::new(&env, "mint"),
-                //     vec![&env, user.into_val(&env), amount]
-                // );
-                // assert!(
-                //     index_response.mint_amount.is_some(),
-                //     "Scheduler: Create Schedule: Invalid Index response"
-                // );
-            }
-        }
-
-        // Update protocol and keeper fees
-        let mut keeper = get_keeper(&env, &sender);
-        // let keeper_fee_before = keeper.fees_owed.get(target_schedule.quote_asset);
-        let keeper_fee_before = match keeper.fees_owed.get(target_schedule.clone().quote_asset) {
-            Some(bal) => bal,
-            None => panic_with_error!(&env, ErrorCode::AdminNotSet), // TODO:
+            ScheduleType::Index => execute_index_order(
+                &env,
+                &config,
+                &user,
+                &target_schedule,
+                &mut schedules.balances,
+            ),
         };
 
-        keeper.fees_owed.set(
-            target_schedule.clone().quote_asset,
-            keeper_fee_before.safe_add(keeper_fee.cast::<i128>(&env), &env),
+        // Charge protocol and keeper fees out of the order's quote amount
+        let mut keeper = get_keeper(&env, &sender);
+        let (protocol_fee, keeper_fee) = charge_order_fees(
+            &env,
+            &mut config,
+            &mut keeper,
+            &mut schedules.balances,
+            &target_schedule.quote_asset,
+            order_quote_asset_amount,
         );
 
-        // ...
+        keeper.total_orders = keeper.total_orders.safe_add(1, &env);
+        keeper.total_order_amount = keeper
+            .total_order_amount
+            .safe_add(order_quote_asset_amount.cast::<u128>(&env), &env);
+        keeper.last_order_time = now;
+
+        save_config(&env, config);
+        save_keeper(&env, &sender, &keeper);
 
         // Update the Schedule
         target_schedule.executed_orders += 1;
         target_schedule.total_executed = target_schedule
             .total_executed
             .safe_add(order_quote_asset_amount, &env);
-        target_schedule.total_fees_paid = target_schedule
-            .total_fees_paid
-            .safe_add(protocol_fee, &env)
-            .safe_add(keeper_fee, &env);
+        target_schedule.total_fees_paid = target_schedule.total_fees_paid.safe_add(
+            protocol_fee.safe_add(keeper_fee, &env).cast::<u64>(&env),
+            &env,
+        );
         target_schedule.last_order_ts = now;
+        target_schedule.last_updated_ts = now;
 
-        save_schedules(&env, &sender, &schedules);
+        let exhausted = target_schedule.is_exhausted();
+        if exhausted {
+            target_schedule.active = false;
+        }
+
+        schedules.schedules.set(schedule_index, target_schedule);
+        save_schedules(&env, &user, &schedules);
+
+        SchedulerEvents::order_execution(&env, sender, user.clone(), schedule_timestamp);
 
-        SchedulerEvents::order_execution(&env, sender, user, schedule_timestamp);
+        if exhausted {
+            SchedulerEvents::deactivate_schedule(&env, user, schedule_timestamp);
+        }
     }
 
     fn collect_keeper_fees(env: Env, sender: Address) {
@@ -255,17 +259,20 @@ impl SchedulerTrait for Scheduler {
 
         let mut keeper = get_keeper(&env, &sender);
 
-        for (address, amount) in keeper.fees_owed.iter() {
-            utils::transfer_token(
-                &env,
-                &address,
-                &env.current_contract_address(),
-                &sender,
-                amount,
-            );
-            // TODO: set fee to collect to zero
+        for (asset, amount) in keeper.fees_owed.iter() {
+            if amount > 0 {
+                utils::transfer_token(
+                    &env,
+                    &asset,
+                    &env.current_contract_address(),
+                    &sender,
+                    amount,
+                );
+                SchedulerEvents::collect_keeper_fees(&env, sender.clone(), asset, amount);
+            }
         }
 
+        keeper.fees_owed = Map::new(&env);
         keeper.last_fee_collection_time = env.ledger().timestamp();
 
         save_keeper(&env, &sender, &keeper);
@@ -294,6 +301,8 @@ impl SchedulerTrait for Scheduler {
             .balances
             .set(asset.clone(), current_balance + amount);
 
+        save_schedules(&env, &sender, &schedules);
+
         SchedulerEvents::deposit(&env, sender, asset, amount);
     }
 
@@ -321,6 +330,8 @@ impl SchedulerTrait for Scheduler {
             .balances
             .set(asset.clone(), current_balance - amount);
 
+        save_schedules(&env, &sender, &schedules);
+
         SchedulerEvents::withdrawal(&env, sender, asset, amount);
     }
 
@@ -330,6 +341,8 @@ impl SchedulerTrait for Scheduler {
         // TODO: Make sure target_contract_address exists
 
         let now = env.ledger().timestamp();
+        validate_schedule_params(&env, &params, now);
+
         let mut schedules = get_schedules(&env, &sender);
 
         let schedule = Schedule {
@@ -341,8 +354,15 @@ impl SchedulerTrait for Scheduler {
             interval_seconds: params.interval_seconds,
             min_price: params.min_price,
             max_price: params.max_price,
+            start_ts: params.start_ts.unwrap_or(now),
+            end_ts: params.end_ts,
+            max_orders: params.max_orders,
+            total_budget: params.total_budget,
+            order_type: params.order_type,
+            trigger_price: params.trigger_price,
+            trigger_price_source: params.trigger_price_source,
+            active: true,
             schedule_timestamp: env.ledger().timestamp(),
-            total_orders: 0,
             executed_orders: 0,
             total_executed: 0,
             total_fees_paid: 0,
@@ -405,18 +425,556 @@ impl SchedulerTrait for Scheduler {
             schedules: schedules.schedules,
         }
     }
+
+    fn query_due_schedules(env: Env, offset: u32, limit: u32) -> Vec<DueSchedule> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let now = env.ledger().timestamp();
+        let users = get_scheduled_users(&env, offset, limit);
+
+        let mut due_schedules = Vec::new(&env);
+        for user in users.iter() {
+            for schedule in get_schedules(&env, &user).schedules.iter() {
+                if schedule.is_due(now) {
+                    due_schedules.push_back(DueSchedule {
+                        user: user.clone(),
+                        schedule_timestamp: schedule.schedule_timestamp,
+                    });
+                }
+            }
+        }
+
+        due_schedules
+    }
+}
+
+/// Protocol and keeper fees on an order's quote amount
+///
+/// Returns `(protocol_fee, keeper_fee)`
+fn calculate_order_fees(config: &Config, order_quote_asset_amount: i128) -> (i128, i128) {
+    (
+        order_quote_asset_amount * config.protocol_fee_rate(),
+        order_quote_asset_amount * config.keeper_fee_rate(),
+    )
 }
 
-fn calculate_order_amount(env: &Env, schedule: &Schedule) -> i128 {
-    let price: i128 = 0; // TODO: get the price
+/// Quote left to trade once the fees are deducted from an order's quote amount
+fn net_of_order_fees(env: &Env, config: &Config, order_quote_asset_amount: i128) -> i128 {
+    let (protocol_fee, keeper_fee) = calculate_order_fees(config, order_quote_asset_amount);
+
+    order_quote_asset_amount
+        .safe_sub(protocol_fee, env)
+        .safe_sub(keeper_fee, env)
+}
+
+/// Charges the protocol and keeper fees on an order's quote amount to the user's
+/// quote balance and accrues them for collection. Buys only trade the quote left
+/// after fees and sells pay them out of their proceeds, so the fees come out of
+/// the order rather than on top of it.
+///
+/// Returns `(protocol_fee, keeper_fee)`
+pub(crate) fn charge_order_fees(
+    env: &Env,
+    config: &mut Config,
+    keeper: &mut KeeperInfo,
+    balances: &mut Map<Address, i128>,
+    quote_asset: &Address,
+    order_quote_asset_amount: i128,
+) -> (i128, i128) {
+    let (protocol_fee, keeper_fee) = calculate_order_fees(config, order_quote_asset_amount);
+    let total_fee = protocol_fee.safe_add(keeper_fee, env);
+
+    let quote_balance = balances.get(quote_asset.clone()).unwrap_or(0);
+    if total_fee > quote_balance {
+        log!(
+            env,
+            "Scheduler: Execute Schedule: fees {} exceed quote balance {}",
+            total_fee,
+            quote_balance
+        );
+        panic_with_error!(env, ErrorCode::InsufficientFunds);
+    }
+    balances.set(quote_asset.clone(), quote_balance.safe_sub(total_fee, env));
+
+    let protocol_fees_to_collect = config
+        .protocol_fees_to_collect
+        .get(quote_asset.clone())
+        .unwrap_or(0);
+    config.protocol_fees_to_collect.set(
+        quote_asset.clone(),
+        protocol_fees_to_collect.safe_add(protocol_fee, env),
+    );
+
+    let keeper_fees_owed = keeper.fees_owed.get(quote_asset.clone()).unwrap_or(0);
+    keeper.fees_owed.set(
+        quote_asset.clone(),
+        keeper_fees_owed.safe_add(keeper_fee, env),
+    );
+    keeper.total_fees = keeper.total_fees.safe_add(keeper_fee.cast::<u64>(env), env);
+
+    (protocol_fee, keeper_fee)
+}
+
+fn validate_schedule_params(env: &Env, params: &ScheduleParams, now: u64) {
+    let start_ts = params.start_ts.unwrap_or(now);
+
+    if params.base_asset_amount_per_interval == 0
+        || params.interval_seconds == 0
+        || params
+            .end_ts
+            .is_some_and(|end_ts| end_ts < start_ts.max(now))
+        || params.max_orders.is_some_and(|max_orders| max_orders == 0)
+        || params
+            .total_budget
+            .is_some_and(|total_budget| total_budget <= 0)
+    {
+        log!(env, "Scheduler: Create Schedule: invalid schedule params");
+        panic_with_error!(env, ErrorCode::InvalidSchedule);
+    }
+
+    // Conditional orders need a trigger price they can be evaluated against
+    if params.order_type != OrderType::Recurring
+        && (params.trigger_price.unwrap_or(0) == 0
+            || (params.schedule_type == ScheduleType::Index
+                && params.trigger_price_source == TriggerPriceSource::Pool))
+    {
+        log!(env, "Scheduler: Create Schedule: invalid schedule params");
+        panic_with_error!(env, ErrorCode::InvalidSchedule);
+    }
+}
+
+fn validate_schedule_is_due(env: &Env, schedule: &Schedule, now: u64) {
+    if !schedule.active {
+        log!(
+            env,
+            "Scheduler: Execute Schedule: schedule {} is inactive",
+            schedule.schedule_timestamp
+        );
+        panic_with_error!(env, ErrorCode::ScheduleInactive);
+    }
+
+    if !schedule.is_due(now) {
+        log!(
+            env,
+            "Scheduler: Execute Schedule: schedule {} is not due until {}",
+            schedule.schedule_timestamp,
+            schedule.next_order_ts()
+        );
+        panic_with_error!(env, ErrorCode::ScheduleNotDue);
+    }
+}
+
+/// Whether the price condition of a conditional order is met. Recurring orders are
+/// always triggered.
+fn is_order_triggered(env: &Env, schedule: &Schedule, now: u64) -> bool {
+    let trigger_price = match (schedule.order_type, schedule.trigger_price) {
+        (OrderType::Recurring, _) | (_, None) => return true,
+        (_, Some(trigger_price)) => trigger_price.cast::<i128>(env),
+    };
+
+    let price = get_trigger_price(env, schedule, now);
+
+    // Limit orders wait for a better price, stop-losses for a worse one
+    let better_price = schedule.order_type == OrderType::Limit;
+    match (schedule.direction, better_price) {
+        (OrderDirection::Buy, true) | (OrderDirection::Sell, false) => price <= trigger_price,
+        (OrderDirection::Buy, false) | (OrderDirection::Sell, true) => price >= trigger_price,
+    }
+}
+
+/// precision: PRICE_PRECISION
+fn get_trigger_price(env: &Env, schedule: &Schedule, now: u64) -> i128 {
+    let target = &schedule.target_contract_address;
+
+    match schedule.schedule_type {
+        ScheduleType::Index => {
+            env.invoke_contract(target, &Symbol::new(env, "query_price"), vec![env])
+        }
+        ScheduleType::Asset => {
+            let price_data = get_market_price_data(env, target);
+            match schedule.trigger_price_source {
+                TriggerPriceSource::Oracle => {
+                    get_oracle_price_for_order(env, target, &price_data, now)
+                }
+                TriggerPriceSource::Pool => get_pool_price(env, &price_data),
+            }
+        }
+    }
+}
+
+/// The synth's USD oracle price, validated for order triggers
+fn get_oracle_price_for_order(
+    env: &Env,
+    market: &Address,
+    price_data: &MarketPriceData,
+    now: u64,
+) -> i128 {
+    let validity_guard_rails = OracleGuardRails::default().validity;
+    let oracle_price_data = get_oracle_price(
+        env,
+        &price_data.oracle_source,
+        &price_data.oracle,
+        (price_data.symbol.clone(), USD),
+        now,
+    );
+
+    let oracle_validity = oracle_validity(
+        env,
+        market.clone(),
+        price_data.last_oracle_price_twap,
+        &oracle_price_data,
+        &validity_guard_rails,
+        1,
+        false,
+    );
+    validate_oracle_for_action(env, oracle_validity, NormalAction::OracleOrderPrice);
+
+    oracle_price_data.price.cast(env)
+}
+
+fn calculate_order_amount(env: &Env, schedule: &Schedule, price: i128) -> i128 {
     let order_quote_asset_amount: i128 = schedule
         .base_asset_amount_per_interval
         .cast::<i128>(env)
-        .safe_mul(price, env);
+        .safe_mul(price, env)
+        .safe_div(PRICE_PRECISION_I128, env);
 
     order_quote_asset_amount
 }
 
+/// Scales `base_asset_amount` down so an order worth `order_quote_amount` doesn't
+/// trade more than the schedule's remaining budget
+fn clamp_to_remaining_budget(
+    env: &Env,
+    schedule: &Schedule,
+    base_asset_amount: i128,
+    order_quote_amount: i128,
+) -> i128 {
+    match schedule.remaining_budget() {
+        Some(remaining_budget) if order_quote_amount > remaining_budget => base_asset_amount
+            .safe_mul(remaining_budget, env)
+            .safe_div(order_quote_amount, env),
+        _ => base_asset_amount,
+    }
+}
+
+/// Swaps one interval of an asset schedule against its synth market pool and settles
+/// both sides against the user's deposited balances.
+///
+/// Buys cost the quote equivalent of `base_asset_amount_per_interval` at the pool price,
+/// swap what is left of it after fees and receive no less synth than `max_price`
+/// allows. Sells offer `base_asset_amount_per_interval` of the deposited synth and
+/// receive no less quote than `min_price` allows, before fees.
+///
+/// Returns the order's quote amount, fees included
+fn execute_market_order(
+    env: &Env,
+    config: &Config,
+    schedule: &Schedule,
+    balances: &mut Map<Address, i128>,
+) -> i128 {
+    let market = &schedule.target_contract_address;
+    let price_data = get_market_price_data(env, market);
+    let pool_price = get_pool_price(env, &price_data);
+
+    let base_asset_amount = clamp_to_remaining_budget(
+        env,
+        schedule,
+        schedule.base_asset_amount_per_interval.cast::<i128>(env),
+        calculate_order_amount(env, schedule, pool_price),
+    );
+
+    let quote_amount = base_asset_amount
+        .safe_mul(pool_price, env)
+        .safe_div(PRICE_PRECISION_I128, env);
+
+    // Pools are synth (token a) / quote (token b)
+    let (offer_asset, ask_asset, offer_amount, ask_asset_min_amount) = match schedule.direction {
+        OrderDirection::Buy => {
+            let quote_balance = balances.get(schedule.quote_asset.clone()).unwrap_or(0);
+            if quote_amount > quote_balance {
+                log!(
+                    env,
+                    "Scheduler: Execute Schedule: order amount {} exceeds balance {}",
+                    quote_amount,
+                    quote_balance
+                );
+                panic_with_error!(env, ErrorCode::InsufficientFunds);
+            }
+
+            let swap_amount = net_of_order_fees(env, config, quote_amount);
+            let min_synth_out = match schedule.max_price {
+                Some(max_price) => swap_amount
+                    .safe_mul(PRICE_PRECISION_I128, env)
+                    .safe_div(i128::from(max_price), env),
+                None => 0,
+            };
+            (
+                schedule.quote_asset.clone(),
+                price_data.synth_token,
+                swap_amount,
+                min_synth_out,
+            )
+        }
+        OrderDirection::Sell => {
+            let min_quote_out = match schedule.min_price {
+                Some(min_price) => base_asset_amount
+                    .safe_mul(i128::from(min_price), env)
+                    .safe_div(PRICE_PRECISION_I128, env),
+                None => 0,
+            };
+            (
+                price_data.synth_token,
+                schedule.quote_asset.clone(),
+                base_asset_amount,
+                min_quote_out,
+            )
+        }
+    };
+
+    let offer_balance = balances.get(offer_asset.clone()).unwrap_or(0);
+    if offer_amount <= 0 || offer_amount > offer_balance {
+        log!(
+            env,
+            "Scheduler: Execute Schedule: order amount {} exceeds balance {}",
+            offer_amount,
+            offer_balance
+        );
+        panic_with_error!(env, ErrorCode::InsufficientFunds);
+    }
+
+    let ask_amount = swap(
+        env,
+        market,
+        &offer_asset,
+        &ask_asset,
+        offer_amount,
+        ask_asset_min_amount,
+        schedule.direction == OrderDirection::Sell,
+    );
+
+    let ask_balance = balances.get(ask_asset.clone()).unwrap_or(0);
+    balances.set(offer_asset, offer_balance.safe_sub(offer_amount, env));
+    balances.set(ask_asset, ask_balance.safe_add(ask_amount, env));
+
+    match schedule.direction {
+        OrderDirection::Buy => quote_amount,
+        OrderDirection::Sell => ask_amount,
+    }
+}
+
+/// Mints or redeems one interval of an index schedule on behalf of the user. The
+/// scheduler holds the index tokens and tracks them in the user's balances under the
+/// index address, so they can be redeemed by later orders or withdrawn. The user
+/// must be allowed by the index access mode, not just the scheduler.
+///
+/// Prices are in quote per index token. Mints cost the quote value of the order at
+/// the index price, only mint what is left of it after fees and spend no more than
+/// `max_price` allows. Redemptions receive no less quote than `min_price` allows,
+/// before fees.
+///
+/// Returns the order's quote amount, fees included
+fn execute_index_order(
+    env: &Env,
+    config: &Config,
+    user: &Address,
+    schedule: &Schedule,
+    balances: &mut Map<Address, i128>,
+) -> i128 {
+    let index = &schedule.target_contract_address;
+    let current_contract = env.current_contract_address();
+    let now = env.ledger().timestamp();
+
+    env.invoke_contract::<()>(
+        index,
+        &Symbol::new(env, "check_account_access"),
+        vec![env, user.clone().into_val(env)],
+    );
+
+    let index_token_amount = schedule.base_asset_amount_per_interval.cast::<i128>(env);
+    let order_quote_amount: i128 = env.invoke_contract(
+        index,
+        &Symbol::new(env, "query_quote_amount"),
+        vec![env, index_token_amount.into_val(env)],
+    );
+    let index_token_amount =
+        clamp_to_remaining_budget(env, schedule, index_token_amount, order_quote_amount);
+    let order_quote_amount = schedule
+        .remaining_budget()
+        .map_or(order_quote_amount, |remaining_budget| {
+            order_quote_amount.min(remaining_budget)
+        });
+    if index_token_amount <= 0 || order_quote_amount <= 0 {
+        panic_with_error!(env, ErrorCode::InsufficientFunds);
+    }
+
+    let quote_token = token_contract::Client::new(env, &schedule.quote_asset);
+    let index_token = token_contract::Client::new(env, index);
+
+    let quote_balance = balances.get(schedule.quote_asset.clone()).unwrap_or(0);
+    let index_balance = balances.get(index.clone()).unwrap_or(0);
+
+    let quote_balance_before = quote_token.balance(&current_contract);
+    let index_balance_before = index_token.balance(&current_contract);
+
+    match schedule.direction {
+        OrderDirection::Buy => {
+            if order_quote_amount > quote_balance {
+                log!(
+                    env,
+                    "Scheduler: Execute Schedule: order amount {} exceeds balance {}",
+                    order_quote_amount,
+                    quote_balance
+                );
+                panic_with_error!(env, ErrorCode::InsufficientFunds);
+            }
+
+            // Only the quote left after fees is minted with
+            let max_quote_in = net_of_order_fees(env, config, order_quote_amount);
+            let mint_amount = index_token_amount
+                .safe_mul(max_quote_in, env)
+                .safe_div(order_quote_amount, env);
+            let max_quote_in = match schedule.max_price {
+                Some(max_price) => mint_amount
+                    .safe_mul(i128::from(max_price), env)
+                    .safe_div(PRICE_PRECISION_I128, env)
+                    .min(max_quote_in),
+                None => max_quote_in,
+            };
+            if mint_amount <= 0 || max_quote_in <= 0 {
+                panic_with_error!(env, ErrorCode::InsufficientFunds);
+            }
+
+            // The index pulls the quote it needs through the allowance
+            let expiration_ledger = env.ledger().sequence();
+            quote_token.approve(&current_contract, index, &max_quote_in, &expiration_ledger);
+
+            env.invoke_contract::<()>(
+                index,
+                &Symbol::new(env, "mint_with_slippage"),
+                vec![
+                    env,
+                    current_contract.clone().into_val(env),
+                    mint_amount.into_val(env),
+                    max_quote_in.into_val(env),
+                    now.into_val(env),
+                ],
+            );
+
+            quote_token.approve(&current_contract, index, &0, &expiration_ledger);
+        }
+        OrderDirection::Sell => {
+            if index_token_amount > index_balance {
+                log!(
+                    env,
+                    "Scheduler: Execute Schedule: order amount {} exceeds balance {}",
+                    index_token_amount,
+                    index_balance
+                );
+                panic_with_error!(env, ErrorCode::InsufficientFunds);
+            }
+
+            let min_quote_out = schedule.min_price.map_or(0, |min_price| {
+                index_token_amount
+                    .safe_mul(i128::from(min_price), env)
+                    .safe_div(PRICE_PRECISION_I128, env)
+            });
+
+            env.invoke_contract::<()>(
+                index,
+                &Symbol::new(env, "redeem_with_slippage"),
+                vec![
+                    env,
+                    current_contract.clone().into_val(env),
+                    index_token_amount.into_val(env),
+                    min_quote_out.into_val(env),
+                    now.into_val(env),
+                ],
+            );
+        }
+    }
+
+    let quote_delta = quote_token
+        .balance(&current_contract)
+        .safe_sub(quote_balance_before, env);
+    let index_delta = index_token
+        .balance(&current_contract)
+        .safe_sub(index_balance_before, env);
+
+    balances.set(
+        schedule.quote_asset.clone(),
+        quote_balance.safe_add(quote_delta, env),
+    );
+    balances.set(index.clone(), index_balance.safe_add(index_delta, env));
+
+    match schedule.direction {
+        OrderDirection::Buy => order_quote_amount,
+        OrderDirection::Sell => quote_delta,
+    }
+}
+
+/// Swaps an exact `offer_amount` held by the scheduler on `market`
+///
+/// Returns the amount of `ask_asset` received
+fn swap(
+    env: &Env,
+    market: &Address,
+    offer_asset: &Address,
+    ask_asset: &Address,
+    offer_amount: i128,
+    ask_asset_min_amount: i128,
+    a_to_b: bool,
+) -> i128 {
+    let current_contract = env.current_contract_address();
+    let ask_token = token_contract::Client::new(env, ask_asset);
+    let ask_balance_before = ask_token.balance(&current_contract);
+
+    // The market pulls the offer asset from the scheduler
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: offer_asset.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (current_contract.clone(), market.clone(), offer_amount).into_val(env),
+            },
+            sub_invocations: Vec::new(env),
+        }),
+    ]);
+
+    env.invoke_contract::<()>(
+        market,
+        &Symbol::new(env, "swap"),
+        vec![
+            env,
+            current_contract.clone().into_val(env),
+            offer_amount.cast::<u64>(env).into_val(env),
+            ask_asset_min_amount.cast::<u64>(env).into_val(env),
+            0u128.into_val(env), // no sqrt price limit
+            true.into_val(env),  // amount is input
+            a_to_b.into_val(env),
+            Vec::<i32>::new(env).into_val(env),
+        ],
+    );
+
+    ask_token
+        .balance(&current_contract)
+        .safe_sub(ask_balance_before, env)
+}
+
+fn get_market_price_data(env: &Env, market: &Address) -> MarketPriceData {
+    env.invoke_contract(market, &Symbol::new(env, "query_price_data"), vec![env])
+}
+
+/// Pool price of the synth in the quote token
+///
+/// precision: PRICE_PRECISION
+fn get_pool_price(env: &Env, price_data: &MarketPriceData) -> i128 {
+    sqrt_price_to_price(env, price_data.sqrt_price).cast(env)
+}
+
 // Function to remove a schedule from the vector
 fn remove_schedule(env: &Env, schedules: &mut Vec<Schedule>, schedule_timestamp: u64) {
     // Find the index of the stake that matches the given stake and schedule_timestamp
@@ -429,7 +987,7 @@ fn remove_schedule(env: &Env, schedules: &mut Vec<Schedule>, schedule_timestamp:
     } else {
         // Schedule not found, return an error
         log!(&env, "Schedule: Remove schedule: Schedule not found");
-        // panic_with_error!(&env, ContractError::StakeNotFound);
+        panic_with_error!(&env, ErrorCode::ScheduleNotFound);
     }
 }
 
@@ -70,4 +70,31 @@ impl SchedulerEvents {
         let topics = (Symbol::new(env, "delete_schedule"), user);
         env.events().publish(topics, schedule_timestamp);
     }
+
+    /// Emitted when a schedule runs out of orders, budget or time
+    ///
+    /// - topics - `["deactivate_schedule", user: Address]`
+    /// - data - [schedule_timestamp: u64]
+    pub fn deactivate_schedule(env: &Env, user: Address, schedule_timestamp: u64) {
+        let topics = (Symbol::new(env, "deactivate_schedule"), user);
+        env.events().publish(topics, schedule_timestamp);
+    }
+
+    /// Emitted when the admin collects protocol fees
+    ///
+    /// - topics - `["collect_protocol_fees", to: Address]`
+    /// - data - `[asset: Address, amount: i128]`
+    pub fn collect_protocol_fees(env: &Env, to: Address, asset: Address, amount: i128) {
+        let topics = (Symbol::new(env, "collect_protocol_fees"), to);
+        env.events().publish(topics, (asset, amount));
+    }
+
+    /// Emitted when a keeper collects the fees owed to it
+    ///
+    /// - topics - `["collect_keeper_fees", keeper: Address]`
+    /// - data - `[asset: Address, amount: i128]`
+    pub fn collect_keeper_fees(env: &Env, keeper: Address, asset: Address, amount: i128) {
+        let topics = (Symbol::new(env, "collect_keeper_fees"), keeper);
+        env.events().publish(topics, (asset, amount));
+    }
 }
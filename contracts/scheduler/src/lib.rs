@@ -15,17 +15,7 @@ pub mod token_contract {
     );
 }
 
-pub mod index_token {
-    soroban_sdk::contractimport!(
-        file = "../../target/wasm32-unknown-unknown/release/normal_index_token.wasm"
-    );
-}
-
-pub mod market {
-    soroban_sdk::contractimport!(
-        file = "../../target/wasm32-unknown-unknown/release/normal_market.wasm"
-    );
-}
-
 #[cfg(test)]
 mod tests;
+
+pub use crate::contract::SchedulerClient;
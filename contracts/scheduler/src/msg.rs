@@ -14,3 +14,10 @@ pub struct ScheduledResponse {
     pub balances: Map<Address, i128>,
     pub schedules: Vec<Schedule>,
 }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DueSchedule {
+    pub user: Address,
+    pub schedule_timestamp: u64,
+}
@@ -1,7 +1,7 @@
 use soroban_sdk::{contractclient, Address, Env, Vec};
 
 use crate::{
-    msg::{ConfigResponse, ScheduledResponse},
+    msg::{ConfigResponse, DueSchedule, ScheduledResponse},
     storage::ScheduleParams,
 };
 
@@ -35,6 +35,9 @@ pub trait SchedulerTrait {
     //                             KEEPER
     // ################################################################
 
+    /// Executes the next order of one of `user`'s due schedules. Conditional orders
+    /// whose trigger price isn't met revert with `OrderNotTriggered`, without charging
+    /// fees or using up the interval.
     fn execute_schedule(env: Env, sender: Address, user: Address, schedule_timestamp: u64);
 
     fn collect_keeper_fees(env: Env, sender: Address);
@@ -60,4 +63,10 @@ pub trait SchedulerTrait {
     fn query_admin(env: Env) -> Address;
 
     fn query_scheduled(env: Env, address: Address) -> ScheduledResponse;
+
+    /// Returns the schedules that can be executed now, scanning up to `limit`
+    /// (capped at `MAX_QUERY_LIMIT`) users with active schedules from `offset`
+    ///
+    /// Trigger prices of conditional orders are only checked at execution
+    fn query_due_schedules(env: Env, offset: u32, limit: u32) -> Vec<DueSchedule>;
 }
@@ -1,9 +1,13 @@
 use normal::{
     constants::{PERSISTENT_BUMP_AMOUNT, PERSISTENT_LIFETIME_THRESHOLD},
-    types::OrderDirection,
+    types::misc::OrderDirection,
 };
 use soroban_decimal::Decimal;
-use soroban_sdk::{contracttype, Address, ConversionError, Env, Map, TryFromVal, Val, Vec};
+use soroban_sdk::{
+    contracttype, symbol_short, Address, ConversionError, Env, Map, Symbol, TryFromVal, Val, Vec,
+};
+
+pub const USD: Symbol = symbol_short!("USD");
 
 #[derive(Clone, Copy)]
 #[repr(u32)]
@@ -11,8 +15,23 @@ pub enum DataKey {
     Config = 1,
     Admin = 2,
     Initialized = 3,
+    ScheduledUserCount = 4,
+}
+
+/// Users with at least one active schedule, stored by position so each save
+/// only touches the entries that change
+#[derive(Clone)]
+#[contracttype]
+pub enum ScheduledUserKey {
+    /// position -> user
+    User(u32),
+    /// user -> position
+    Position(Address),
 }
 
+/// Max number of users scanned by a paginated query
+pub const MAX_QUERY_LIMIT: u32 = 50;
+
 impl TryFromVal<Env, DataKey> for Val {
     type Error = ConversionError;
 
@@ -84,6 +103,33 @@ pub enum ScheduleType {
     Index = 1,
 }
 
+/// When an order fires relative to its `trigger_price`. Conditional orders
+/// without `max_orders` execute once.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum OrderType {
+    /// Executes every interval regardless of price
+    Recurring = 0,
+    /// Buys at or below, or sells at or above, the trigger price. Also used to
+    /// take profit.
+    Limit = 1,
+    /// Buys at or above, or sells at or below, the trigger price
+    StopLoss = 2,
+}
+
+/// The price a conditional asset order is evaluated against. Index orders are
+/// evaluated against the index price.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TriggerPriceSource {
+    /// The synth market's oracle price in USD
+    Oracle = 0,
+    /// The synth market pool price in the quote asset
+    Pool = 1,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Asset {
@@ -104,6 +150,19 @@ pub struct ScheduleParams {
     pub interval_seconds: u64,
     pub min_price: Option<u32>,
     pub max_price: Option<u32>,
+    /// The first ts an order can be executed, defaults to now
+    pub start_ts: Option<u64>,
+    /// The last ts an order can be executed
+    pub end_ts: Option<u64>,
+    /// Max number of orders to execute
+    pub max_orders: Option<u32>,
+    /// Max quote amount to trade across all orders
+    pub total_budget: Option<i128>,
+    pub order_type: OrderType,
+    /// Required by conditional orders
+    /// precision: PRICE_PRECISION
+    pub trigger_price: Option<u64>,
+    pub trigger_price_source: TriggerPriceSource,
 }
 
 #[contracttype]
@@ -115,9 +174,17 @@ pub struct Schedule {
     pub base_asset_amount_per_interval: u64,
     pub direction: OrderDirection,
     pub interval_seconds: u64,
-    pub total_orders: u32,
     pub min_price: Option<u32>,
     pub max_price: Option<u32>,
+    pub start_ts: u64,
+    pub end_ts: Option<u64>,
+    pub max_orders: Option<u32>,
+    pub total_budget: Option<i128>,
+    pub order_type: OrderType,
+    pub trigger_price: Option<u64>,
+    pub trigger_price_source: TriggerPriceSource,
+    /// Set to false once the schedule is exhausted
+    pub active: bool,
     pub executed_orders: u32,
     pub total_executed: i128,
     pub total_fees_paid: u64,
@@ -127,6 +194,45 @@ pub struct Schedule {
     pub schedule_timestamp: u64,
 }
 
+impl Schedule {
+    /// The earliest ts the next order can be executed
+    pub fn next_order_ts(&self) -> u64 {
+        if self.last_order_ts == 0 {
+            return self.start_ts;
+        }
+
+        self.last_order_ts
+            .saturating_add(self.interval_seconds)
+            .max(self.start_ts)
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.active && now >= self.next_order_ts() && self.end_ts.is_none_or(|end_ts| now <= end_ts)
+    }
+
+    /// Quote amount left to trade under `total_budget`, if set
+    pub fn remaining_budget(&self) -> Option<i128> {
+        self.total_budget
+            .map(|total_budget| total_budget.saturating_sub(self.total_executed).max(0))
+    }
+
+    /// Whether the order, budget or time limits leave no more orders to execute
+    pub fn is_exhausted(&self) -> bool {
+        let max_orders = match self.order_type {
+            OrderType::Recurring => self.max_orders,
+            _ => Some(self.max_orders.unwrap_or(1)),
+        };
+
+        max_orders.is_some_and(|max_orders| self.executed_orders >= max_orders)
+            || self
+                .total_budget
+                .is_some_and(|total_budget| self.total_executed >= total_budget)
+            || self
+                .end_ts
+                .is_some_and(|end_ts| self.next_order_ts() > end_ts)
+    }
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ScheduleInfo {
@@ -162,6 +268,121 @@ pub fn save_schedules(env: &Env, key: &Address, schedule_info: &ScheduleInfo) {
         PERSISTENT_LIFETIME_THRESHOLD,
         PERSISTENT_BUMP_AMOUNT,
     );
+
+    update_scheduled_users(env, key, schedule_info);
+}
+
+pub fn get_scheduled_user_count(env: &Env) -> u32 {
+    let count = env
+        .storage()
+        .persistent()
+        .get(&DataKey::ScheduledUserCount)
+        .unwrap_or(0);
+    env.storage()
+        .persistent()
+        .has(&DataKey::ScheduledUserCount)
+        .then(|| {
+            env.storage().persistent().extend_ttl(
+                &DataKey::ScheduledUserCount,
+                PERSISTENT_LIFETIME_THRESHOLD,
+                PERSISTENT_BUMP_AMOUNT,
+            );
+        });
+
+    count
+}
+
+/// Returns up to `limit` (capped at `MAX_QUERY_LIMIT`) users with an active
+/// schedule starting at `offset`
+pub fn get_scheduled_users(env: &Env, offset: u32, limit: u32) -> Vec<Address> {
+    let count = get_scheduled_user_count(env);
+    let start = offset.min(count);
+    let end = start.saturating_add(limit.min(MAX_QUERY_LIMIT)).min(count);
+
+    let mut users = Vec::new(env);
+    for position in start..end {
+        let key = ScheduledUserKey::User(position);
+        let user: Address = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("Scheduler: scheduled user not found");
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+        users.push_back(user);
+    }
+
+    users
+}
+
+fn save_scheduled_user(env: &Env, position: u32, user: &Address) {
+    let key = ScheduledUserKey::User(position);
+    env.storage().persistent().set(&key, user);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+
+    let key = ScheduledUserKey::Position(user.clone());
+    env.storage().persistent().set(&key, &position);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+fn save_scheduled_user_count(env: &Env, count: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::ScheduledUserCount, &count);
+    env.storage().persistent().extend_ttl(
+        &DataKey::ScheduledUserCount,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+/// Adds or removes `user` from the scheduled users, moving the last user into
+/// the freed position on removal
+fn update_scheduled_users(env: &Env, user: &Address, schedule_info: &ScheduleInfo) {
+    let position: Option<u32> = env
+        .storage()
+        .persistent()
+        .get(&ScheduledUserKey::Position(user.clone()));
+    let has_active_schedule = schedule_info.schedules.iter().any(|s| s.active);
+
+    match (position, has_active_schedule) {
+        (None, true) => {
+            let count = get_scheduled_user_count(env);
+            save_scheduled_user(env, count, user);
+            save_scheduled_user_count(env, count + 1);
+        }
+        (Some(position), false) => {
+            let last = get_scheduled_user_count(env) - 1;
+            if position != last {
+                let last_user: Address = env
+                    .storage()
+                    .persistent()
+                    .get(&ScheduledUserKey::User(last))
+                    .expect("Scheduler: scheduled user not found");
+                save_scheduled_user(env, position, &last_user);
+            }
+
+            env.storage()
+                .persistent()
+                .remove(&ScheduledUserKey::User(last));
+            env.storage()
+                .persistent()
+                .remove(&ScheduledUserKey::Position(user.clone()));
+            save_scheduled_user_count(env, last);
+        }
+        _ => {}
+    }
 }
 
 // ################################################################
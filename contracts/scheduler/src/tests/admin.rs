@@ -1,171 +1,204 @@
 use super::setup::{
-    deploy_index_token_factory_contract, deploy_market_factory_contract, deploy_scheduler_contract,
+    deploy_market_contract, deploy_scheduler_contract, deploy_token_contract, recurring_buy_params,
 };
-use crate::{contract::Scheduler, tests::setup::install_and_deploy_token_contract};
 
-use soroban_sdk::{
-    testutils::{arbitrary::std, Address as _},
-    vec, Address, Env, String,
-};
+use soroban_sdk::{testutils::Address as _, vec, Address, Env};
 
 #[test]
 fn scheduler_successfully_inits_itself() {
     let env = Env::default();
-    let admin = Address::generate(&env);
+    env.mock_all_auths();
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
-    let keepers = [Address::generate(&env)];
+    let admin = Address::generate(&env);
+    let market_factory = Address::generate(&env);
+    let index_factory = Address::generate(&env);
 
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
+        admin.clone(),
+        &market_factory,
+        &index_factory,
         500,
         200,
     );
 
-    assert_eq!(scheduler.get_admin(), admin);
+    assert_eq!(scheduler.query_admin(), admin);
+
+    let config = scheduler.query_config().config;
+    assert_eq!(config.synth_market_factory_address, market_factory);
+    assert_eq!(config.index_factory_address, index_factory);
+    assert_eq!(config.protocol_fee_bps, 500);
+    assert_eq!(config.keeper_fee_bps, 200);
 }
 
 #[test]
-#[should_panic(expected = "Insurance: Initialize: initializing contract twice is not allowed")]
-fn test_deploying_insurance_twice_should_fail() {
+#[should_panic(expected = "Error(Contract, #43)")]
+fn scheduler_initializing_twice_should_fail() {
     let env = Env::default();
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
-    let governor = Address::generate(&env);
-    let deposit_token = deploy_token_contract(&env, &admin);
+    let market_factory = Address::generate(&env);
+    let index_factory = Address::generate(&env);
 
-    let first = deploy_insurance_contract(
+    let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &deposit_token.address,
+        &market_factory,
+        &index_factory,
+        500,
+        200,
     );
 
-    first.initialize(
-        &admin,
-        &governor,
-        &deposit_token.address,
-        &100i128,
-        &10u32,
-        &String::from_str(&env, "Normal Insurance Fund Stake"),
-        &String::from_str(&env, "NIFS"),
-    );
+    scheduler.initialize(&admin, &market_factory, &index_factory, &500, &200);
 }
 
 #[test]
 fn scheduler_successfully_updates_config() {
     let env = Env::default();
-    let admin = Address::generate(&env);
+    env.mock_all_auths();
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
-    let keepers = [Address::generate(&env)];
+    let admin = Address::generate(&env);
+    let market_factory = Address::generate(&env);
+    let index_factory = Address::generate(&env);
 
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
         500,
         200,
     );
 
-    assert_eq!(scheduler.get_admin(), admin);
+    scheduler.update_config(
+        &admin,
+        &Some(market_factory.clone()),
+        &Some(index_factory.clone()),
+        &Some(100),
+        &None,
+    );
+
+    let config = scheduler.query_config().config;
+    assert_eq!(config.synth_market_factory_address, market_factory);
+    assert_eq!(config.index_factory_address, index_factory);
+    assert_eq!(config.protocol_fee_bps, 100);
+    assert_eq!(config.keeper_fee_bps, 200);
 }
 
 #[test]
-#[should_panic(expected = "Scheduler: You are not authorized!")]
+#[should_panic(expected = "Error(Contract, #36)")]
 fn scheduler_fails_to_update_config_if_not_admin() {
     let env = Env::default();
+    env.mock_all_auths();
+
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
-
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
         500,
         200,
     );
 
-    scheduler.update_config(user.clone(), "", "", "", "");
+    scheduler.update_config(&user, &None, &None, &Some(100), &None);
 }
 
 #[test]
 fn scheduler_successfully_updates_keepers() {
     let env = Env::default();
-    let admin = Address::generate(&env);
+    env.mock_all_auths();
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
+    let admin = Address::generate(&env);
+    let keeper_a = Address::generate(&env);
+    let keeper_b = Address::generate(&env);
 
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
         500,
         200,
     );
 
-    let keepers_to_add = [Address::generate(&env)];
-    let keepers_to_remove = [Address::generate(&env)];
-
-    scheduler.update_keepers(admin.clone(), keepers_to_add, keepers_to_remove);
+    scheduler.update_keepers(
+        &admin,
+        &vec![&env, keeper_a.clone(), keeper_b.clone()],
+        &vec![&env],
+    );
+    scheduler.update_keepers(&admin, &vec![&env], &vec![&env, keeper_a.clone()]);
 
     let config = scheduler.query_config();
 
-    assert_eq!(config.config.keepers, []);
+    assert_eq!(config.config.keepers, vec![&env, keeper_b]);
 }
 
 #[test]
-#[should_panic(expected = "Scheduler: You are not authorized!")]
+#[should_panic(expected = "Error(Contract, #36)")]
 fn scheduler_fails_to_update_keepers_if_not_admin() {
     let env = Env::default();
+    env.mock_all_auths();
+
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
-
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
         500,
         200,
     );
 
-    scheduler.update_keepers(user.clone(), [], []);
+    scheduler.update_keepers(&user, &vec![&env, user.clone()], &vec![&env]);
 }
 
 #[test]
 fn scheduler_successfully_distributes_protocol_fees() {
     let env = Env::default();
-    let admin = Address::generate(&env);
+    env.mock_all_auths();
 
-    let mut market_factory = deploy_market_factory_contract(&env, &admin);
-    let mut index_token_factory = deploy_index_token_factory_contract(&env, &admin);
-    let keepers = [Address::generate(&env)];
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
 
     let scheduler = deploy_scheduler_contract(
         &env,
-        Some(admin.clone()),
-        &market_factory.address,
-        &index_token_factory.address,
-        500,
-        200,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote.address, &market, 1_000_000, 3_600),
     );
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    scheduler.collect_protocol_fees(&admin, &treasury);
+
+    assert_eq!(quote.balance(&treasury), 10_000);
+    assert!(scheduler
+        .query_config()
+        .config
+        .protocol_fees_to_collect
+        .is_empty());
 
-    assert_eq!(scheduler.get_admin(), admin);
+    // Fees are only paid out once
+    scheduler.collect_protocol_fees(&admin, &treasury);
+    assert_eq!(quote.balance(&treasury), 10_000);
 }
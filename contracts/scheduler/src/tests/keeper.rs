@@ -1,13 +1,21 @@
 extern crate std;
 
+use normal::types::misc::OrderDirection;
 use pretty_assertions::assert_eq;
 use soroban_sdk::{
     symbol_short,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
-    vec, Address, Env, IntoVal, String, Symbol, Vec,
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Map,
 };
 
-use super::setup::{deploy_scheduler_contract, deploy_token_contract};
+use super::setup::{
+    deploy_index_contract, deploy_market_contract, deploy_oracle_contract,
+    deploy_scheduler_contract, deploy_token_contract, recurring_buy_params, set_oracle_price,
+    MockMarketClient,
+};
+use crate::storage::{
+    get_config, get_keeper, save_config, save_keeper, OrderType, ScheduleType, TriggerPriceSource,
+};
 
 #[test]
 fn execute_schedule() {
@@ -15,37 +23,693 @@ fn execute_schedule() {
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote.address, &market, 1_000_000, 3_600),
+    );
+
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(9_000_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(1_000_000)
+    );
+    assert_eq!(synth.balance(&scheduler.address), 1_000_000);
+
+    let schedule = scheduled.schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 1);
+    assert_eq!(schedule.total_executed, 1_000_000);
+    assert_eq!(schedule.last_order_ts, 1_000);
+}
+
+#[test]
+fn execute_schedule_buys_at_pool_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+    MockMarketClient::new(&env, &market).set_price(&4_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    // Buying at the max price still fills
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.max_price = Some(4_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(6_000_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(1_000_000)
+    );
+    assert_eq!(
+        scheduled.schedules.get(0).unwrap().total_executed,
+        4_000_000
+    );
+}
+
+#[test]
+fn execute_sell_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+    MockMarketClient::new(&env, &market).set_price(&4_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    synth.mint(&user, &2_000_000);
+    scheduler.deposit(&user, &synth.address, &2_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.direction = OrderDirection::Sell;
+    params.min_price = Some(4_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(1_000_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(4_000_000)
+    );
+    assert_eq!(synth.balance(&scheduler.address), 1_000_000);
+    assert_eq!(quote.balance(&scheduler.address), 4_000_000);
+
+    let schedule = scheduled.schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 1);
+    assert_eq!(schedule.total_executed, 4_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn buy_filled_above_max_price_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.max_price = Some(1_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    // The pool quotes 1.0 but only fills 95%, an effective price above the max
+    MockMarketClient::new(&env, &market).set_fill_bps(&9_500);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #26)")]
+fn sell_filled_below_min_price_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    synth.mint(&user, &2_000_000);
+    scheduler.deposit(&user, &synth.address, &2_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.direction = OrderDirection::Sell;
+    params.min_price = Some(1_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    MockMarketClient::new(&env, &market).set_fill_bps(&9_500);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+fn execute_index_mint_and_redeem_schedules() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let index = deploy_index_contract(&env, &quote.address, 2_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &index.address, 1_000_000, 3_600);
+    params.schedule_type = ScheduleType::Index;
+    params.max_price = Some(2_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    // Mints 1 index token at 2.0
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(8_000_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(index.address.clone()),
+        Some(1_000_000)
+    );
+    assert_eq!(index.balance(&scheduler.address), 1_000_000);
+    assert_eq!(quote.balance(&index.address), 2_000_000);
+    assert_eq!(
+        scheduled.schedules.get(0).unwrap().total_executed,
+        2_000_000
+    );
+
+    // Redeems half of it after the index price rises to 3.0
+    env.ledger().with_mut(|li| li.timestamp = 1);
+    quote.mint(&index.address, &1_000_000);
+    index.set_index_price(&3_000_000);
+    params.direction = OrderDirection::Sell;
+    params.base_asset_amount_per_interval = 500_000;
+    params.max_price = None;
+    params.min_price = Some(3_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &1);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(9_500_000)
+    );
+    assert_eq!(scheduled.balances.get(index.address.clone()), Some(500_000));
+    assert_eq!(index.balance(&scheduler.address), 500_000);
+    assert_eq!(quote.balance(&scheduler.address), 9_500_000);
+    assert_eq!(
+        scheduled.schedules.get(1).unwrap().total_executed,
+        1_500_000
+    );
+}
+
+#[test]
+fn index_mint_schedule_deducts_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let index = deploy_index_contract(&env, &quote.address, 2_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &index.address, 1_000_000, 3_600);
+    params.schedule_type = ScheduleType::Index;
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    // The 30_000 in fees on the 2_000_000 order leave 1_970_000 to mint with
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(8_000_000)
+    );
+    assert_eq!(scheduled.balances.get(index.address.clone()), Some(985_000));
+    assert_eq!(quote.balance(&index.address), 1_970_000);
+    assert_eq!(scheduled.schedules.get(0).unwrap().total_fees_paid, 30_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #34)")]
+fn index_schedule_for_blacklisted_user_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = deploy_token_contract(&env, &admin);
+    let index = deploy_index_contract(&env, &quote.address, 2_000_000);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &index.address, 1_000_000, 3_600);
+    params.schedule_type = ScheduleType::Index;
+    scheduler.create_schedule(&user, &params);
+
+    // The index access mode applies even though the scheduler holds the tokens
+    index.blacklist(&user);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
 }
 
 #[test]
-#[should_panic(expected = "Insurance: Initialize: initializing contract twice is not allowed")]
+#[should_panic(expected = "Error(Contract, #38)")]
+fn execute_schedule_twice_in_one_interval_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote.address, &market, 1_000_000, 3_600),
+    );
+
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+    env.ledger().with_mut(|li| li.timestamp = 4_599);
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+}
+
+#[test]
+fn schedule_waits_for_start_ts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.start_ts = Some(5_000);
+    scheduler.create_schedule(&user, &params);
+
+    env.ledger().with_mut(|li| li.timestamp = 4_999);
+    assert!(scheduler.query_due_schedules(&0, &10).is_empty());
+
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    assert_eq!(scheduler.query_due_schedules(&0, &10).len(), 1);
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+
+    let schedule = scheduler.query_scheduled(&user).schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 1);
+    assert_eq!(schedule.last_order_ts, 5_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #38)")]
+fn execute_schedule_before_start_ts_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.start_ts = Some(5_000);
+    scheduler.create_schedule(&user, &params);
+
+    env.ledger().with_mut(|li| li.timestamp = 4_999);
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #38)")]
+fn execute_schedule_after_end_ts_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.end_ts = Some(2_000);
+    scheduler.create_schedule(&user, &params);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+}
+
+#[test]
+fn schedule_stops_after_max_orders() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.max_orders = Some(2);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+    assert!(
+        scheduler
+            .query_scheduled(&user)
+            .schedules
+            .get(0)
+            .unwrap()
+            .active
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 4_600);
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+
+    let schedule = scheduler.query_scheduled(&user).schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 2);
+    assert!(!schedule.active);
+
+    env.ledger().with_mut(|li| li.timestamp = 8_200);
+    assert!(scheduler.query_due_schedules(&0, &10).is_empty());
+    assert_eq!(
+        scheduler
+            .query_scheduled(&user)
+            .balances
+            .get(synth.address.clone()),
+        Some(2_000_000)
+    );
+}
+
+#[test]
+fn last_order_is_clamped_to_total_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.total_budget = Some(2_500_000);
+    scheduler.create_schedule(&user, &params);
+
+    for ts in [1_000, 4_600, 8_200] {
+        env.ledger().with_mut(|li| li.timestamp = ts);
+        scheduler.execute_schedule(&keeper, &user, &1_000);
+    }
+
+    // The third order only spends the 500_000 left in the budget
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(7_500_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(2_500_000)
+    );
+
+    let schedule = scheduled.schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 3);
+    assert_eq!(schedule.total_executed, 2_500_000);
+    assert!(!schedule.active);
+}
+
+#[test]
+fn schedule_deactivates_when_next_order_is_past_end_ts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.end_ts = Some(4_000);
+    scheduler.create_schedule(&user, &params);
+
+    assert_eq!(scheduler.query_due_schedules(&0, &10).len(), 1);
+
+    // The next order would land after end_ts, so this one is the last
+    scheduler.execute_schedule(&keeper, &user, &1_000);
+
+    let schedule = scheduler.query_scheduled(&user).schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 1);
+    assert!(!schedule.active);
+
+    env.ledger().with_mut(|li| li.timestamp = 3_999);
+    assert!(scheduler.query_due_schedules(&0, &10).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #37)")]
 fn execute_invalid_schedule_should_fail() {
     let env = Env::default();
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
 
-    // ...
+    scheduler.execute_schedule(&keeper, &user, &0);
 }
 
 #[test]
@@ -54,14 +718,596 @@ fn collect_fees() {
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote.address, &market, 1_000_000, 3_600),
     );
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    scheduler.collect_keeper_fees(&keeper);
+
+    assert_eq!(quote.balance(&keeper), 5_000);
+}
+
+#[test]
+fn execute_schedule_accrues_fees_per_quote_asset() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote_a = deploy_token_contract(&env, &admin);
+    let quote_b = deploy_token_contract(&env, &admin);
+    let synth_a = deploy_token_contract(&env, &admin);
+    let synth_b = deploy_token_contract(&env, &admin);
+    let market_a = deploy_market_contract(&env, &synth_a, &quote_a, 10_000_000);
+    let market_b = deploy_market_contract(&env, &synth_b, &quote_b, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote_a.mint(&user, &10_000_000);
+    quote_b.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote_a.address, &10_000_000);
+    scheduler.deposit(&user, &quote_b.address, &10_000_000);
+
+    // Schedules are keyed by creation ts
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote_a.address, &market_a, 1_000_000, 3_600),
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote_b.address, &market_b, 2_000_000, 3_600),
+    );
+
+    // 1% protocol and 0.5% keeper fee come out of each order
+    scheduler.execute_schedule(&keeper, &user, &0);
+    scheduler.execute_schedule(&keeper, &user, &1);
+    env.ledger().with_mut(|li| li.timestamp = 3_601);
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote_a.address.clone()),
+        Some(10_000_000 - 2_000_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(quote_b.address.clone()),
+        Some(10_000_000 - 2_000_000)
+    );
+
+    // Only the quote left after fees is swapped
+    assert_eq!(
+        scheduled.balances.get(synth_a.address.clone()),
+        Some(2 * (1_000_000 - 15_000))
+    );
+    assert_eq!(
+        scheduled.balances.get(synth_b.address.clone()),
+        Some(2_000_000 - 30_000)
+    );
+    assert_eq!(
+        quote_a.balance(&market_a),
+        10_000_000 + 2 * (1_000_000 - 15_000)
+    );
+    assert_eq!(quote_b.balance(&market_b), 10_000_000 + 2_000_000 - 30_000);
+    assert_eq!(
+        scheduled.schedules.get(0).unwrap().total_executed,
+        2_000_000
+    );
+    assert_eq!(scheduled.schedules.get(0).unwrap().total_fees_paid, 30_000);
+    assert_eq!(scheduled.schedules.get(1).unwrap().total_fees_paid, 30_000);
+
+    let config = scheduler.query_config().config;
+    assert_eq!(
+        config.protocol_fees_to_collect.get(quote_a.address.clone()),
+        Some(20_000)
+    );
+    assert_eq!(
+        config.protocol_fees_to_collect.get(quote_b.address.clone()),
+        Some(20_000)
+    );
+
+    scheduler.collect_keeper_fees(&keeper);
+    assert_eq!(quote_a.balance(&keeper), 10_000);
+    assert_eq!(quote_b.balance(&keeper), 10_000);
+}
+
+#[test]
+fn limit_order_executes_once_when_triggered() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    // The pool trades 1:1, so a buy limit at 1.0 fires
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(1_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    let filled = scheduled.schedules.get(0).unwrap();
+    assert_eq!(filled.executed_orders, 1);
+    assert!(!filled.active);
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(1_000_000)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #48)")]
+fn limit_order_not_triggered_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    // The pool trades 1:1, above the 0.9 limit
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(900_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+fn limit_sell_executes_at_or_above_trigger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    synth.mint(&user, &1_000_000);
+    scheduler.deposit(&user, &synth.address, &1_000_000);
+
+    // Takes profit once the pool price doubles
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.direction = OrderDirection::Sell;
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(2_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    MockMarketClient::new(&env, &market).set_price(&4_000_000);
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(scheduled.balances.get(synth.address.clone()), Some(0));
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(4_000_000)
+    );
+
+    let schedule = scheduled.schedules.get(0).unwrap();
+    assert_eq!(schedule.executed_orders, 1);
+    assert!(!schedule.active);
+}
+
+#[test]
+fn stop_loss_sell_executes_at_or_below_trigger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    synth.mint(&user, &1_000_000);
+    scheduler.deposit(&user, &synth.address, &1_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.direction = OrderDirection::Sell;
+    params.order_type = OrderType::StopLoss;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(500_000);
+    scheduler.create_schedule(&user, &params);
+
+    MockMarketClient::new(&env, &market).set_price(&250_000);
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(scheduled.balances.get(synth.address.clone()), Some(0));
+    assert_eq!(scheduled.balances.get(quote.address.clone()), Some(250_000));
+    assert!(!scheduled.schedules.get(0).unwrap().active);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #48)")]
+fn stop_loss_sell_above_trigger_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    synth.mint(&user, &1_000_000);
+    scheduler.deposit(&user, &synth.address, &1_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.direction = OrderDirection::Sell;
+    params.order_type = OrderType::StopLoss;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(500_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+fn oracle_triggered_limit_buy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    let oracle = deploy_oracle_contract(&env);
+    set_oracle_price(&env, &oracle, symbol_short!("BTC"), 90_000_000);
+    MockMarketClient::new(&env, &market).set_oracle(&oracle.address);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    // Buys BTC below $95 on the oracle, whatever the pool quotes
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Oracle;
+    params.trigger_price = Some(95_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(synth.address.clone()),
+        Some(1_000_000)
+    );
+    assert_eq!(scheduled.schedules.get(0).unwrap().executed_orders, 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #48)")]
+fn oracle_limit_buy_above_trigger_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    let oracle = deploy_oracle_contract(&env);
+    set_oracle_price(&env, &oracle, symbol_short!("BTC"), 100_000_000);
+    MockMarketClient::new(&env, &market).set_oracle(&oracle.address);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    // The pool would trigger at 1.0, but the oracle is above $95
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Oracle;
+    params.trigger_price = Some(95_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+fn index_limit_buy_triggers_on_index_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let index = deploy_index_contract(&env, &quote.address, 2_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &index.address, 1_000_000, 3_600);
+    params.schedule_type = ScheduleType::Index;
+    params.order_type = OrderType::Limit;
+    params.trigger_price = Some(1_500_000);
+    scheduler.create_schedule(&user, &params);
+
+    index.set_index_price(&1_500_000);
+    scheduler.execute_schedule(&keeper, &user, &0);
+
+    let scheduled = scheduler.query_scheduled(&user);
+    assert_eq!(
+        scheduled.balances.get(quote.address.clone()),
+        Some(8_500_000)
+    );
+    assert_eq!(
+        scheduled.balances.get(index.address.clone()),
+        Some(1_000_000)
+    );
+    assert!(!scheduled.schedules.get(0).unwrap().active);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #39)")]
+fn filled_limit_order_does_not_repeat() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    quote.mint(&user, &10_000_000);
+    scheduler.deposit(&user, &quote.address, &10_000_000);
+
+    let mut params = recurring_buy_params(&quote.address, &market, 1_000_000, 3_600);
+    params.order_type = OrderType::Limit;
+    params.trigger_price_source = TriggerPriceSource::Pool;
+    params.trigger_price = Some(1_000_000);
+    scheduler.create_schedule(&user, &params);
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+    env.ledger().with_mut(|li| li.timestamp = 3_600);
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #20)")]
+fn order_with_fees_above_quote_balance_should_fail() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let user = Address::generate(&env);
+    let quote = deploy_token_contract(&env, &admin);
+    let synth = deploy_token_contract(&env, &admin);
+    let market = deploy_market_contract(&env, &synth, &quote, 10_000_000);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+    scheduler.update_keepers(&admin, &vec![&env, keeper.clone()], &vec![&env]);
+
+    // Enough for the order net of fees but not the order itself
+    quote.mint(&user, &999_999);
+    scheduler.deposit(&user, &quote.address, &999_999);
+    scheduler.create_schedule(
+        &user,
+        &recurring_buy_params(&quote.address, &market, 1_000_000, 3_600),
+    );
+
+    scheduler.execute_schedule(&keeper, &user, &0);
+}
+
+#[test]
+fn collect_multi_asset_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let quote_a = deploy_token_contract(&env, &admin);
+    let quote_b = deploy_token_contract(&env, &admin);
+
+    let scheduler = deploy_scheduler_contract(
+        &env,
+        admin.clone(),
+        &Address::generate(&env),
+        &Address::generate(&env),
+        100,
+        50,
+    );
+
+    quote_a.mint(&scheduler.address, &30_000);
+    quote_b.mint(&scheduler.address, &60_000);
+
+    env.as_contract(&scheduler.address, || {
+        let mut config = get_config(&env);
+        config
+            .protocol_fees_to_collect
+            .set(quote_a.address.clone(), 20_000);
+        config
+            .protocol_fees_to_collect
+            .set(quote_b.address.clone(), 40_000);
+        save_config(&env, config);
+
+        let mut keeper_info = get_keeper(&env, &keeper);
+        keeper_info.fees_owed = Map::new(&env);
+        keeper_info.fees_owed.set(quote_a.address.clone(), 10_000);
+        keeper_info.fees_owed.set(quote_b.address.clone(), 20_000);
+        save_keeper(&env, &keeper, &keeper_info);
+    });
+
+    scheduler.collect_protocol_fees(&admin, &treasury);
+
+    assert_eq!(quote_a.balance(&treasury), 20_000);
+    assert_eq!(quote_b.balance(&treasury), 40_000);
+    assert!(scheduler
+        .query_config()
+        .config
+        .protocol_fees_to_collect
+        .is_empty());
+
+    scheduler.collect_keeper_fees(&keeper);
+
+    assert_eq!(quote_a.balance(&keeper), 10_000);
+    assert_eq!(quote_b.balance(&keeper), 20_000);
+    assert_eq!(quote_a.balance(&scheduler.address), 0);
+    assert_eq!(quote_b.balance(&scheduler.address), 0);
+
+    // nothing left to pay out
+    scheduler.collect_protocol_fees(&admin, &treasury);
+    scheduler.collect_keeper_fees(&keeper);
+
+    assert_eq!(quote_a.balance(&treasury), 20_000);
+    assert_eq!(quote_b.balance(&keeper), 20_000);
 }
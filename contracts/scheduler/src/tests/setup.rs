@@ -1,6 +1,29 @@
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use normal::{
+    constants::{PRICE_PRECISION_I128, REFLECTOR_PRICE_RECORDS},
+    error::ErrorCode,
+    oracle::OracleSource,
+    reflector::{Asset, ReflectorClient},
+    testutils::{MockReflector, MockReflectorClient},
+    types::{market::MarketPriceData, misc::OrderDirection},
+};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, symbol_short, testutils::Address as _, Address, Env,
+    Symbol, Vec,
+};
 
-use crate::contract::{Scheduler, SchedulerClient};
+use crate::{
+    contract::{Scheduler, SchedulerClient},
+    storage::{OrderType, ScheduleParams, ScheduleType, TriggerPriceSource, USD},
+    token_contract,
+};
+
+pub fn deploy_token_contract<'a>(env: &Env, admin: &Address) -> token_contract::Client<'a> {
+    token_contract::Client::new(
+        env,
+        &env.register_stellar_asset_contract_v2(admin.clone())
+            .address(),
+    )
+}
 
 pub fn deploy_scheduler_contract<'a>(
     env: &Env,
@@ -17,9 +40,335 @@ pub fn deploy_scheduler_contract<'a>(
         &admin,
         market_factory,
         index_factory,
-        protocol_fee_bps,
-        keeper_fee_bps,
+        &protocol_fee_bps.into().unwrap_or(0),
+        &keeper_fee_bps.into().unwrap_or(0),
     );
 
     scheduler
 }
+
+/// Deploys a Reflector oracle quoting in USD
+pub fn deploy_oracle_contract<'a>(env: &Env) -> MockReflectorClient<'a> {
+    let oracle = MockReflectorClient::new(env, &env.register(MockReflector, ()));
+    oracle.initialize(&Asset::Other(USD), &6, &300);
+
+    oracle
+}
+
+/// Pushes enough identical records of `price` at the current ledger time for
+/// the oracle to be valid
+pub fn set_oracle_price(env: &Env, oracle: &MockReflectorClient, symbol: Symbol, price: i128) {
+    let asset = Asset::Other(symbol);
+    for _ in 0..REFLECTOR_PRICE_RECORDS {
+        oracle.set_price(&asset, &price, &env.ledger().timestamp());
+    }
+}
+
+/// Stands in for a synth market whose pool trades synth (token a) and quote
+/// (token b) at `price`, filling `fill_bps` of the quoted value
+#[contract]
+pub struct MockMarket;
+
+#[contractimpl]
+impl MockMarket {
+    pub fn __constructor(env: Env, synth_token: Address, quote_token: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("synth"), &synth_token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("quote"), &quote_token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &PRICE_PRECISION_I128);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fill_bps"), &10_000_i128);
+    }
+
+    /// precision: PRICE_PRECISION
+    pub fn set_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    pub fn set_fill_bps(env: Env, fill_bps: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fill_bps"), &fill_bps);
+    }
+
+    /// Prices the synth with a Reflector `oracle` instead of the pool
+    pub fn set_oracle(env: Env, oracle: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("oracle"), &oracle);
+    }
+
+    pub fn query_price_data(env: Env) -> MarketPriceData {
+        let synth_token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("synth"))
+            .unwrap();
+        let price: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap();
+
+        // sqrt(price) in Q64.64, exact for prices with a rational square root
+        let sqrt_price = ((price as u128) << 64)
+            .checked_div(PRICE_PRECISION_I128 as u128)
+            .unwrap()
+            .isqrt()
+            << 32;
+
+        let oracle: Option<Address> = env.storage().instance().get(&symbol_short!("oracle"));
+        let (oracle, oracle_source, oracle_price) = match oracle {
+            Some(oracle) => {
+                let oracle_price = ReflectorClient::new(&env, &oracle)
+                    .lastprice(&Asset::Other(symbol_short!("BTC")))
+                    .unwrap()
+                    .price;
+                (oracle, OracleSource::Reflector, oracle_price)
+            }
+            None => (env.current_contract_address(), OracleSource::Band, price),
+        };
+
+        MarketPriceData {
+            synth_token,
+            symbol: symbol_short!("BTC"),
+            oracle,
+            oracle_source,
+            last_oracle_price_twap: oracle_price as i64,
+            sqrt_price,
+            last_price_ts: env.ledger().timestamp(),
+            synth_supply: 0,
+            synth_decimals: 7,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap(
+        env: Env,
+        sender: Address,
+        amount: u64,
+        other_amount_threshold: u64,
+        _sqrt_price_limit: u128,
+        _amount_specified_is_input: bool,
+        a_to_b: bool,
+        _tick_array_indexes: Vec<i32>,
+    ) {
+        sender.require_auth();
+
+        let storage = env.storage().instance();
+        let synth_token: Address = storage.get(&symbol_short!("synth")).unwrap();
+        let quote_token: Address = storage.get(&symbol_short!("quote")).unwrap();
+        let price: i128 = storage.get(&symbol_short!("price")).unwrap();
+        let fill_bps: i128 = storage.get(&symbol_short!("fill_bps")).unwrap();
+
+        let amount = i128::from(amount);
+        let (offer_token, ask_token, ask_amount) = match a_to_b {
+            true => (
+                synth_token,
+                quote_token,
+                amount * price / PRICE_PRECISION_I128,
+            ),
+            false => (
+                quote_token,
+                synth_token,
+                amount * PRICE_PRECISION_I128 / price,
+            ),
+        };
+        let ask_amount = ask_amount * fill_bps / 10_000;
+
+        if ask_amount < i128::from(other_amount_threshold) {
+            panic_with_error!(&env, ErrorCode::SlippageToleranceExceeded);
+        }
+
+        let market = env.current_contract_address();
+        token_contract::Client::new(&env, &offer_token).transfer(&sender, &market, &amount);
+        token_contract::Client::new(&env, &ask_token).transfer(&market, &sender, &ask_amount);
+    }
+}
+
+/// Deploys a market trading `synth_token` against `quote_token`, funded with
+/// `liquidity` of each
+pub fn deploy_market_contract(
+    env: &Env,
+    synth_token: &token_contract::Client,
+    quote_token: &token_contract::Client,
+    liquidity: i128,
+) -> Address {
+    let market = env.register(
+        MockMarket,
+        (synth_token.address.clone(), quote_token.address.clone()),
+    );
+    synth_token.mint(&market, &liquidity);
+    quote_token.mint(&market, &liquidity);
+
+    market
+}
+
+/// Stands in for an index token minted and redeemed for quote at `price`
+#[contract]
+pub struct MockIndex;
+
+#[contractimpl]
+impl MockIndex {
+    pub fn initialize(env: Env, quote_token: Address, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("quote"), &quote_token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    /// precision: PRICE_PRECISION
+    pub fn set_index_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    pub fn blacklist(env: Env, account: Address) {
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("blocked"), account), &true);
+    }
+
+    pub fn check_account_access(env: Env, account: Address) {
+        if env
+            .storage()
+            .instance()
+            .has(&(symbol_short!("blocked"), account))
+        {
+            panic_with_error!(&env, ErrorCode::AccountBlacklisted);
+        }
+    }
+
+    pub fn query_price(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap()
+    }
+
+    pub fn query_quote_amount(env: Env, index_token_amount: i128) -> i128 {
+        index_token_amount * Self::query_price(env) / PRICE_PRECISION_I128
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("balance"), id))
+            .unwrap_or(0)
+    }
+
+    pub fn mint_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        max_quote_in: i128,
+        _deadline: u64,
+    ) {
+        sender.require_auth();
+
+        let quote_amount = Self::query_quote_amount(env.clone(), index_token_amount);
+        if quote_amount > max_quote_in {
+            panic_with_error!(&env, ErrorCode::SlippageToleranceExceeded);
+        }
+
+        let quote_token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("quote"))
+            .unwrap();
+        let index = env.current_contract_address();
+        token_contract::Client::new(&env, &quote_token).transfer_from(
+            &index,
+            &sender,
+            &index,
+            &quote_amount,
+        );
+
+        let balance = Self::balance(env.clone(), sender.clone());
+        env.storage().instance().set(
+            &(symbol_short!("balance"), sender),
+            &(balance + index_token_amount),
+        );
+    }
+
+    pub fn redeem_with_slippage(
+        env: Env,
+        sender: Address,
+        index_token_amount: i128,
+        min_quote_out: i128,
+        _deadline: u64,
+    ) {
+        sender.require_auth();
+
+        let quote_amount = Self::query_quote_amount(env.clone(), index_token_amount);
+        if quote_amount < min_quote_out {
+            panic_with_error!(&env, ErrorCode::SlippageToleranceExceeded);
+        }
+
+        let balance = Self::balance(env.clone(), sender.clone());
+        env.storage().instance().set(
+            &(symbol_short!("balance"), sender.clone()),
+            &(balance - index_token_amount),
+        );
+
+        let quote_token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("quote"))
+            .unwrap();
+        token_contract::Client::new(&env, &quote_token).transfer(
+            &env.current_contract_address(),
+            &sender,
+            &quote_amount,
+        );
+    }
+}
+
+/// Deploys an index priced at `price` in `quote_token`
+pub fn deploy_index_contract<'a>(
+    env: &Env,
+    quote_token: &Address,
+    price: i128,
+) -> MockIndexClient<'a> {
+    let index = MockIndexClient::new(env, &env.register(MockIndex, ()));
+    index.initialize(quote_token, &price);
+
+    index
+}
+
+/// A recurring asset order buying `amount` of the synth on `market` every `interval_seconds`
+pub fn recurring_buy_params(
+    quote_asset: &Address,
+    market: &Address,
+    amount: u64,
+    interval_seconds: u64,
+) -> ScheduleParams {
+    ScheduleParams {
+        schedule_type: ScheduleType::Asset,
+        quote_asset: quote_asset.clone(),
+        target_contract_address: market.clone(),
+        base_asset_amount_per_interval: amount,
+        direction: OrderDirection::Buy,
+        interval_seconds,
+        min_price: None,
+        max_price: None,
+        start_ts: None,
+        end_ts: None,
+        max_orders: None,
+        total_budget: None,
+        order_type: OrderType::Recurring,
+        trigger_price: None,
+        trigger_price_source: TriggerPriceSource::Oracle,
+    }
+}
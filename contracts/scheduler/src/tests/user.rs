@@ -1,14 +1,13 @@
 extern crate std;
 
-use normal::constants::{ONE_MILLION_QUOTE, THIRTEEN_DAY};
 use pretty_assertions::assert_eq;
 use soroban_sdk::{
     symbol_short,
     testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
-    vec, Address, Env, IntoVal, String, Symbol, Vec,
+    Address, Env, IntoVal, Symbol,
 };
 
-use super::setup::{deploy_scheduler_contract, deploy_token_contract};
+use super::setup::{deploy_scheduler_contract, deploy_token_contract, recurring_buy_params};
 
 #[test]
 fn deposit() {
@@ -17,23 +16,20 @@ fn deposit() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = deploy_token_contract(&env, &admin);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
 
-    env.ledger().with_mut(|li| {
-        li.timestamp = ONE_WEEK;
-    });
+    quote.mint(&user, &10_000);
 
-    stake_asset.mint(&user, &10_000);
-
-    scheduler.deposit(&user, &10_000);
+    scheduler.deposit(&user, &quote.address, &10_000);
 
     assert_eq!(
         env.auths(),
@@ -43,11 +39,11 @@ fn deposit() {
                 function: AuthorizedFunction::Contract((
                     scheduler.address.clone(),
                     Symbol::new(&env, "deposit"),
-                    (&user.clone(), 10_000i128).into_val(&env),
+                    (&user.clone(), &quote.address, 10_000i128).into_val(&env),
                 )),
                 sub_invocations: std::vec![AuthorizedInvocation {
                     function: AuthorizedFunction::Contract((
-                        stake_asset.address.clone(),
+                        quote.address.clone(),
                         symbol_short!("transfer"),
                         (&user, &scheduler.address.clone(), 10_000i128).into_val(&env),
                     )),
@@ -58,9 +54,8 @@ fn deposit() {
     );
 
     let schedules = scheduler.query_scheduled(&user);
-    assert_eq!(schedules.balances.get(stake_asset.address), 10_000);
-
-    //    ...
+    assert_eq!(schedules.balances.get(quote.address.clone()), Some(10_000));
+    assert_eq!(quote.balance(&scheduler.address), 10_000);
 }
 
 #[test]
@@ -70,38 +65,50 @@ fn withdraw() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = deploy_token_contract(&env, &admin);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
 
-    // ...
+    quote.mint(&user, &10_000);
+    scheduler.deposit(&user, &quote.address, &10_000);
+
+    scheduler.withdraw(&user, &quote.address, &4_000);
+
+    let schedules = scheduler.query_scheduled(&user);
+    assert_eq!(schedules.balances.get(quote.address.clone()), Some(6_000));
+    assert_eq!(quote.balance(&user), 4_000);
 }
 
 #[test]
-#[should_panic(expected = "Insurance: Initialize: initializing contract twice is not allowed")]
+#[should_panic(expected = "Error(Contract, #20)")]
 fn withdraw_more_than_balance_should_fail() {
     let env = Env::default();
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = deploy_token_contract(&env, &admin);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
 
-    // ...
+    quote.mint(&user, &10_000);
+    scheduler.deposit(&user, &quote.address, &10_000);
+
+    scheduler.withdraw(&user, &quote.address, &10_001);
 }
 
 #[test]
@@ -111,17 +118,34 @@ fn create_schedule() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = Address::generate(&env);
+    let market = Address::generate(&env);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
 
-    // ...
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    scheduler.create_schedule(&user, &recurring_buy_params(&quote, &market, 100, 3_600));
+
+    let schedules = scheduler.query_scheduled(&user).schedules;
+    assert_eq!(schedules.len(), 1);
+
+    let schedule = schedules.get(0).unwrap();
+    assert_eq!(schedule.target_contract_address, market);
+    assert_eq!(schedule.start_ts, 1_000);
+    assert_eq!(schedule.schedule_timestamp, 1_000);
+    assert!(schedule.active);
+
+    let due = scheduler.query_due_schedules(&0, &10);
+    assert_eq!(due.len(), 1);
+    assert_eq!(due.get(0).unwrap().user, user);
 }
 
 #[test]
@@ -131,15 +155,23 @@ fn delete_schedule() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
-    let stake_asset = deploy_token_contract(&env, &admin);
+    let quote = Address::generate(&env);
+    let market = Address::generate(&env);
 
     let scheduler = deploy_scheduler_contract(
         &env,
         admin.clone(),
-        governor.clone(),
-        &gov_token.address,
-        &stake_asset.address,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        None,
+        None,
     );
 
-    // ...
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    scheduler.create_schedule(&user, &recurring_buy_params(&quote, &market, 100, 3_600));
+    scheduler.delete_schedule(&user, &1_000);
+
+    assert!(scheduler.query_scheduled(&user).schedules.is_empty());
+    assert!(scheduler.query_due_schedules(&0, &10).is_empty());
 }
@@ -25,8 +25,17 @@ pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_L
 
 // FEES
 pub const MAX_INDEX_MANAGER_FEE_BPS: u32 = 1000; // Maximum fee: 10% (in basis points)
+pub const MAX_INDEX_PROTOCOL_FEE_BPS: i64 = 500; // Maximum fee: 5% (in basis points)
+pub const BPS_DENOMINATOR: i128 = 10_000; // 100% (in basis points)
 pub const SECONDS_IN_A_YEAR: u32 = 365 * 24 * 60 * 60 * 100;
 
+// INDEX
+pub const DEFAULT_INDEX_MAX_WEIGHT_CHANGE: i128 = 100_000; // 10% (PERCENTAGE_PRECISION)
+pub const DEFAULT_INDEX_MAX_TURNOVER: i128 = 250_000; // 25% (PERCENTAGE_PRECISION)
+pub const DEFAULT_INDEX_PERFORMANCE_FEE_PERIOD: u64 = 90 * 24 * 60 * 60; // Quarterly
+pub const DEFAULT_INDEX_DRIFT_TOLERANCE: i128 = 50_000; // 5% (PERCENTAGE_PRECISION)
+pub const DEFAULT_INDEX_MAX_SWAP_SLIPPAGE: i128 = 10_000; // 1% (PERCENTAGE_PRECISION)
+
 // PRECISIONS
 pub const AMM_RESERVE_PRECISION: u128 = 1_000_000_000; //expo = -9;
 pub const AMM_RESERVE_PRECISION_I128: i128 = AMM_RESERVE_PRECISION as i128;
@@ -62,6 +71,7 @@ pub const LIQUIDATION_PCT_PRECISION: u128 = 10_000;
 
 pub const SPOT_BALANCE_PRECISION: u128 = 1_000_000_000; // expo = -9
 pub const SPOT_BALANCE_PRECISION_U64: u64 = 1_000_000_000; // expo = -9
+pub const SPOT_BALANCE_PRECISION_EXP: u32 = 9;
 pub const SPOT_CUMULATIVE_INTEREST_PRECISION: u128 = 10_000_000_000; // expo = -10
 
 pub const PERCENTAGE_PRECISION: u128 = 1_000_000; // expo -6 (represents 100%)
@@ -216,6 +226,11 @@ pub const DEFAULT_QUOTE_ASSET_AMOUNT_TICK_SIZE: u64 =
 // FUNDING
 // pub const FUNDING_RATE_OFFSET_DENOMINATOR: i64 = 5000; // 5000 => 7.3% annualized rate for hourly funding
 
+// ORACLES
+pub const REFLECTOR_PRICE_RECORDS: u32 = 5; // records used to derive confidence
+pub const REFLECTOR_MIN_DATA_POINTS: u32 = 3;
+pub const MIN_FRESH_ORACLE_FEEDS_FOR_MEDIAN: u32 = 3;
+
 // ORDERS
 pub const AUCTION_DERIVE_PRICE_FRACTION: i64 = 200;
 
@@ -20,6 +20,34 @@ pub enum ErrorCode {
     CastingFailure = 18,
     FailedUnwrap = 19,
     InsufficientFunds = 20,
+    UnableToLoadOracle = 21,
+    InvalidOracle = 22,
+    StaleIndexComponentPrice = 23,
+    UnauthorizedParticipant = 24,
+    EmptyIndex = 25,
+    SlippageToleranceExceeded = 26,
+    DeadlineExceeded = 27,
+    InvalidIndexWeights = 28,
+    RebalanceWeightChangeTooLarge = 29,
+    RebalanceTurnoverTooLarge = 30,
+    UnauthorizedKeeper = 31,
+    TooSoonToCrystallize = 32,
+    AccountNotWhitelisted = 33,
+    AccountBlacklisted = 34,
+    RebalanceDriftWithinTolerance = 35,
+    NotAuthorized = 36,
+    ScheduleNotFound = 37,
+    ScheduleNotDue = 38,
+    ScheduleInactive = 39,
+    InvalidSchedule = 40,
+    InvalidPerformanceFeePeriod = 41,
+    InvalidAccessMode = 42,
+    AlreadyInitialized = 43,
+    InvalidRebalanceParams = 44,
+    IndexTokenNotFound = 45,
+    AdminNotSet = 46,
+    IndexFactoryOperationPaused = 47,
+    OrderNotTriggered = 48,
 }
 
 pub type NormalResult<T = ()> = core::result::Result<T, ErrorCode>;
@@ -0,0 +1,23 @@
+use soroban_sdk::{Address, Env, Symbol, Vec};
+
+use crate::oracle::OracleAggregation;
+
+pub struct OracleEvents {}
+
+impl OracleEvents {
+    /// Emitted when a price is read from a set of aggregated oracle feeds
+    ///
+    /// - topics - `["oracle_aggregation", symbol: Symbol]`
+    /// - data - `[aggregation: OracleAggregation, feeds_used: Vec<Address>, price: i64]`
+    pub fn oracle_aggregation(
+        env: &Env,
+        symbol: Symbol,
+        aggregation: OracleAggregation,
+        feeds_used: Vec<Address>,
+        price: i64,
+    ) {
+        let topics = (Symbol::new(env, "oracle_aggregation"), symbol);
+        env.events()
+            .publish(topics, (aggregation, feeds_used, price));
+    }
+}
@@ -2,12 +2,17 @@
 
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod macros;
 pub mod math;
 pub mod oracle;
+pub mod reflector;
 pub mod types;
 pub mod utils;
 
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
 pub mod band_std_reference {
     soroban_sdk::contractimport!(file = "../../dist/std_reference.wasm");
 }
@@ -1,15 +1,17 @@
-// use core::cmp::min;
+use core::cmp::min;
 
-// use soroban_sdk::Env;
+use soroban_sdk::Env;
+
+use crate::types::{auction::Auction, misc::OrderDirection};
+
+use super::safe_math::SafeMath;
 
 // use crate::{
 //     constants::AUCTION_DERIVE_PRICE_FRACTION,
-//     error::{self, NormalResult},
 //     oracle::OraclePriceData,
-//     types::{Auction, OrderDirection},
 // };
 
-// use super::{casting::Cast, safe_math::SafeMath};
+// use super::casting::Cast;
 
 // pub fn calculate_auction_prices(
 //     env: &Env,
@@ -77,84 +79,138 @@
 //     Ok((oracle_price, auction_end_price))
 // }
 
-// pub fn calculate_auction_price(
-//     env: &Env,
-//     auction: &Auction,
-//     now: u64,
-//     tick_size: u64,
-// ) -> NormalResult<u64> {
-//     let slots_elapsed = now.safe_sub(auction.start_ts, env)?;
-
-//     let delta_numerator = min(slots_elapsed, auction.duration);
-//     let delta_denominator = auction.duration;
-
-//     // let auction_start_price = auction.start_price.cast::<u64>(env)?;
-//     // let auction_end_price = auction.end_price.cast::<u64>(env)?;
-
-//     if delta_denominator == 0 {
-//         return standardize_price(env, auction.end_price, tick_size, auction.direction);
-//     }
-
-//     let price_delta = match auction.direction {
-//         OrderDirection::Buy => auction
-//             .end_price
-//             .safe_sub(auction.start_price, env)?
-//             .safe_mul(delta_numerator, env)?
-//             .safe_div(delta_denominator, env)?,
-//         OrderDirection::Sell => auction
-//             .start_price
-//             .safe_sub(auction.end_price, env)?
-//             .safe_mul(delta_numerator, env)?
-//             .safe_div(delta_denominator, env)?,
-//     };
-
-//     let price = match auction.direction {
-//         OrderDirection::Buy => auction.start_price.safe_add(price_delta, env)?,
-//         OrderDirection::Sell => auction.start_price.safe_sub(price_delta, env)?,
-//     };
-
-//     standardize_price(env, price, tick_size, auction.direction)
-// }
-
-// pub fn is_auction_complete(
-//     env: &Env,
-//     order_slot: u64,
-//     auction_duration: u64,
-//     now: u64,
-// ) -> NormalResult<bool> {
-//     if auction_duration == 0 {
-//         return Ok(true);
-//     }
-
-//     let slots_elapsed = now.safe_sub(order_slot, env)?;
-
-//     Ok(slots_elapsed > auction_duration)
-// }
-
-// // From orders.math
-
-// #[allow(clippy::unnecessary_lazy_evaluations)]
-// pub fn standardize_price(
-//     env: &Env,
-//     price: u64,
-//     tick_size: u64,
-//     direction: OrderDirection,
-// ) -> NormalResult<u64> {
-//     if price == 0 {
-//         return Ok(0);
-//     }
-
-//     let error_code = error::ErrorCode::MathError;
-//     let remainder = price
-//         .checked_rem_euclid(tick_size)
-//         .ok_or_else(|| error_code)?;
-
-//     if remainder == 0 {
-//         return Ok(price);
-//     }
-
-//     match direction {
-//         OrderDirection::Buy => price.safe_sub(remainder, env),
-//         OrderDirection::Sell => price.safe_add(tick_size, env)?.safe_sub(remainder, env),
-//     }
-// }
+pub fn calculate_auction_price(env: &Env, auction: &Auction, now: u64, tick_size: u64) -> u64 {
+    let slots_elapsed = now.safe_sub(auction.start_ts, env);
+
+    let delta_numerator = min(slots_elapsed, auction.duration);
+    let delta_denominator = auction.duration;
+
+    if delta_denominator == 0 {
+        return standardize_price(env, auction.end_price, tick_size, auction.direction);
+    }
+
+    let price_delta = match auction.direction {
+        OrderDirection::Buy => auction
+            .end_price
+            .safe_sub(auction.start_price, env)
+            .safe_mul(delta_numerator, env)
+            .safe_div(delta_denominator, env),
+        OrderDirection::Sell => auction
+            .start_price
+            .safe_sub(auction.end_price, env)
+            .safe_mul(delta_numerator, env)
+            .safe_div(delta_denominator, env),
+    };
+
+    let price = match auction.direction {
+        OrderDirection::Buy => auction.start_price.safe_add(price_delta, env),
+        OrderDirection::Sell => auction.start_price.safe_sub(price_delta, env),
+    };
+
+    standardize_price(env, price, tick_size, auction.direction)
+}
+
+pub fn is_auction_complete(env: &Env, start_ts: u64, auction_duration: u64, now: u64) -> bool {
+    if auction_duration == 0 {
+        return true;
+    }
+
+    let slots_elapsed = now.safe_sub(start_ts, env);
+
+    slots_elapsed > auction_duration
+}
+
+// From orders.math
+
+pub fn standardize_price(env: &Env, price: u64, tick_size: u64, direction: OrderDirection) -> u64 {
+    if price == 0 || tick_size <= 1 {
+        return price;
+    }
+
+    let remainder = price % tick_size;
+
+    if remainder == 0 {
+        return price;
+    }
+
+    match direction {
+        OrderDirection::Buy => price.safe_sub(remainder, env),
+        OrderDirection::Sell => price.safe_add(tick_size, env).safe_sub(remainder, env),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::Env;
+
+    use crate::{
+        math::auction::{calculate_auction_price, is_auction_complete},
+        types::{
+            auction::{Auction, AuctionLocation},
+            misc::OrderDirection,
+        },
+    };
+
+    fn sell_auction(duration: u64) -> Auction {
+        Auction {
+            amount: 1_000,
+            direction: OrderDirection::Sell,
+            location: AuctionLocation::Native,
+            duration,
+            start_ts: 100,
+            total_auctioned: 0,
+            start_price: 1_000_000,
+            end_price: 800_000,
+        }
+    }
+
+    #[test]
+    fn sell_auction_price_decays_to_end_price() {
+        let env = Env::default();
+        let auction = sell_auction(1_000);
+
+        assert_eq!(calculate_auction_price(&env, &auction, 100, 1), 1_000_000);
+        assert_eq!(calculate_auction_price(&env, &auction, 600, 1), 900_000);
+        assert_eq!(calculate_auction_price(&env, &auction, 1_100, 1), 800_000);
+        // price holds at the end price once the auction duration has passed
+        assert_eq!(calculate_auction_price(&env, &auction, 5_000, 1), 800_000);
+    }
+
+    #[test]
+    fn buy_auction_price_rises_to_end_price() {
+        let env = Env::default();
+        let auction = Auction {
+            direction: OrderDirection::Buy,
+            start_price: 800_000,
+            end_price: 1_000_000,
+            ..sell_auction(1_000)
+        };
+
+        assert_eq!(calculate_auction_price(&env, &auction, 350, 1), 850_000);
+        assert_eq!(calculate_auction_price(&env, &auction, 1_100, 1), 1_000_000);
+    }
+
+    #[test]
+    fn auction_price_rounds_to_tick_size() {
+        let env = Env::default();
+        let auction = sell_auction(1_000);
+
+        // sells round up, buys round down
+        assert_eq!(
+            calculate_auction_price(&env, &auction, 103, 1_000),
+            1_000_000
+        );
+        assert_eq!(calculate_auction_price(&env, &auction, 105, 1_000), 999_000);
+    }
+
+    #[test]
+    fn zero_duration_auction_is_complete_at_end_price() {
+        let env = Env::default();
+        let auction = sell_auction(0);
+
+        assert_eq!(calculate_auction_price(&env, &auction, 100, 1), 800_000);
+        assert!(is_auction_complete(&env, 100, 0, 100));
+        assert!(!is_auction_complete(&env, 100, 1_000, 1_100));
+        assert!(is_auction_complete(&env, 100, 1_000, 1_101));
+    }
+}
@@ -76,6 +76,7 @@ impl_cast!(u128, usize);
 
 impl_cast!(i128, i64);
 impl_cast!(i128, u128);
+impl_cast!(i128, u64);
 impl_cast!(i64, i32);
 impl_cast!(i64, i128);
 impl_cast!(i64, u64);
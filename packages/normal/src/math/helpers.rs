@@ -1,12 +1,14 @@
-use soroban_sdk::{panic_with_error, Env};
+use soroban_sdk::{panic_with_error, Env, U256};
 
 // use crate::error::{ self, NormalResult };
 
-use super::{
-    casting::Cast,
-    safe_math::{ErrorCode, SafeMath},
+use crate::{
+    constants::{BPS_DENOMINATOR, ONE_YEAR, PRICE_PRECISION},
+    error::ErrorCode,
 };
 
+use super::{casting::Cast, safe_math::SafeMath};
+
 #[allow(clippy::unnecessary_lazy_evaluations)]
 pub fn standardize_value_with_remainder_i128(
     env: &Env,
@@ -110,6 +112,48 @@ pub fn on_the_hour_update(env: &Env, now: i64, last_update_ts: i64, update_perio
     time_remaining_until_update
 }
 
+/// Converts a Q64.64 pool sqrt price into a price with PRICE_PRECISION.
+///
+/// Squaring is done in 256 bits so any u128 sqrt price is supported.
+pub fn sqrt_price_to_price(env: &Env, sqrt_price: u128) -> u128 {
+    let sqrt_price = U256::from_u128(env, sqrt_price);
+    let price = sqrt_price
+        .mul(&sqrt_price)
+        .shr(64)
+        .mul(&U256::from_u128(env, PRICE_PRECISION))
+        .shr(64);
+
+    match price.to_u128() {
+        Some(price) => price,
+        None => panic_with_error!(env, ErrorCode::MathError),
+    }
+}
+
+/// Rescales a token `amount` from `decimals` to `target_decimals`
+pub fn standardize_token_amount(
+    env: &Env,
+    amount: u128,
+    decimals: u32,
+    target_decimals: u32,
+) -> u128 {
+    if decimals > target_decimals {
+        amount.safe_div(10_u128.pow(decimals - target_decimals), env)
+    } else {
+        amount.safe_mul(10_u128.pow(target_decimals - decimals), env)
+    }
+}
+
+/// Pro-rata share of an annual `fee_bps` on `amount` for `elapsed` seconds
+pub fn annualize_fee_amount(env: &Env, amount: i128, fee_bps: i64, elapsed: u64) -> i128 {
+    amount
+        .safe_mul(fee_bps.cast::<i128>(env), env)
+        .safe_mul(elapsed.cast::<i128>(env), env)
+        .safe_div(
+            ONE_YEAR.cast::<i128>(env).safe_mul(BPS_DENOMINATOR, env),
+            env,
+        )
+}
+
 #[cfg(test)]
 #[allow(clippy::comparison_chain)]
 pub fn log10(n: u128) -> u128 {
@@ -133,3 +177,66 @@ pub fn log10_iter(n: u128) -> u128 {
 
     result
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constants::{ONE_YEAR, PRICE_PRECISION},
+        math::helpers::{annualize_fee_amount, sqrt_price_to_price, standardize_token_amount},
+    };
+    use soroban_sdk::Env;
+
+    #[test]
+    fn sqrt_price_to_price_scales_q64_64() {
+        let env = Env::default();
+        assert_eq!(sqrt_price_to_price(&env, 1 << 64), PRICE_PRECISION);
+        assert_eq!(sqrt_price_to_price(&env, 2 << 64), 4 * PRICE_PRECISION);
+        assert_eq!(sqrt_price_to_price(&env, 1 << 63), PRICE_PRECISION / 4);
+    }
+
+    #[test]
+    fn sqrt_price_to_price_handles_large_sqrt_prices() {
+        let env = Env::default();
+        // squaring this in 128 bits overflows
+        assert_eq!(
+            sqrt_price_to_price(&env, 1 << 90),
+            (1 << 52) * PRICE_PRECISION
+        );
+    }
+
+    #[test]
+    fn annualize_fee_amount_streams_pro_rata() {
+        let env = Env::default();
+        let supply = 1_000_000_000_000;
+        let one_year = ONE_YEAR as u64;
+
+        // 1% over a full year
+        assert_eq!(
+            annualize_fee_amount(&env, supply, 100, one_year),
+            10_000_000_000
+        );
+        // 1% over half a year
+        assert_eq!(
+            annualize_fee_amount(&env, supply, 100, one_year / 2),
+            5_000_000_000
+        );
+        // 2.5% over one day
+        assert_eq!(annualize_fee_amount(&env, supply, 250, 86_400), 68_493_150);
+        assert_eq!(annualize_fee_amount(&env, supply, 0, one_year), 0);
+        assert_eq!(annualize_fee_amount(&env, supply, 100, 0), 0);
+    }
+
+    #[test]
+    fn standardize_token_amount_rescales_decimals() {
+        let env = Env::default();
+        assert_eq!(
+            standardize_token_amount(&env, 1_000_000, 6, 9),
+            1_000_000_000
+        );
+        assert_eq!(standardize_token_amount(&env, 10_000_000, 7, 7), 10_000_000);
+        assert_eq!(
+            standardize_token_amount(&env, 1_000_000_000_000_000_000, 18, 9),
+            1_000_000_000
+        );
+    }
+}
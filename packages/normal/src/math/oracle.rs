@@ -1,6 +1,6 @@
 use core::{cmp::max, fmt};
 
-use soroban_sdk::{contracttype, log, Address, Env};
+use soroban_sdk::{contracttype, log, panic_with_error, Address, Env};
 
 use crate::math::casting::Cast;
 use crate::math::safe_math::SafeMath;
@@ -58,12 +58,18 @@ impl fmt::Display for OracleValidity {
 #[contracttype]
 #[derive(Clone, Copy, PartialEq, Debug, Eq)]
 pub enum NormalAction {
+    Deposit,
+    Withdraw,
+    Borrow,
+    Swap,
     Liquidate,
     MarginCalc,
     UpdateTwap,
     UpdateAMMCurve,
     OracleOrderPrice,
     IndexPricing,
+    IndexMint,
+    IndexRedeem,
 }
 
 pub fn is_oracle_valid_for_action(
@@ -72,6 +78,39 @@ pub fn is_oracle_valid_for_action(
 ) -> bool {
     let is_ok = match action {
         Some(action) => match action {
+            // deposits only reduce risk, the price is used for stats
+            NormalAction::Deposit => !matches!(oracle_validity, OracleValidity::NonPositive),
+            NormalAction::Withdraw => !matches!(
+                oracle_validity,
+                OracleValidity::NonPositive
+                    | OracleValidity::TooVolatile
+                    | OracleValidity::TooUncertain
+                    | OracleValidity::StaleForMargin
+            ),
+            NormalAction::Borrow => !matches!(
+                oracle_validity,
+                OracleValidity::NonPositive
+                    | OracleValidity::TooVolatile
+                    | OracleValidity::TooUncertain
+                    | OracleValidity::StaleForMargin
+                    | OracleValidity::InsufficientDataPoints
+            ),
+            NormalAction::Swap => matches!(
+                oracle_validity,
+                OracleValidity::Valid | OracleValidity::InsufficientDataPoints
+            ),
+            NormalAction::IndexMint => matches!(
+                oracle_validity,
+                OracleValidity::Valid | OracleValidity::StaleForAMM
+            ),
+            // redemptions stay open on a slightly stale feed so holders can exit
+            NormalAction::IndexRedeem => !matches!(
+                oracle_validity,
+                OracleValidity::NonPositive
+                    | OracleValidity::TooVolatile
+                    | OracleValidity::TooUncertain
+                    | OracleValidity::StaleForMargin
+            ),
             NormalAction::OracleOrderPrice => {
                 matches!(
                     oracle_validity,
@@ -110,6 +149,23 @@ pub fn is_oracle_valid_for_action(
     is_ok
 }
 
+/// Panics with the error code matching `oracle_validity` when it is not acceptable for `action`
+pub fn validate_oracle_for_action(
+    env: &Env,
+    oracle_validity: OracleValidity,
+    action: NormalAction,
+) {
+    if !is_oracle_valid_for_action(oracle_validity, Some(action)) {
+        log!(
+            env,
+            "Invalid oracle ({:?}) for action {:?}",
+            oracle_validity,
+            action
+        );
+        panic_with_error!(env, oracle_validity.get_error_code());
+    }
+}
+
 pub fn block_operation(// env: Env,
     // market_name: String,
     // oracle_price_data: &OraclePriceData,
@@ -206,14 +262,16 @@ pub fn oracle_validity(
 
     let is_oracle_price_nonpositive = oracle_price <= 0;
 
-    let is_oracle_price_too_volatile = oracle_price
-        .max(last_oracle_twap)
-        .safe_div(last_oracle_twap.min(oracle_price).max(1), env)
-        .gt(&valid_oracle_guard_rails.too_volatile_ratio);
+    // Without a twap yet (e.g. a new market) there is nothing to compare the first reading to
+    let is_oracle_price_too_volatile = last_oracle_twap > 0
+        && oracle_price
+            .max(last_oracle_twap)
+            .safe_div(last_oracle_twap.min(oracle_price).max(1), env)
+            .gt(&valid_oracle_guard_rails.too_volatile_ratio);
 
     let conf_pct_of_price = max(1, oracle_conf)
         .safe_mul(BID_ASK_SPREAD_PRECISION, env)
-        .safe_div(oracle_price.cast(env), env);
+        .safe_div(oracle_price.max(1).cast(env), env);
 
     // TooUncertain
     let is_conf_too_large = conf_pct_of_price.gt(&valid_oracle_guard_rails
@@ -291,3 +349,103 @@ pub fn oracle_validity(
 
     oracle_validity
 }
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::{testutils::Address as _, Address, Env};
+
+    use crate::{
+        math::oracle::{
+            is_oracle_valid_for_action, oracle_validity, validate_oracle_for_action, NormalAction,
+            OracleValidity,
+        },
+        oracle::{OracleGuardRails, OraclePriceData},
+    };
+
+    fn classify(env: &Env, price: i64, confidence: u64, delay: u64, twap: i64) -> OracleValidity {
+        oracle_validity(
+            env,
+            Address::generate(env),
+            twap,
+            &OraclePriceData {
+                price,
+                confidence,
+                delay,
+                has_sufficient_data_points: true,
+            },
+            &OracleGuardRails::default().validity,
+            1,
+            false,
+        )
+    }
+
+    #[test]
+    fn oracle_validity_classification() {
+        let env = Env::default();
+
+        assert_eq!(
+            classify(&env, 100_000, 1, 0, 100_000),
+            OracleValidity::Valid
+        );
+        assert_eq!(
+            classify(&env, 0, 1, 0, 100_000),
+            OracleValidity::NonPositive
+        );
+        assert_eq!(
+            classify(&env, 600_000, 1, 0, 100_000),
+            OracleValidity::TooVolatile
+        );
+        assert_eq!(classify(&env, 600_000, 1, 0, 0), OracleValidity::Valid);
+        assert_eq!(
+            classify(&env, 100_000, 5_000, 0, 100_000),
+            OracleValidity::TooUncertain
+        );
+        assert_eq!(
+            classify(&env, 100_000, 1, 121, 100_000),
+            OracleValidity::StaleForMargin
+        );
+        assert_eq!(
+            classify(&env, 100_000, 1, 11, 100_000),
+            OracleValidity::StaleForAMM
+        );
+    }
+
+    #[test]
+    fn oracle_validity_per_action() {
+        assert!(is_oracle_valid_for_action(
+            OracleValidity::StaleForMargin,
+            Some(NormalAction::Deposit)
+        ));
+        assert!(!is_oracle_valid_for_action(
+            OracleValidity::StaleForMargin,
+            Some(NormalAction::Withdraw)
+        ));
+        assert!(!is_oracle_valid_for_action(
+            OracleValidity::InsufficientDataPoints,
+            Some(NormalAction::Borrow)
+        ));
+        assert!(!is_oracle_valid_for_action(
+            OracleValidity::StaleForAMM,
+            Some(NormalAction::Swap)
+        ));
+        assert!(is_oracle_valid_for_action(
+            OracleValidity::StaleForAMM,
+            Some(NormalAction::IndexMint)
+        ));
+        assert!(is_oracle_valid_for_action(
+            OracleValidity::InsufficientDataPoints,
+            Some(NormalAction::IndexRedeem)
+        ));
+        assert!(!is_oracle_valid_for_action(
+            OracleValidity::TooVolatile,
+            Some(NormalAction::Liquidate)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #12)")]
+    fn invalid_oracle_for_action_fails_with_typed_error() {
+        let env = Env::default();
+        validate_oracle_for_action(&env, OracleValidity::StaleForMargin, NormalAction::Borrow);
+    }
+}
@@ -1,10 +1,16 @@
+use crate::error::{ErrorCode, NormalResult};
+use crate::events::OracleEvents;
 use crate::math::casting::Cast;
 use crate::math::safe_math::SafeMath;
+use crate::reflector::{Asset, ReflectorClient};
 use crate::{
     band_std_reference,
-    constants::{PERCENTAGE_PRECISION_U64, PRICE_PRECISION_I64},
+    constants::{
+        MIN_FRESH_ORACLE_FEEDS_FOR_MEDIAN, PERCENTAGE_PRECISION_U64, PRICE_PRECISION,
+        PRICE_PRECISION_I64, REFLECTOR_MIN_DATA_POINTS, REFLECTOR_PRICE_RECORDS,
+    },
 };
-use soroban_sdk::{contracttype, log, Address, Env, Symbol, Vec};
+use soroban_sdk::{contracttype, log, panic_with_error, Address, Env, Symbol, Vec};
 
 #[contracttype]
 #[derive(Default, Clone, Copy, Eq, PartialEq, Debug)]
@@ -57,13 +63,49 @@ impl HistoricalOracleData {
             ..HistoricalOracleData::default()
         }
     }
+
+    /// Records a new oracle reading and folds it into the twap over `twap_period` seconds
+    pub fn update(
+        &mut self,
+        env: &Env,
+        oracle_price_data: &OraclePriceData,
+        now: u64,
+        twap_period: u64,
+    ) {
+        self.last_oracle_price_twap = if self.last_oracle_price_twap_ts == 0 {
+            oracle_price_data.price
+        } else {
+            let since_last = now
+                .safe_sub(self.last_oracle_price_twap_ts, env)
+                .min(twap_period);
+            let from_start = twap_period.safe_sub(since_last, env);
+
+            oracle_price_data
+                .price
+                .cast::<i128>(env)
+                .safe_mul(since_last.cast(env), env)
+                .safe_add(
+                    self.last_oracle_price_twap
+                        .cast::<i128>(env)
+                        .safe_mul(from_start.cast(env), env),
+                    env,
+                )
+                .safe_div(twap_period.max(1).cast(env), env)
+                .cast::<i64>(env)
+        };
+
+        self.last_oracle_price = oracle_price_data.price;
+        self.last_oracle_conf = oracle_price_data.confidence;
+        self.last_oracle_delay = oracle_price_data.delay;
+        self.last_oracle_price_twap_ts = now;
+    }
 }
 
 #[contracttype]
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum OracleSource {
     Band, // (https://github.com/bandprotocol/band-std-reference-contracts-soroban/tree/main)
-          // Reflector, // (https://github.com/reflector-network/reflector-contract)
+    Reflector, // (https://github.com/reflector-network/reflector-contract)
           // QuoteAsset,
 }
 
@@ -83,8 +125,207 @@ pub fn get_oracle_price(
     symbol_pair: (Symbol, Symbol),
     now: u64,
 ) -> OraclePriceData {
+    match try_get_oracle_price(env, oracle_source, price_oracle_address, symbol_pair, now) {
+        Ok(oracle_price_data) => oracle_price_data,
+        Err(error_code) => panic_with_error!(env, error_code),
+    }
+}
+
+/// Reads an oracle without aborting when it can't be loaded, so a dead feed can be skipped
+pub fn try_get_oracle_price(
+    env: &Env,
+    oracle_source: &OracleSource,
+    price_oracle_address: &Address,
+    symbol_pair: (Symbol, Symbol),
+    now: u64,
+) -> NormalResult<OraclePriceData> {
     match oracle_source {
-        OracleSource::Band => get_band_price(env, price_oracle_address, symbol_pair, now),
+        OracleSource::Band => try_get_band_price(env, price_oracle_address, symbol_pair, now),
+        OracleSource::Reflector => {
+            try_get_reflector_price(env, price_oracle_address, symbol_pair, now)
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleFeed {
+    pub source: OracleSource,
+    pub address: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OracleAggregation {
+    /// enough feeds were fresh, the median of their prices was used
+    Median,
+    /// too few feeds were fresh, the first fresh feed (in priority order) was used
+    Fallback,
+    /// no feed was fresh, the primary feed was used as-is
+    Stale,
+}
+
+/// Reads every feed and aggregates them into a single price.
+///
+/// Feeds are in priority order (primary first). Feeds that can't be read are skipped. A feed is
+/// fresh when its price is positive and its delay is within `slots_before_stale_for_margin`.
+/// When at least `MIN_FRESH_ORACLE_FEEDS_FOR_MEDIAN` feeds are fresh the median is returned,
+/// otherwise the first fresh feed is used. If no feed is fresh the first readable feed is
+/// returned so the caller's validity checks reject it. Fails only when no feed can be read.
+pub fn get_aggregated_oracle_price(
+    env: &Env,
+    feeds: &Vec<OracleFeed>,
+    symbol_pair: (Symbol, Symbol),
+    now: u64,
+    validity_guard_rails: &ValidityGuardRails,
+) -> OraclePriceData {
+    if feeds.is_empty() {
+        log!(env, "No oracle feeds configured");
+        panic_with_error!(env, ErrorCode::InvalidOracle);
+    }
+
+    let max_delay = validity_guard_rails
+        .slots_before_stale_for_margin
+        .cast::<u64>(env);
+
+    let mut first_readable: Option<(Address, OraclePriceData)> = None;
+    let mut fresh_feeds: Vec<Address> = Vec::new(env);
+    let mut fresh_prices: Vec<OraclePriceData> = Vec::new(env);
+
+    for feed in feeds.iter() {
+        let oracle_price_data = match try_get_oracle_price(
+            env,
+            &feed.source,
+            &feed.address,
+            symbol_pair.clone(),
+            now,
+        ) {
+            Ok(oracle_price_data) => oracle_price_data,
+            Err(_) => {
+                log!(env, "Skipping unreadable oracle feed {:?}", feed.address);
+                continue;
+            }
+        };
+
+        if first_readable.is_none() {
+            first_readable = Some((feed.address.clone(), oracle_price_data));
+        }
+
+        if oracle_price_data.price > 0 && oracle_price_data.delay <= max_delay {
+            fresh_feeds.push_back(feed.address);
+            fresh_prices.push_back(oracle_price_data);
+        }
+    }
+
+    let (first_readable_feed, first_readable_price) = match first_readable {
+        Some(first_readable) => first_readable,
+        None => {
+            log!(env, "No oracle feed could be read");
+            panic_with_error!(env, ErrorCode::UnableToLoadOracle);
+        }
+    };
+
+    let (aggregation, feeds_used, oracle_price_data) =
+        if fresh_prices.len() >= MIN_FRESH_ORACLE_FEEDS_FOR_MEDIAN {
+            (
+                OracleAggregation::Median,
+                fresh_feeds,
+                calculate_median_oracle_price(env, &fresh_prices),
+            )
+        } else if !fresh_prices.is_empty() {
+            log!(
+                env,
+                "Only {} fresh oracle feeds, falling back to {:?}",
+                fresh_prices.len(),
+                fresh_feeds.get_unchecked(0)
+            );
+            (
+                OracleAggregation::Fallback,
+                Vec::from_array(env, [fresh_feeds.get_unchecked(0)]),
+                fresh_prices.get_unchecked(0),
+            )
+        } else {
+            log!(env, "No fresh oracle feeds");
+            (
+                OracleAggregation::Stale,
+                Vec::from_array(env, [first_readable_feed]),
+                first_readable_price,
+            )
+        };
+
+    OracleEvents::oracle_aggregation(
+        env,
+        symbol_pair.0,
+        aggregation,
+        feeds_used,
+        oracle_price_data.price,
+    );
+
+    oracle_price_data
+}
+
+/// Reads the primary oracle on its own, or aggregated with `oracle_feeds` when any are configured
+pub fn get_oracle_price_with_feeds(
+    env: &Env,
+    oracle_source: &OracleSource,
+    price_oracle_address: &Address,
+    oracle_feeds: &Vec<OracleFeed>,
+    symbol_pair: (Symbol, Symbol),
+    now: u64,
+    validity_guard_rails: &ValidityGuardRails,
+) -> OraclePriceData {
+    if oracle_feeds.is_empty() {
+        return get_oracle_price(env, oracle_source, price_oracle_address, symbol_pair, now);
+    }
+
+    let mut feeds = Vec::from_array(
+        env,
+        [OracleFeed {
+            source: oracle_source.clone(),
+            address: price_oracle_address.clone(),
+        }],
+    );
+    feeds.append(oracle_feeds);
+
+    get_aggregated_oracle_price(env, &feeds, symbol_pair, now, validity_guard_rails)
+}
+
+fn calculate_median_oracle_price(env: &Env, prices: &Vec<OraclePriceData>) -> OraclePriceData {
+    let mut sorted: Vec<i64> = Vec::new(env);
+    for oracle_price_data in prices.iter() {
+        let index = sorted
+            .iter()
+            .position(|price| price > oracle_price_data.price)
+            .unwrap_or(sorted.len() as usize);
+        sorted.insert(index as u32, oracle_price_data.price);
+    }
+
+    let mid = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        sorted
+            .get_unchecked(mid - 1)
+            .safe_add(sorted.get_unchecked(mid), env)
+            .safe_div(2, env)
+    } else {
+        sorted.get_unchecked(mid)
+    };
+
+    // Widen the confidence by how far each feed sits from the median
+    let mut confidence: u64 = 0;
+    let mut delay: u64 = 0;
+    let mut has_sufficient_data_points = true;
+    for oracle_price_data in prices.iter() {
+        let spread = oracle_price_data.price.safe_sub(median, env).unsigned_abs();
+        confidence = confidence.max(oracle_price_data.confidence.safe_add(spread, env));
+        delay = delay.max(oracle_price_data.delay);
+        has_sufficient_data_points &= oracle_price_data.has_sufficient_data_points;
+    }
+
+    OraclePriceData {
+        price: median,
+        confidence,
+        delay,
+        has_sufficient_data_points,
     }
 }
 
@@ -118,15 +359,30 @@ pub fn get_band_price(
     env: &Env,
     oracle_contract_address: &Address,
     symbol_pair: (Symbol, Symbol),
-    now: u64, // multiple: u128,
+    now: u64,
 ) -> OraclePriceData {
-    let client = band_std_reference::Client::new(env, oracle_contract_address);
+    match try_get_band_price(env, oracle_contract_address, symbol_pair, now) {
+        Ok(oracle_price_data) => oracle_price_data,
+        Err(error_code) => panic_with_error!(env, error_code),
+    }
+}
 
-    let reference_datum = client
-        .get_reference_data(&Vec::from_array(env, [symbol_pair]))
-        .get_unchecked(0);
+pub fn try_get_band_price(
+    env: &Env,
+    oracle_contract_address: &Address,
+    symbol_pair: (Symbol, Symbol),
+    now: u64, // multiple: u128,
+) -> NormalResult<OraclePriceData> {
+    let client = band_std_reference::Client::new(env, oracle_contract_address);
 
-    //  or(Err(crate::error::ErrorCode::UnableToLoadOracle))?;
+    let reference_datum = match client.try_get_reference_data(&Vec::from_array(env, [symbol_pair]))
+    {
+        Ok(Ok(reference_data)) if !reference_data.is_empty() => reference_data.get_unchecked(0),
+        _ => {
+            log!(env, "Unable to load Band price");
+            return Err(ErrorCode::UnableToLoadOracle);
+        }
+    };
 
     let oracle_price = reference_datum.rate;
     // let oracle_conf: u64;
@@ -168,12 +424,118 @@ pub fn get_band_price(
 
     let oracle_delay: u64 = now.safe_sub(published_slot, env);
 
-    OraclePriceData {
+    Ok(OraclePriceData {
         price: oracle_price_scaled,
         confidence: 1, // oracle_conf_scaled,
         delay: oracle_delay,
         has_sufficient_data_points,
+    })
+}
+
+pub fn get_reflector_price(
+    env: &Env,
+    oracle_contract_address: &Address,
+    symbol_pair: (Symbol, Symbol),
+    now: u64,
+) -> OraclePriceData {
+    match try_get_reflector_price(env, oracle_contract_address, symbol_pair, now) {
+        Ok(oracle_price_data) => oracle_price_data,
+        Err(error_code) => panic_with_error!(env, error_code),
+    }
+}
+
+pub fn try_get_reflector_price(
+    env: &Env,
+    oracle_contract_address: &Address,
+    symbol_pair: (Symbol, Symbol),
+    now: u64,
+) -> NormalResult<OraclePriceData> {
+    let client = ReflectorClient::new(env, oracle_contract_address);
+
+    let (base_symbol, quote_symbol) = symbol_pair;
+    let base_asset = Asset::Other(base_symbol);
+    let quote_asset = Asset::Other(quote_symbol);
+
+    let oracle_base = match client.try_base() {
+        Ok(Ok(oracle_base)) => oracle_base,
+        _ => {
+            log!(env, "Unable to load Reflector base asset");
+            return Err(ErrorCode::UnableToLoadOracle);
+        }
+    };
+
+    // Prices are newest first. Pairs not quoted in the oracle base asset are
+    // resolved through the cross price endpoint.
+    let prices = if oracle_base == quote_asset {
+        client.try_prices(&base_asset, &REFLECTOR_PRICE_RECORDS)
+    } else {
+        client.try_x_prices(&base_asset, &quote_asset, &REFLECTOR_PRICE_RECORDS)
+    };
+
+    let prices = match prices {
+        Ok(Ok(Some(prices))) if !prices.is_empty() => prices,
+        _ => {
+            log!(env, "Unable to load Reflector price");
+            return Err(ErrorCode::UnableToLoadOracle);
+        }
+    };
+
+    let latest = prices.get_unchecked(0);
+
+    let decimals = match client.try_decimals() {
+        Ok(Ok(decimals)) => decimals,
+        _ => {
+            log!(env, "Unable to load Reflector decimals");
+            return Err(ErrorCode::UnableToLoadOracle);
+        }
+    };
+
+    let oracle_precision = match 10_u128.checked_pow(decimals) {
+        Some(precision) => precision,
+        None => {
+            log!(env, "Reflector decimals out of range");
+            return Err(ErrorCode::InvalidOracle);
+        }
+    };
+
+    let mut oracle_scale_mult = 1;
+    let mut oracle_scale_div = 1;
+
+    if oracle_precision > PRICE_PRECISION {
+        oracle_scale_div = oracle_precision.safe_div(PRICE_PRECISION, env);
+    } else {
+        oracle_scale_mult = PRICE_PRECISION.safe_div(oracle_precision, env);
+    }
+
+    let oracle_price_scaled = latest
+        .price
+        .safe_mul(oracle_scale_mult.cast(env), env)
+        .safe_div(oracle_scale_div.cast(env), env)
+        .cast::<i64>(env);
+
+    // Reflector does not publish a confidence interval, so use the widest
+    // deviation of the recent records from the latest price instead
+    let mut oracle_conf: u128 = 0;
+    for record in prices.iter() {
+        oracle_conf = oracle_conf.max(record.price.safe_sub(latest.price, env).unsigned_abs());
     }
+
+    let oracle_conf_scaled = oracle_conf
+        .safe_mul(oracle_scale_mult, env)
+        .safe_div(oracle_scale_div, env)
+        .cast::<u64>(env);
+
+    let has_sufficient_data_points = prices.len() >= REFLECTOR_MIN_DATA_POINTS;
+
+    // A feed timestamped ahead of the ledger is treated as current
+    let oracle_delay: u64 = now.saturating_sub(latest.timestamp);
+
+    Ok(OraclePriceData {
+        price: oracle_price_scaled,
+        confidence: oracle_conf_scaled,
+        delay: oracle_delay,
+        has_sufficient_data_points,
+    })
 }
 
 #[contracttype]
@@ -348,3 +710,308 @@ pub struct ValidityGuardRails {
 
 //     Ok((oracle_price_data, validity_guard_rails))
 // }
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Events, Ledger},
+        Env, FromVal, IntoVal, Symbol,
+    };
+
+    use soroban_sdk::{Address, Vec};
+
+    use crate::{
+        oracle::{
+            get_aggregated_oracle_price, get_oracle_price, OracleAggregation, OracleFeed,
+            OracleGuardRails, OracleSource,
+        },
+        reflector::Asset,
+        testutils::{MockReflector, MockReflectorClient},
+    };
+
+    fn deploy_reflector_feed(env: &Env, price: i128, timestamp: u64) -> OracleFeed {
+        let oracle = MockReflectorClient::new(env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &6, &300);
+        oracle.set_price(&Asset::Other(symbol_short!("XLM")), &price, &timestamp);
+
+        OracleFeed {
+            source: OracleSource::Reflector,
+            address: oracle.address,
+        }
+    }
+
+    fn feed_addresses(env: &Env, feeds: &[&OracleFeed]) -> Vec<Address> {
+        let mut addresses = Vec::new(env);
+        for feed in feeds {
+            addresses.push_back(feed.address.clone());
+        }
+        addresses
+    }
+
+    #[test]
+    fn reflector_price_is_rescaled_to_price_precision() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let oracle = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &14, &300);
+
+        let xlm = Asset::Other(symbol_short!("XLM"));
+        oracle.set_price(&xlm, &11_900_000_000_000, &400);
+        oracle.set_price(&xlm, &12_100_000_000_000, &700);
+        oracle.set_price(&xlm, &12_000_000_000_000, &970);
+
+        let oracle_price_data = get_oracle_price(
+            &env,
+            &OracleSource::Reflector,
+            &oracle.address,
+            (symbol_short!("XLM"), symbol_short!("USD")),
+            env.ledger().timestamp(),
+        );
+
+        assert_eq!(oracle_price_data.price, 120_000);
+        assert_eq!(oracle_price_data.confidence, 1_000);
+        assert_eq!(oracle_price_data.delay, 30);
+        assert!(oracle_price_data.has_sufficient_data_points);
+    }
+
+    #[test]
+    fn reflector_cross_price_without_enough_records() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let oracle = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &4, &300);
+
+        oracle.set_price(&Asset::Other(symbol_short!("BTC")), &600_000_000, &990);
+        oracle.set_price(&Asset::Other(symbol_short!("ETH")), &30_000_000, &980);
+
+        let oracle_price_data = get_oracle_price(
+            &env,
+            &OracleSource::Reflector,
+            &oracle.address,
+            (symbol_short!("BTC"), symbol_short!("ETH")),
+            env.ledger().timestamp(),
+        );
+
+        assert_eq!(oracle_price_data.price, 20_000_000);
+        assert_eq!(oracle_price_data.confidence, 0);
+        assert_eq!(oracle_price_data.delay, 20);
+        assert!(!oracle_price_data.has_sufficient_data_points);
+    }
+
+    #[test]
+    fn reflector_price_ahead_of_ledger_has_no_delay() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let oracle = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &6, &300);
+        oracle.set_price(&Asset::Other(symbol_short!("XLM")), &120_000, &1_060);
+
+        let oracle_price_data = get_oracle_price(
+            &env,
+            &OracleSource::Reflector,
+            &oracle.address,
+            (symbol_short!("XLM"), symbol_short!("USD")),
+            env.ledger().timestamp(),
+        );
+
+        assert_eq!(oracle_price_data.price, 120_000);
+        assert_eq!(oracle_price_data.delay, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #21)")]
+    fn reflector_missing_price_fails() {
+        let env = Env::default();
+
+        let oracle = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        oracle.initialize(&Asset::Other(symbol_short!("USD")), &14, &300);
+
+        get_oracle_price(
+            &env,
+            &OracleSource::Reflector,
+            &oracle.address,
+            (symbol_short!("XLM"), symbol_short!("USD")),
+            0,
+        );
+    }
+
+    #[test]
+    fn aggregated_price_uses_median_of_fresh_feeds() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let a = deploy_reflector_feed(&env, 120_000, 990);
+        let b = deploy_reflector_feed(&env, 125_000, 995);
+        let c = deploy_reflector_feed(&env, 119_000, 980);
+        let feeds = Vec::from_array(&env, [a.clone(), b.clone(), c.clone()]);
+
+        let consumer = env.register(MockReflector, ());
+        let oracle_price_data = env.as_contract(&consumer, || {
+            get_aggregated_oracle_price(
+                &env,
+                &feeds,
+                (symbol_short!("XLM"), symbol_short!("USD")),
+                env.ledger().timestamp(),
+                &OracleGuardRails::default().validity,
+            )
+        });
+
+        assert_eq!(oracle_price_data.price, 120_000);
+        assert_eq!(oracle_price_data.confidence, 5_000);
+        assert_eq!(oracle_price_data.delay, 20);
+
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            topics,
+            (
+                Symbol::new(&env, "oracle_aggregation"),
+                symbol_short!("XLM")
+            )
+                .into_val(&env)
+        );
+        assert_eq!(
+            <(OracleAggregation, Vec<Address>, i64)>::from_val(&env, &data),
+            (
+                OracleAggregation::Median,
+                feed_addresses(&env, &[&a, &b, &c]),
+                120_000_i64
+            )
+        );
+    }
+
+    #[test]
+    fn aggregated_price_falls_back_when_primary_is_stale() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let stale = deploy_reflector_feed(&env, 150_000, 100);
+        let fresh = deploy_reflector_feed(&env, 121_000, 990);
+        let feeds = Vec::from_array(&env, [stale, fresh.clone()]);
+
+        let consumer = env.register(MockReflector, ());
+        let oracle_price_data = env.as_contract(&consumer, || {
+            get_aggregated_oracle_price(
+                &env,
+                &feeds,
+                (symbol_short!("XLM"), symbol_short!("USD")),
+                env.ledger().timestamp(),
+                &OracleGuardRails::default().validity,
+            )
+        });
+
+        assert_eq!(oracle_price_data.price, 121_000);
+        assert_eq!(oracle_price_data.delay, 10);
+
+        let (_, _, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            <(OracleAggregation, Vec<Address>, i64)>::from_val(&env, &data),
+            (
+                OracleAggregation::Fallback,
+                feed_addresses(&env, &[&fresh]),
+                121_000_i64
+            )
+        );
+    }
+
+    #[test]
+    fn aggregated_price_skips_unreadable_feeds() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        // a feed with no contract behind it and a feed missing the pair
+        let dead = OracleFeed {
+            source: OracleSource::Reflector,
+            address: Address::generate(&env),
+        };
+        let missing = MockReflectorClient::new(&env, &env.register(MockReflector, ()));
+        missing.initialize(&Asset::Other(symbol_short!("USD")), &6, &300);
+        let missing = OracleFeed {
+            source: OracleSource::Reflector,
+            address: missing.address,
+        };
+        let a = deploy_reflector_feed(&env, 120_000, 990);
+        let b = deploy_reflector_feed(&env, 125_000, 995);
+        let c = deploy_reflector_feed(&env, 119_000, 980);
+        let feeds = Vec::from_array(&env, [dead, a.clone(), missing, b.clone(), c.clone()]);
+
+        let consumer = env.register(MockReflector, ());
+        let oracle_price_data = env.as_contract(&consumer, || {
+            get_aggregated_oracle_price(
+                &env,
+                &feeds,
+                (symbol_short!("XLM"), symbol_short!("USD")),
+                env.ledger().timestamp(),
+                &OracleGuardRails::default().validity,
+            )
+        });
+
+        assert_eq!(oracle_price_data.price, 120_000);
+
+        let (_, _, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            <(OracleAggregation, Vec<Address>, i64)>::from_val(&env, &data),
+            (
+                OracleAggregation::Median,
+                feed_addresses(&env, &[&a, &b, &c]),
+                120_000_i64
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #21)")]
+    fn aggregated_price_fails_when_no_feed_is_readable() {
+        let env = Env::default();
+
+        let feeds = Vec::from_array(
+            &env,
+            [
+                OracleFeed {
+                    source: OracleSource::Reflector,
+                    address: Address::generate(&env),
+                },
+                OracleFeed {
+                    source: OracleSource::Reflector,
+                    address: Address::generate(&env),
+                },
+            ],
+        );
+
+        get_aggregated_oracle_price(
+            &env,
+            &feeds,
+            (symbol_short!("XLM"), symbol_short!("USD")),
+            0,
+            &OracleGuardRails::default().validity,
+        );
+    }
+
+    #[test]
+    fn aggregated_price_returns_primary_when_all_feeds_are_stale() {
+        let env = Env::default();
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let feeds = Vec::from_array(
+            &env,
+            [
+                deploy_reflector_feed(&env, 150_000, 100),
+                deploy_reflector_feed(&env, 160_000, 200),
+            ],
+        );
+
+        let oracle_price_data = get_aggregated_oracle_price(
+            &env,
+            &feeds,
+            (symbol_short!("XLM"), symbol_short!("USD")),
+            env.ledger().timestamp(),
+            &OracleGuardRails::default().validity,
+        );
+
+        assert_eq!(oracle_price_data.price, 150_000);
+        assert_eq!(oracle_price_data.delay, 900);
+    }
+}
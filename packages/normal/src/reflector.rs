@@ -0,0 +1,54 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol, Vec};
+
+// SEP-40 price feed interface implemented by Reflector
+// (https://github.com/reflector-network/reflector-contract)
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    /// Stellar asset contract address
+    Stellar(Address),
+    /// Off-chain asset ticker (e.g. "BTC")
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    /// precision: 10^decimals()
+    pub price: i128,
+    /// unix timestamp (seconds) the price was recorded at
+    pub timestamp: u64,
+}
+
+#[contractclient(name = "ReflectorClient")]
+pub trait ReflectorTrait {
+    /// Base asset all prices are quoted in
+    fn base(env: Env) -> Asset;
+
+    /// All assets quoted by the oracle
+    fn assets(env: Env) -> Vec<Asset>;
+
+    /// Number of decimal places used to represent prices
+    fn decimals(env: Env) -> u32;
+
+    /// Interval (seconds) between price updates
+    fn resolution(env: Env) -> u32;
+
+    fn price(env: Env, asset: Asset, timestamp: u64) -> Option<PriceData>;
+
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+
+    /// Most recent `records` prices, newest first
+    fn prices(env: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>>;
+
+    fn x_last_price(env: Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData>;
+
+    /// Most recent `records` cross prices, newest first
+    fn x_prices(
+        env: Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        records: u32,
+    ) -> Option<Vec<PriceData>>;
+}
@@ -0,0 +1,121 @@
+//! Mock contracts used by the contract test suites.
+//!
+//! Enable with `normal = { workspace = true, features = ["testutils"] }` under `[dev-dependencies]`.
+
+use soroban_sdk::{contract, contractimpl, contracttype, Env, Vec};
+
+use crate::reflector::{Asset, PriceData, ReflectorTrait};
+
+#[contracttype]
+#[derive(Clone)]
+enum MockReflectorKey {
+    Base,
+    Decimals,
+    Resolution,
+    Prices(Asset),
+}
+
+/// Minimal SEP-40 (Reflector) oracle whose prices are pushed manually by tests
+#[contract]
+pub struct MockReflector;
+
+#[contractimpl]
+impl MockReflector {
+    pub fn initialize(env: Env, base: Asset, decimals: u32, resolution: u32) {
+        env.storage().instance().set(&MockReflectorKey::Base, &base);
+        env.storage()
+            .instance()
+            .set(&MockReflectorKey::Decimals, &decimals);
+        env.storage()
+            .instance()
+            .set(&MockReflectorKey::Resolution, &resolution);
+    }
+
+    /// Records a new price for `asset`, expressed with `decimals()` precision
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        let key = MockReflectorKey::Prices(asset);
+        let mut prices: Vec<PriceData> =
+            env.storage().instance().get(&key).unwrap_or(Vec::new(&env));
+        prices.push_front(PriceData { price, timestamp });
+        env.storage().instance().set(&key, &prices);
+    }
+}
+
+#[contractimpl]
+impl ReflectorTrait for MockReflector {
+    fn base(env: Env) -> Asset {
+        env.storage()
+            .instance()
+            .get(&MockReflectorKey::Base)
+            .unwrap()
+    }
+
+    fn assets(env: Env) -> Vec<Asset> {
+        Vec::new(&env)
+    }
+
+    fn decimals(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&MockReflectorKey::Decimals)
+            .unwrap()
+    }
+
+    fn resolution(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&MockReflectorKey::Resolution)
+            .unwrap()
+    }
+
+    fn price(env: Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        let prices: Vec<PriceData> = env
+            .storage()
+            .instance()
+            .get(&MockReflectorKey::Prices(asset))?;
+        prices.iter().find(|p| p.timestamp <= timestamp)
+    }
+
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        Self::prices(env, asset, 1)?.first()
+    }
+
+    fn prices(env: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+        let prices: Vec<PriceData> = env
+            .storage()
+            .instance()
+            .get(&MockReflectorKey::Prices(asset))?;
+        if prices.is_empty() {
+            return None;
+        }
+        Some(prices.slice(0..records.min(prices.len())))
+    }
+
+    fn x_last_price(env: Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData> {
+        Self::x_prices(env, base_asset, quote_asset, 1)?.first()
+    }
+
+    fn x_prices(
+        env: Env,
+        base_asset: Asset,
+        quote_asset: Asset,
+        records: u32,
+    ) -> Option<Vec<PriceData>> {
+        let decimals = Self::decimals(env.clone());
+        let base_prices = Self::prices(env.clone(), base_asset, records)?;
+        let quote_prices = Self::prices(env.clone(), quote_asset, records)?;
+
+        let mut cross_prices = Vec::new(&env);
+        for (base, quote) in base_prices.iter().zip(quote_prices.iter()) {
+            if quote.price == 0 {
+                return None;
+            }
+            cross_prices.push_back(PriceData {
+                price: base.price * 10_i128.pow(decimals) / quote.price,
+                timestamp: base.timestamp.min(quote.timestamp),
+            });
+        }
+
+        Some(cross_prices)
+    }
+}
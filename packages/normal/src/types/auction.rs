@@ -35,3 +35,18 @@ pub struct Auction {
     pub start_price: u64,
     pub end_price: u64,
 }
+
+impl Default for Auction {
+    fn default() -> Self {
+        Auction {
+            amount: 0,
+            direction: OrderDirection::Sell,
+            location: AuctionLocation::Native,
+            duration: 0,
+            start_ts: 0,
+            total_auctioned: 0,
+            start_price: 0,
+            end_price: 0,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use soroban_sdk::{contracttype, Address, String, Vec};
 
-use crate::oracle::OracleSource;
+use crate::oracle::{OracleFeed, OracleSource};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -12,6 +12,39 @@ pub struct IndexAsset {
     pub last_updated_ts: i64,
 }
 
+/// Where component prices are read from when computing an index's NAV
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexPriceSource {
+    /// The synth market's oracle price
+    Oracle,
+    /// The synth market pool's sqrt_price, converted to USD with the index oracle
+    Pool,
+}
+
+/// How target weights are derived when a keeper rebalances an index
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RebalanceStrategy {
+    /// The weights stored in `component_assets`
+    FixedWeights,
+    /// Weighted by each synth's market cap (outstanding supply at its current price)
+    MarketCap,
+    /// The same weight for every component
+    EqualWeight,
+}
+
+/// Who can mint, redeem and hold an index token. Blacklisted accounts are
+/// blocked in every mode.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexAccessMode {
+    /// Open to everyone
+    Public,
+    /// Only accounts on the whitelist. Not allowed for public indexes.
+    WhitelistOnly,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct IndexParams {
@@ -23,7 +56,13 @@ pub struct IndexParams {
     pub quote_token: Address,
     pub oracle: Address,
     pub oracle_source: OracleSource,
+    /// Additional feeds aggregated with `oracle`, empty for a single feed
+    pub oracle_feeds: Vec<OracleFeed>,
+    pub price_source: IndexPriceSource,
+    /// Max seconds since a component price was last updated
+    pub max_price_staleness: u64,
     pub is_public: bool,
+    pub access_mode: IndexAccessMode,
     pub manager_fee_bps: i64,
     pub whitelist: Vec<Address>,
     pub blacklist: Vec<Address>,
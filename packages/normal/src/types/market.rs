@@ -1,6 +1,6 @@
-use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, String, Symbol, Vec};
 
-use crate::oracle::{OracleGuardRails, OracleSource};
+use crate::oracle::{OracleFeed, OracleGuardRails, OracleSource};
 
 use super::pool::PoolParams;
 
@@ -59,7 +59,8 @@ pub struct MarketParams {
     pub tier: SynthTier,
     pub oracle_source: OracleSource, // Oracle
     pub oracle: Address,
-    pub margin_ratio_initial: u32, // Margin
+    pub oracle_feeds: Vec<OracleFeed>, // aggregated with `oracle`, empty for a single feed
+    pub margin_ratio_initial: u32,     // Margin
     pub margin_ratio_maintenance: u32,
     pub imf_factor: u32,
     pub liquidation_penalty: u32, // Liquidation
@@ -84,3 +85,26 @@ pub struct MarketInfo {
     pub market_address: Address,
     pub market_response: MarketResponse,
 }
+
+/// Pricing inputs a market exposes to contracts built on top of it (e.g. indexes)
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketPriceData {
+    pub synth_token: Address,
+    /// Symbol of the asset tracked by the synthetic (e.g. BTC)
+    pub symbol: Symbol,
+    pub oracle: Address,
+    pub oracle_source: OracleSource,
+    /// precision: PRICE_PRECISION
+    pub last_oracle_price_twap: i64,
+    /// Pool price of the synthetic in the quote token
+    /// precision: Q64.64 sqrt price
+    pub sqrt_price: u128,
+    /// The last ts the pool price was updated
+    pub last_price_ts: u64,
+    /// Outstanding synthetic debt, i.e. the minted supply
+    /// precision: 10^synth_decimals
+    pub synth_supply: u128,
+    /// The synthetic token decimals
+    pub synth_decimals: u32,
+}